@@ -0,0 +1,62 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use lidar_utils::velodyne::{
+    consts::{BLOCKS_PER_PACKET, CHANNELS_PER_BLOCK},
+    Block, BlockIdentifier, Channel, Config, DataPacket, PointCloudConverter, ProductID,
+    ReturnMode, Vlp16_Strongest_PcdConverter,
+};
+
+fn new_packets() -> Vec<DataPacket> {
+    (0..100)
+        .map(|packet_idx| {
+            let mut blocks = [Block {
+                block_identifier: BlockIdentifier::Block0To31,
+                azimuth_count: 0,
+                channels: [Channel {
+                    distance: 5000,
+                    intensity: 0,
+                }; CHANNELS_PER_BLOCK],
+            }; BLOCKS_PER_PACKET];
+            blocks
+                .iter_mut()
+                .enumerate()
+                .for_each(|(block_idx, block)| {
+                    block.azimuth_count =
+                        ((packet_idx * BLOCKS_PER_PACKET + block_idx) * 40 % 36000) as u16;
+                });
+            DataPacket {
+                blocks,
+                timestamp: packet_idx as u32 * 1327,
+                return_mode: ReturnMode::StrongestReturn,
+                product_id: ProductID::VLP16,
+            }
+        })
+        .collect()
+}
+
+fn direction_cache_benchmark(c: &mut Criterion) {
+    let packets = new_packets();
+
+    c.bench_function("vlp16 convert", |b| {
+        let mut converter =
+            Vlp16_Strongest_PcdConverter::from_config(Config::vlp_16_strongest_return());
+        b.iter(|| {
+            packets.iter().for_each(|packet| {
+                black_box(converter.convert(packet).unwrap());
+            })
+        })
+    });
+
+    c.bench_function("vlp16 convert with direction cache", |b| {
+        let mut converter =
+            Vlp16_Strongest_PcdConverter::from_config(Config::vlp_16_strongest_return());
+        converter.enable_direction_cache();
+        b.iter(|| {
+            packets.iter().for_each(|packet| {
+                black_box(converter.convert(packet).unwrap());
+            })
+        })
+    });
+}
+
+criterion_group!(benches, direction_cache_benchmark);
+criterion_main!(benches);
@@ -0,0 +1,64 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use lidar_utils::velodyne::{
+    consts::{BLOCKS_PER_PACKET, CHANNELS_PER_BLOCK},
+    Block, BlockIdentifier, Channel, Config, DataPacket, PointCloudConverter, ProductID,
+    ReturnMode, Vlp16_Strongest_PcdConverter, Vlp32_Strongest_PcdConverter,
+};
+
+fn new_packets(product_id: ProductID) -> Vec<DataPacket> {
+    (0..100)
+        .map(|packet_idx| {
+            let mut blocks = [Block {
+                block_identifier: BlockIdentifier::Block0To31,
+                azimuth_count: 0,
+                channels: [Channel {
+                    distance: 5000,
+                    intensity: 0,
+                }; CHANNELS_PER_BLOCK],
+            }; BLOCKS_PER_PACKET];
+            blocks
+                .iter_mut()
+                .enumerate()
+                .for_each(|(block_idx, block)| {
+                    block.azimuth_count =
+                        ((packet_idx * BLOCKS_PER_PACKET + block_idx) * 40 % 36000) as u16;
+                });
+            DataPacket {
+                blocks,
+                timestamp: packet_idx as u32 * 1327,
+                return_mode: ReturnMode::StrongestReturn,
+                product_id,
+            }
+        })
+        .collect()
+}
+
+/// The scalar baseline is the "vlp16 convert" benchmark in direction_cache run without the
+/// `simd` feature.
+fn simd_benchmark(c: &mut Criterion) {
+    let vlp_16_packets = new_packets(ProductID::VLP16);
+    let vlp_32c_packets = new_packets(ProductID::VLP32C);
+
+    c.bench_function("vlp16 convert with simd", |b| {
+        let mut converter =
+            Vlp16_Strongest_PcdConverter::from_config(Config::vlp_16_strongest_return());
+        b.iter(|| {
+            vlp_16_packets.iter().for_each(|packet| {
+                black_box(converter.convert(packet).unwrap());
+            })
+        })
+    });
+
+    c.bench_function("vlp32c convert with simd", |b| {
+        let mut converter =
+            Vlp32_Strongest_PcdConverter::from_config(Config::vlp_32c_strongest_return());
+        b.iter(|| {
+            vlp_32c_packets.iter().for_each(|packet| {
+                black_box(converter.convert(packet).unwrap());
+            })
+        })
+    });
+}
+
+criterion_group!(benches, simd_benchmark);
+criterion_main!(benches);
@@ -9,7 +9,7 @@ pub use serde_big_array::big_array;
 pub use std::{
     borrow::Borrow,
     cmp::Ordering,
-    convert::TryInto,
+    convert::{TryFrom, TryInto},
     fmt::{self, Debug, Display, Formatter},
     fs::File,
     io::{prelude::*, BufReader, LineWriter, Lines},
@@ -20,14 +20,16 @@ pub use std::{
     ops::Range,
     path::Path,
     str::FromStr,
+    sync::mpsc::SyncSender,
     time::Duration,
 };
 pub use uom::{
     si::{
         angle::{degree, radian},
-        f64::{Angle, Length, Ratio, Time},
-        length::millimeter,
-        time::{microsecond, nanosecond},
+        f64::{Angle, Length, Ratio, ThermodynamicTemperature, Time},
+        length::{meter, millimeter},
+        thermodynamic_temperature::degree_celsius,
+        time::{microsecond, nanosecond, second},
     },
     Conversion,
 };
@@ -0,0 +1,73 @@
+//! Structured reports of recoverable anomalies found during conversion.
+
+use crate::common::*;
+
+/// A recoverable anomaly reported by converters.
+///
+/// Converters collect diagnostics only if they are enabled,
+/// so that the checks cost nothing by default.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Diagnostic {
+    /// Firings are missing between two consecutively received firings,
+    /// which usually indicates dropped packets.
+    DroppedFiring {
+        /// Timestamp of the last firing before the gap.
+        prev_timestamp: Time,
+        /// Timestamp of the first firing after the gap.
+        curr_timestamp: Time,
+        /// Estimated number of missing firings.
+        num_dropped: usize,
+    },
+    /// The return mode or product ID byte of a packet is not recognized.
+    InvalidFactoryBytes { return_mode: u8, product_id: u8 },
+    /// The block identifier of a packet is not recognized.
+    InvalidBlockIdentifier { block_idx: usize, value: u16 },
+    /// The encoder count exceeds the count of one revolution.
+    EncoderOverflow { encoder_count: u32 },
+    /// The column is not marked valid.
+    InvalidColumn { measurement_id: u16 },
+    /// The measurement ID is not less than the number of columns per revolution.
+    MeasurementIdOutOfRange {
+        measurement_id: u16,
+        columns_per_revolution: u16,
+    },
+    /// The column timestamp is earlier than that of the previous column in the same frame,
+    /// which indicates reordered columns.
+    NonMonotonicTimestamp {
+        measurement_id: u16,
+        /// Timestamp of the previous column in nanoseconds.
+        prev_timestamp: u64,
+        /// Timestamp of the column in nanoseconds.
+        curr_timestamp: u64,
+    },
+}
+
+/// Determines how packet anomalies are handled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum ValidationLevel {
+    /// Any anomaly is an error.
+    Strict,
+    /// Anomalies are reported as [Diagnostic]s, and the decoding continues
+    /// on a best-effort basis.
+    #[default]
+    Lenient,
+}
+
+impl ValidationLevel {
+    /// Fails on the anomaly in strict mode, or records it in lenient mode.
+    pub(crate) fn check(
+        &self,
+        diagnostics: Option<&mut Vec<Diagnostic>>,
+        diagnostic: Diagnostic,
+    ) -> Result<()> {
+        match self {
+            Self::Strict => bail!("invalid packet: {:?}", diagnostic),
+            Self::Lenient => {
+                if let Some(diagnostics) = diagnostics {
+                    diagnostics.push(diagnostic);
+                }
+                Ok(())
+            }
+        }
+    }
+}
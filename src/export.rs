@@ -0,0 +1,500 @@
+//! Common point accessors for exporting point clouds regardless of the sensor.
+
+use crate::{
+    common::*,
+    ouster::{frame_converter::Frame as OusterFrame, pcd_converter::Point as OusterPoint},
+    velodyne::{
+        frame_converter::PcdFrame,
+        point::{DualReturnPoint, LidarFrameMsg, PrimaryData, SingleReturnPoint},
+    },
+};
+
+/// Points that can be written by the exporters of this crate.
+pub trait ExportablePoint {
+    /// Returns the position in meters.
+    fn xyz(&self) -> [f64; 3];
+
+    /// Returns the intensity in the native scale of the sensor.
+    fn intensity(&self) -> f32;
+
+    /// Returns the ring, that is, the row index in elevation order.
+    fn export_ring(&self) -> u16;
+
+    /// Returns the timestamp if the point has one.
+    fn export_timestamp(&self) -> Option<Time>;
+
+    /// Returns true if the point has a return, that is, a nonzero distance.
+    fn has_return(&self) -> bool;
+}
+
+/// Returns the position of [PointData](crate::velodyne::point::PointData) in meters.
+fn position_in_meters(position: &[Length; 3]) -> [f64; 3] {
+    let [x, y, z] = *position;
+    [x.get::<meter>(), y.get::<meter>(), z.get::<meter>()]
+}
+
+impl ExportablePoint for SingleReturnPoint {
+    fn xyz(&self) -> [f64; 3] {
+        position_in_meters(&self.data.position)
+    }
+
+    fn intensity(&self) -> f32 {
+        self.data.intensity as f32
+    }
+
+    fn export_ring(&self) -> u16 {
+        self.row_idx() as u16
+    }
+
+    fn export_timestamp(&self) -> Option<Time> {
+        Some(self.timestamp)
+    }
+
+    fn has_return(&self) -> bool {
+        self.data.distance > Length::new::<meter>(0.0)
+    }
+}
+
+/// The strongest return is exported for dual return points.
+impl ExportablePoint for DualReturnPoint {
+    fn xyz(&self) -> [f64; 3] {
+        position_in_meters(&self.primary_data().position)
+    }
+
+    fn intensity(&self) -> f32 {
+        self.primary_data().intensity as f32
+    }
+
+    fn export_ring(&self) -> u16 {
+        self.row_idx() as u16
+    }
+
+    fn export_timestamp(&self) -> Option<Time> {
+        Some(self.timestamp)
+    }
+
+    fn has_return(&self) -> bool {
+        self.primary_data().distance > Length::new::<meter>(0.0)
+    }
+}
+
+/// The intensity is the number of signal photons, and the ring is the laser ID,
+/// which is ordered from top to bottom on Ouster sensors.
+impl ExportablePoint for OusterPoint {
+    fn xyz(&self) -> [f64; 3] {
+        position_in_meters(&self.point)
+    }
+
+    fn intensity(&self) -> f32 {
+        self.signal_photons as f32
+    }
+
+    fn export_ring(&self) -> u16 {
+        self.laser_id as u16
+    }
+
+    fn export_timestamp(&self) -> Option<Time> {
+        Some(self.timestamp)
+    }
+
+    fn has_return(&self) -> bool {
+        self.distance > Length::new::<meter>(0.0)
+    }
+}
+
+/// A type-erased point cloud, so that clouds from different sensors can be stored in one
+/// collection, such as `Vec<Box<dyn DynPointCloud>>`.
+///
+/// The points are indexed in the storage order of the cloud. The accessors panic if the
+/// index is out of bound.
+pub trait DynPointCloud {
+    /// Returns the number of points.
+    fn len(&self) -> usize;
+
+    /// Returns true if the cloud has no points.
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the position of the point in meters.
+    fn point(&self, index: usize) -> [f64; 3];
+
+    /// Returns the intensity of the point in the native scale of the sensor.
+    fn intensity(&self, index: usize) -> f32;
+}
+
+impl<P> DynPointCloud for PcdFrame<P>
+where
+    P: Copy + ExportablePoint,
+{
+    fn len(&self) -> usize {
+        self.data.len()
+    }
+
+    fn point(&self, index: usize) -> [f64; 3] {
+        self.data[index].xyz()
+    }
+
+    fn intensity(&self, index: usize) -> f32 {
+        self.data[index].intensity()
+    }
+}
+
+impl DynPointCloud for OusterFrame {
+    fn len(&self) -> usize {
+        self.points.len()
+    }
+
+    fn point(&self, index: usize) -> [f64; 3] {
+        self.points[index].xyz()
+    }
+
+    fn intensity(&self, index: usize) -> f32 {
+        self.points[index].intensity()
+    }
+}
+
+/// Packs the points into a contiguous buffer for GPU upload.
+///
+/// Each point takes 4 floats in the order `[x, y, z, intensity]`, that is,
+/// a stride of 16 bytes without padding. The position is in meters. The buffer has
+/// exactly `4 * points.len()` floats in the same order as `points`.
+pub fn to_interleaved_f32<P>(points: &[P]) -> Vec<f32>
+where
+    P: ExportablePoint,
+{
+    points
+        .iter()
+        .flat_map(|point| {
+            let [x, y, z] = point.xyz();
+            [x as f32, y as f32, z as f32, point.intensity()]
+        })
+        .collect()
+}
+
+/// Returns parallel vectors of positions in meters and intensities of the points with
+/// returns, which are the array shapes expected by Python bindings such as Open3D.
+pub fn to_xyz_intensity_vecs<P>(points: &[P]) -> (Vec<[f64; 3]>, Vec<f64>)
+where
+    P: ExportablePoint,
+{
+    points
+        .iter()
+        .filter(|point| point.has_return())
+        .map(|point| (point.xyz(), point.intensity() as f64))
+        .unzip()
+}
+
+/// Writes the points as CSV with the header `x,y,z,intensity,ring,timestamp`.
+///
+/// The position is in meters and the timestamp is in seconds, which is left empty for
+/// points without timestamps. There is one line per point ordered as `points`, including
+/// the points without returns.
+pub fn write_csv<P, W>(points: &[P], mut writer: W) -> Result<()>
+where
+    P: ExportablePoint,
+    W: Write,
+{
+    writeln!(writer, "x,y,z,intensity,ring,timestamp")?;
+    for point in points {
+        let [x, y, z] = point.xyz();
+        let timestamp = point
+            .export_timestamp()
+            .map(|timestamp| timestamp.get::<second>().to_string())
+            .unwrap_or_default();
+        writeln!(
+            writer,
+            "{},{},{},{},{},{}",
+            x,
+            y,
+            z,
+            point.intensity(),
+            point.export_ring(),
+            timestamp
+        )?;
+    }
+    Ok(())
+}
+
+/// Converts the points into [pcd-rs](pcd_rs) records along with the schema.
+///
+/// The fields are `x`, `y`, `z` in meters as `F32`, `intensity` as `F32`,
+/// `ring` as `U16` and `timestamp` in seconds as `F64`, which is NaN for
+/// points without timestamps. The records are ordered as `points`. Unlike
+/// [PcdFrame::to_pcd_rs], the intensity is not a byte since the photon counts of
+/// Ouster sensors exceed 255.
+#[cfg(feature = "pcd-rs")]
+pub fn to_pcd_rs<P>(points: &[P]) -> (pcd_rs::Schema, Vec<pcd_rs::DynRecord>)
+where
+    P: ExportablePoint,
+{
+    use pcd_rs::{DynRecord, Field, Schema, ValueKind};
+
+    let schema: Schema = vec![
+        ("x", ValueKind::F32, 1),
+        ("y", ValueKind::F32, 1),
+        ("z", ValueKind::F32, 1),
+        ("intensity", ValueKind::F32, 1),
+        ("ring", ValueKind::U16, 1),
+        ("timestamp", ValueKind::F64, 1),
+    ]
+    .into_iter()
+    .collect();
+
+    let records = points
+        .iter()
+        .map(|point| {
+            let [x, y, z] = point.xyz();
+            let timestamp = point
+                .export_timestamp()
+                .map(|timestamp| timestamp.get::<second>())
+                .unwrap_or(f64::NAN);
+            DynRecord(vec![
+                Field::F32(vec![x as f32]),
+                Field::F32(vec![y as f32]),
+                Field::F32(vec![z as f32]),
+                Field::F32(vec![point.intensity()]),
+                Field::U16(vec![point.export_ring()]),
+                Field::F64(vec![timestamp]),
+            ])
+        })
+        .collect();
+
+    (schema, records)
+}
+
+/// Converts the points into an Arrow [RecordBatch](arrow::record_batch::RecordBatch)
+/// for analytical queries.
+///
+/// The columns are `x`, `y`, `z` in meters as `Float64`, `intensity` as `Float32`,
+/// `ring` as `UInt16` and `timestamp` in seconds as `Float64`, which is null for
+/// points without timestamps. The rows are ordered as `points`.
+#[cfg(feature = "arrow")]
+pub fn to_record_batch<P>(points: &[P]) -> arrow::record_batch::RecordBatch
+where
+    P: ExportablePoint,
+{
+    use arrow::{
+        array::{ArrayRef, Float32Array, Float64Array, UInt16Array},
+        datatypes::{DataType, Field, Schema},
+        record_batch::RecordBatch,
+    };
+    use std::sync::Arc;
+
+    let schema = Schema::new(vec![
+        Field::new("x", DataType::Float64, false),
+        Field::new("y", DataType::Float64, false),
+        Field::new("z", DataType::Float64, false),
+        Field::new("intensity", DataType::Float32, false),
+        Field::new("ring", DataType::UInt16, false),
+        Field::new("timestamp", DataType::Float64, true),
+    ]);
+
+    let xyz: Vec<_> = points.iter().map(|point| point.xyz()).collect();
+    let coordinate = |axis: usize| -> ArrayRef {
+        Arc::new(Float64Array::from_iter_values(
+            xyz.iter().map(|position| position[axis]),
+        ))
+    };
+    let intensity = Float32Array::from_iter_values(points.iter().map(|point| point.intensity()));
+    let ring = UInt16Array::from_iter_values(points.iter().map(|point| point.export_ring()));
+    let timestamp: Float64Array = points
+        .iter()
+        .map(|point| {
+            point
+                .export_timestamp()
+                .map(|timestamp| timestamp.get::<second>())
+        })
+        .collect();
+
+    RecordBatch::try_new(
+        Arc::new(schema),
+        vec![
+            coordinate(0),
+            coordinate(1),
+            coordinate(2),
+            Arc::new(intensity),
+            Arc::new(ring),
+            Arc::new(timestamp),
+        ],
+    )
+    .unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::velodyne::{
+        point::{LidarFrameEntry, PointData, ReturnKind},
+        test_support::PointBuilder,
+    };
+
+    fn position(xyz: [f64; 3]) -> [Length; 3] {
+        let [x, y, z] = xyz;
+        [
+            Length::new::<meter>(x),
+            Length::new::<meter>(y),
+            Length::new::<meter>(z),
+        ]
+    }
+
+    fn point_data(xyz: [f64; 3], intensity: u8) -> PointData {
+        PointData {
+            distance: Length::new::<meter>(1.0),
+            intensity,
+            intensity_value: intensity as f64,
+            position: position(xyz),
+            suspect: false,
+        }
+    }
+
+    /// Writes the points as CSV, and compares the parsed lines with the points.
+    fn assert_round_trip<P>(points: &[P])
+    where
+        P: ExportablePoint,
+    {
+        let mut buffer = vec![];
+        write_csv(points, &mut buffer).unwrap();
+        let text = String::from_utf8(buffer).unwrap();
+        let mut lines = text.lines();
+        assert_eq!(lines.next(), Some("x,y,z,intensity,ring,timestamp"));
+
+        let rows: Vec<Vec<f64>> = lines
+            .map(|line| {
+                line.split(',')
+                    .map(|value| value.parse().unwrap())
+                    .collect()
+            })
+            .collect();
+        assert_eq!(rows.len(), points.len());
+        izip!(rows, points).for_each(|(row, point)| {
+            let [x, y, z] = point.xyz();
+            let timestamp = point.export_timestamp().unwrap().get::<second>();
+            assert_eq!(
+                row,
+                vec![
+                    x,
+                    y,
+                    z,
+                    point.intensity() as f64,
+                    point.export_ring() as f64,
+                    timestamp
+                ]
+            );
+        });
+
+        // the interleaved buffer follows the same order
+        let buffer = to_interleaved_f32(points);
+        assert_eq!(buffer.len(), points.len() * 4);
+        izip!(buffer.chunks(4), points).for_each(|(chunk, point)| {
+            let [x, y, z] = point.xyz();
+            assert_eq!(chunk, &[x as f32, y as f32, z as f32, point.intensity()]);
+        });
+    }
+
+    #[test]
+    fn exportable_point_test() {
+        let single = PointBuilder::new()
+            .laser_id(3)
+            .timestamp(Time::new::<second>(1.5))
+            .position([1.0, 2.0, 3.0])
+            .distance(Length::new::<meter>(1.0))
+            .intensity(7)
+            .entry(5, 0)
+            .build();
+        assert_eq!(single.xyz(), [1.0, 2.0, 3.0]);
+        assert_eq!(single.intensity(), 7.0);
+        assert_eq!(single.export_ring(), 5);
+        assert_eq!(single.export_timestamp(), Some(Time::new::<second>(1.5)));
+        assert!(single.has_return());
+        assert_round_trip(&[single, single]);
+
+        let dual = DualReturnPoint {
+            laser_id: 3,
+            timestamp: Time::new::<second>(2.5),
+            firing_time_offset: Time::new::<second>(0.0),
+            original_azimuth_angle: Angle::new::<radian>(0.0),
+            corrected_azimuth_angle: Angle::new::<radian>(0.0),
+            strongest_return_data: point_data([4.0, 5.0, 6.0], 9),
+            last_return_data: point_data([7.0, 8.0, 9.0], 1),
+            lidar_frame_entry: LidarFrameEntry {
+                row_idx: 2,
+                col_idx: 0,
+            },
+            return_kind: ReturnKind::Both,
+        };
+        assert_eq!(dual.xyz(), [4.0, 5.0, 6.0]);
+        assert_eq!(dual.intensity(), 9.0);
+        assert_eq!(dual.export_ring(), 2);
+        assert_eq!(dual.export_timestamp(), Some(Time::new::<second>(2.5)));
+        assert_round_trip(&[dual]);
+
+        let ouster = OusterPoint {
+            timestamp: Time::new::<second>(3.5),
+            azimuth_angle: Angle::new::<radian>(0.0),
+            distance: Length::new::<meter>(1.0),
+            reflectivity: 11,
+            signal_photons: 300,
+            noise_photons: 13,
+            laser_id: 63,
+            point: position([-1.0, -2.0, -3.0]),
+        };
+        assert_eq!(ouster.xyz(), [-1.0, -2.0, -3.0]);
+        assert_eq!(ouster.intensity(), 300.0);
+        assert_eq!(ouster.export_ring(), 63);
+        assert_eq!(ouster.export_timestamp(), Some(Time::new::<second>(3.5)));
+        assert_round_trip(&[ouster.clone(), ouster.clone()]);
+
+        // the points without returns are skipped in the vectors
+        let mut empty = ouster.clone();
+        empty.distance = Length::new::<meter>(0.0);
+        assert!(!empty.has_return());
+        let (xyz, intensity) = to_xyz_intensity_vecs(&[ouster, empty]);
+        assert_eq!(xyz, vec![[-1.0, -2.0, -3.0]]);
+        assert_eq!(intensity, vec![300.0]);
+    }
+
+    #[test]
+    fn dyn_point_cloud_test() {
+        let velodyne_point = PointBuilder::new()
+            .position([1.0, 2.0, 3.0])
+            .distance(Length::new::<meter>(1.0))
+            .intensity(7)
+            .build();
+        let velodyne_frame = PcdFrame {
+            height: 1,
+            width: 2,
+            data: vec![velodyne_point; 2],
+        };
+
+        let ouster_point = OusterPoint {
+            timestamp: Time::new::<second>(0.0),
+            azimuth_angle: Angle::new::<radian>(0.0),
+            distance: Length::new::<meter>(1.0),
+            reflectivity: 11,
+            signal_photons: 300,
+            noise_photons: 13,
+            laser_id: 0,
+            point: position([-1.0, -2.0, -3.0]),
+        };
+        let ouster_frame = OusterFrame {
+            frame_id: 0,
+            skipped_frame_ids: 0..0,
+            timestamps: vec![(0, 0)],
+            points: vec![ouster_point; 3],
+            columns_per_revolution: 1024,
+        };
+
+        let clouds: Vec<Box<dyn DynPointCloud>> =
+            vec![Box::new(velodyne_frame), Box::new(ouster_frame)];
+        assert_eq!(
+            clouds.iter().map(|cloud| cloud.len()).collect::<Vec<_>>(),
+            vec![2, 3]
+        );
+        assert!(clouds.iter().all(|cloud| !cloud.is_empty()));
+        assert_eq!(clouds[0].point(1), [1.0, 2.0, 3.0]);
+        assert_eq!(clouds[0].intensity(1), 7.0);
+        assert_eq!(clouds[1].point(2), [-1.0, -2.0, -3.0]);
+        assert_eq!(clouds[1].intensity(2), 300.0);
+    }
+}
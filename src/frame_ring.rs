@@ -0,0 +1,91 @@
+//! A fixed-capacity buffer of recent frames.
+
+use std::collections::VecDeque;
+
+/// Keeps the last `capacity` frames, for example, for visualization.
+///
+/// Pushing a frame to a full ring drops the oldest frame.
+#[derive(Debug, Clone)]
+pub struct FrameRing<F> {
+    capacity: usize,
+    frames: VecDeque<F>,
+}
+
+impl<F> FrameRing<F> {
+    /// Creates an empty ring. It panics if `capacity` is zero.
+    pub fn new(capacity: usize) -> Self {
+        assert!(capacity > 0, "the capacity must be nonzero");
+        Self {
+            capacity,
+            frames: VecDeque::with_capacity(capacity),
+        }
+    }
+
+    /// Pushes a frame, and returns the evicted oldest frame if the ring is full.
+    pub fn push(&mut self, frame: F) -> Option<F> {
+        let evicted = if self.frames.len() == self.capacity {
+            self.frames.pop_back()
+        } else {
+            None
+        };
+        self.frames.push_front(frame);
+        evicted
+    }
+
+    /// Iterates the frames from the newest to the oldest.
+    pub fn iter(&self) -> impl Iterator<Item = &F> {
+        self.frames.iter()
+    }
+
+    /// Returns the most recently pushed frame.
+    pub fn latest(&self) -> Option<&F> {
+        self.frames.front()
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.frames.clear();
+    }
+}
+
+impl<F> Extend<F> for FrameRing<F> {
+    fn extend<I>(&mut self, frames: I)
+    where
+        I: IntoIterator<Item = F>,
+    {
+        frames.into_iter().for_each(|frame| {
+            self.push(frame);
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn frame_ring_test() {
+        let capacity = 4;
+        let mut ring = FrameRing::new(capacity);
+        let evicted: Vec<_> = (0..(capacity + 2))
+            .filter_map(|frame| ring.push(frame))
+            .collect();
+
+        // the two oldest frames are evicted
+        assert_eq!(evicted, vec![0, 1]);
+        assert_eq!(ring.len(), capacity);
+        assert_eq!(ring.iter().copied().collect::<Vec<_>>(), vec![5, 4, 3, 2]);
+        assert_eq!(ring.latest(), Some(&5));
+    }
+}
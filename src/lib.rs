@@ -10,5 +10,10 @@
 extern crate pcap;
 
 mod common;
+pub mod diagnostic;
+pub mod export;
+pub mod frame_ring;
 pub mod ouster;
+#[cfg(feature = "pcap-file")]
+pub mod pcap_file_reader;
 pub mod velodyne;
@@ -21,6 +21,18 @@ pub struct Config {
     #[derivative(Debug(format_with = "self::large_array_fmt"))]
     pub beam_azimuth_angle_corrections: [R64; PIXELS_PER_COLUMN],
     pub lidar_mode: LidarMode,
+    /// The `[start, end]` azimuth range in millidegrees where the sensor outputs valid columns.
+    /// It covers full revolution if not set.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub azimuth_window: Option<[u32; 2]>,
+    /// Overrides the columns per revolution implied by `lidar_mode`, which is
+    /// used by legacy firmware. It is one of 512, 1024 or 2048 if set.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub columns_per_rev: Option<u16>,
+    /// Indicates the reflectivity field is calibrated, which is given by the
+    /// `calibration_status` of metadata on firmware 2.1 or later.
+    #[serde(default)]
+    pub reflectivity_calibrated: bool,
 }
 
 impl Config {
@@ -36,6 +48,9 @@ impl Config {
                 mem::transmute(beam_azimuth_angle_corrections)
             },
             lidar_mode,
+            azimuth_window: None,
+            columns_per_rev: None,
+            reflectivity_calibrated: false,
         }
     }
 
@@ -58,6 +73,29 @@ impl Config {
         Ok(ret)
     }
 
+    /// Parses from sensor metadata JSON string.
+    ///
+    /// It accepts either the flat schema with top-level beam intrinsics, or the nested
+    /// schema with `beam_intrinsics`, `lidar_intrinsics` and `config_params` keys.
+    pub fn from_metadata_json_str(data: &str) -> Result<Config> {
+        let value: serde_json::Value = serde_json::from_str(data)?;
+
+        let flat_err = match serde_json::from_value::<Config>(value.clone()) {
+            Ok(config) => return Ok(config),
+            Err(err) => err,
+        };
+        let nested_err = match serde_json::from_value::<NestedMetadata>(value) {
+            Ok(metadata) => return Ok(metadata.into()),
+            Err(err) => err,
+        };
+
+        bail!(
+            "the metadata matches neither the flat schema ({}) nor the nested schema ({})",
+            flat_err,
+            nested_err
+        )
+    }
+
     /// Sets `beam_azimuth_angle_corrections` field.
     pub fn beam_azimuth_angle_corrections(
         &mut self,
@@ -77,6 +115,46 @@ impl Config {
         self.lidar_mode = lidar_mode;
     }
 
+    /// Sets `columns_per_rev` field. It fails if the value is not 512, 1024 or 2048.
+    pub fn columns_per_rev(&mut self, columns_per_rev: u16) -> Result<()> {
+        ensure!(
+            matches!(columns_per_rev, 512 | 1024 | 2048),
+            "columns_per_rev must be 512, 1024 or 2048, but get {}",
+            columns_per_rev
+        );
+        self.columns_per_rev = Some(columns_per_rev);
+        Ok(())
+    }
+
+    /// Sets `reflectivity_calibrated` field.
+    pub fn reflectivity_calibrated(&mut self, reflectivity_calibrated: bool) {
+        self.reflectivity_calibrated = reflectivity_calibrated;
+    }
+
+    /// Returns the number of columns per revolution, which is `columns_per_rev` if set,
+    /// or determined by `lidar_mode` otherwise. It is 1024 for the default config.
+    pub fn columns_per_revolution(&self) -> u16 {
+        self.columns_per_rev
+            .unwrap_or_else(|| self.lidar_mode.columns_per_revolution())
+    }
+
+    /// Sets `azimuth_window` field from `(start, end)` angles.
+    pub fn azimuth_window(&mut self, azimuth_window: (Angle, Angle)) {
+        let (start, end) = azimuth_window;
+        let to_millidegrees = |angle: Angle| (angle.get::<degree>() * 1000.0).round() as u32;
+        self.azimuth_window = Some([to_millidegrees(start), to_millidegrees(end)]);
+    }
+
+    /// Returns the `(start, end)` angles of azimuth window. It returns
+    /// the full revolution if the window is not set.
+    pub fn azimuth_window_angles(&self) -> (Angle, Angle) {
+        let [start, end] = self.azimuth_window.unwrap_or([0, 360_000]);
+        (
+            Angle::new::<degree>(start as f64 / 1000.0),
+            Angle::new::<degree>(end as f64 / 1000.0),
+        )
+    }
+
     /// Create default configuration for Ouster OS-1.
     pub fn os_1_config() -> Self {
         // From firmware 1.12.0
@@ -89,6 +167,78 @@ impl Config {
                 mem::transmute(beam_azimuth_angle_corrections)
             },
             lidar_mode: LidarMode::Mode1024x10,
+            azimuth_window: None,
+            columns_per_rev: None,
+            reflectivity_calibrated: false,
+        }
+    }
+}
+
+/// The metadata schema that nests beam intrinsics and lidar mode.
+#[derive(Deserialize)]
+struct NestedMetadata {
+    beam_intrinsics: BeamIntrinsics,
+    #[allow(dead_code)]
+    lidar_intrinsics: serde_json::Value,
+    config_params: ConfigParams,
+    #[serde(default)]
+    calibration_status: Option<CalibrationStatus>,
+}
+
+#[derive(Deserialize)]
+struct BeamIntrinsics {
+    #[serde(with = "BigArray")]
+    beam_altitude_angles: [R64; PIXELS_PER_COLUMN],
+    #[serde(with = "BigArray")]
+    beam_azimuth_angles: [R64; PIXELS_PER_COLUMN],
+}
+
+#[derive(Deserialize)]
+struct ConfigParams {
+    lidar_mode: LidarMode,
+    #[serde(default)]
+    azimuth_window: Option<[u32; 2]>,
+}
+
+#[derive(Deserialize)]
+struct CalibrationStatus {
+    reflectivity: ReflectivityCalibrationStatus,
+}
+
+#[derive(Deserialize)]
+struct ReflectivityCalibrationStatus {
+    valid: bool,
+}
+
+impl From<NestedMetadata> for Config {
+    fn from(metadata: NestedMetadata) -> Self {
+        let NestedMetadata {
+            beam_intrinsics:
+                BeamIntrinsics {
+                    beam_altitude_angles,
+                    beam_azimuth_angles,
+                },
+            config_params:
+                ConfigParams {
+                    lidar_mode,
+                    azimuth_window,
+                },
+            calibration_status,
+            ..
+        } = metadata;
+
+        Config {
+            beam_altitude_angles,
+            beam_azimuth_angle_corrections: beam_azimuth_angles,
+            lidar_mode,
+            azimuth_window,
+            columns_per_rev: None,
+            reflectivity_calibrated: matches!(
+                calibration_status,
+                Some(CalibrationStatus {
+                    reflectivity: ReflectivityCalibrationStatus { valid: true }
+                })
+            ),
         }
     }
 }
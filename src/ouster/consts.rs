@@ -9,6 +9,9 @@ pub const PIXELS_PER_COLUMN: usize = 64;
 /// Number of columns in one packet, where each column represents a vertical scan.
 pub const COLUMNS_PER_PACKET: usize = 16;
 
+/// Range resolution in millimeters of the low data rate `RNG15_RFL8_NIR8` profile.
+pub const COMPACT_RANGE_RESOLUTION_MILLIS: u32 = 8;
+
 /// Altitude angles of OS-1.
 pub const OS_1_BEAM_ALTITUDE_DEGREES: [f64; 64] = [
     17.042, 16.427, 15.872, 15.324, 14.851, 14.269, 13.733, 13.18, 12.713, 12.136, 11.599, 11.067,
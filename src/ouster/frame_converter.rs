@@ -2,11 +2,14 @@
 
 use super::{
     config::Config,
-    consts::COLUMNS_PER_PACKET,
+    consts::{COLUMNS_PER_PACKET, PIXELS_PER_COLUMN},
     packet::{Column, Packet},
     pcd_converter::{Point, PointCloudConverter},
 };
-use crate::common::*;
+use crate::{
+    common::*,
+    diagnostic::{Diagnostic, ValidationLevel},
+};
 
 /// A frame is a collection of points gathered in one
 /// LIDAR rotation.
@@ -20,6 +23,94 @@ pub struct Frame {
     pub timestamps: Vec<(u16, u64)>,
     /// Point cloud data.
     pub points: Vec<Point>,
+    /// The number of columns per revolution, which determines the azimuth step of columns.
+    pub columns_per_revolution: u16,
+}
+
+/// The percentiles clipped by [Frame::near_ir_autocontrast].
+const AUTOCONTRAST_PERCENTILES: (f64, f64) = (0.01, 0.99);
+
+impl Frame {
+    /// Arranges the values of points into a row-major image of 64 rows by the number of
+    /// columns per revolution, where the rows are ordered by laser ID.
+    ///
+    /// The pixels of a column point to different azimuths due to the beam azimuth
+    /// corrections. Each pixel is placed at the column `(measurement_id + shift) % width`,
+    /// where the shift is the number of azimuth steps from the nominal azimuth of the
+    /// column to the azimuth of the pixel, so that the image is rectified. The pixels of
+    /// missing columns, including those outside the azimuth window, are left default.
+    pub fn destagger<T, F>(&self, value: F) -> Vec<T>
+    where
+        T: Copy + Default,
+        F: Fn(&Point) -> T,
+    {
+        let width = self.columns_per_revolution as usize;
+        let mut image = vec![T::default(); width * PIXELS_PER_COLUMN];
+        let step = 360.0 / self.columns_per_revolution as f64;
+
+        izip!(
+            self.timestamps.iter(),
+            self.points.chunks(PIXELS_PER_COLUMN)
+        )
+        .filter(|(_, column)| !column.is_empty())
+        .for_each(|(&(measurement_id, _), column)| {
+            let nominal_azimuth = measurement_id as f64 * step;
+            column.iter().enumerate().for_each(|(row_idx, point)| {
+                let diff = (point.azimuth_angle.get::<degree>() - nominal_azimuth + 180.0)
+                    .rem_euclid(360.0)
+                    - 180.0;
+                let shift = (diff / step).round() as isize;
+                let col_idx = (measurement_id as isize + shift).rem_euclid(width as isize) as usize;
+                image[row_idx * width + col_idx] = value(point);
+            });
+        });
+
+        image
+    }
+
+    /// Renders the near-infrared ambient image with contrast stretching, for example,
+    /// for passive imaging.
+    ///
+    /// The noise photons, which measure the ambient near-infrared light, are linearly
+    /// mapped from the 1st–99th percentile range to 0–255 and clipped. The image is
+    /// [destaggered](Frame::destagger) in row-major order.
+    pub fn near_ir_autocontrast(&self) -> Vec<u8> {
+        let image = self.destagger(|point| point.noise_photons);
+        if image.is_empty() {
+            return vec![];
+        }
+
+        let mut sorted = image.clone();
+        sorted.sort_unstable();
+        let percentile = |q: f64| sorted[((sorted.len() - 1) as f64 * q).round() as usize] as f64;
+        let (lower, upper) = AUTOCONTRAST_PERCENTILES;
+        let (lower, upper) = (percentile(lower), percentile(upper));
+
+        image
+            .into_iter()
+            .map(|value| {
+                if upper <= lower {
+                    return 0;
+                }
+                let ratio = (value as f64 - lower) / (upper - lower);
+                (ratio * 255.0).round().clamp(0.0, 255.0) as u8
+            })
+            .collect()
+    }
+}
+
+/// Decides when [FrameConverter] closes a frame.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum FrameBoundary {
+    /// Outputs a frame once the last measurement ID of a revolution
+    /// is received, or the frame ID changes.
+    #[default]
+    MeasurementId,
+    /// Outputs a frame only when the frame ID changes. It tolerates
+    /// dropped columns at the end of revolution, and handles the frame ID
+    /// that wraps around from 65535 to 0. The skipped frame IDs are not
+    /// reported if they cross the wrap-around point.
+    FrameId,
 }
 
 /// It reads [columns](Column) of sensor data, and
@@ -33,7 +124,9 @@ pub struct Frame {
 #[derive(Debug)]
 pub struct FrameConverter {
     pcd_converter: PointCloudConverter,
+    frame_boundary: FrameBoundary,
     state: Option<FrameConverterState>,
+    diagnostics: Option<Vec<Diagnostic>>,
 }
 
 impl FrameConverter {
@@ -41,13 +134,39 @@ impl FrameConverter {
     pub fn from_config(config: Config) -> Self {
         Self {
             pcd_converter: PointCloudConverter::from_config(config),
+            frame_boundary: FrameBoundary::default(),
             state: None,
+            diagnostics: None,
         }
     }
 
+    /// Sets how invalid columns, out-of-range measurement IDs and encoder overflows are handled.
+    ///
+    /// See [PointCloudConverter::set_validation_level].
+    pub fn set_validation_level(&mut self, level: ValidationLevel) {
+        self.pcd_converter.set_validation_level(level);
+    }
+
+    /// Starts collecting [Diagnostic]s of the columns rejected in lenient mode.
+    pub fn enable_diagnostics(&mut self) {
+        self.diagnostics.get_or_insert_with(Vec::new);
+    }
+
+    /// Takes the collected diagnostics.
+    pub fn take_diagnostics(&mut self) -> Vec<Diagnostic> {
+        self.diagnostics.as_mut().map(mem::take).unwrap_or_default()
+    }
+
+    /// Sets the condition to output a frame.
+    pub fn frame_boundary(&mut self, frame_boundary: FrameBoundary) {
+        self.frame_boundary = frame_boundary;
+    }
+
     /// Returns the resolution in `(width, height)` pair.
+    ///
+    /// The width counts the columns within the azimuth window.
     pub fn resolution(&self) -> (u16, u16) {
-        let width = self.pcd_converter.columns_per_revolution();
+        let width = self.pcd_converter.columns_per_frame();
         (width, 64)
     }
 
@@ -56,15 +175,35 @@ impl FrameConverter {
         self.pcd_converter.columns_per_revolution()
     }
 
+    /// Returns the number of columns per frame within the azimuth window.
+    pub fn columns_per_frame(&self) -> u16 {
+        self.pcd_converter.columns_per_frame()
+    }
+
     /// Pushes new [Column] to converter.
     pub fn push_column(&mut self, column: &Column) -> Result<Vec<Frame>> {
+        // skip the corrupted column before it is assembled into frames
+        if !self
+            .pcd_converter
+            .validate_measurement_id(column.measurement_id, self.diagnostics.as_mut())?
+        {
+            return Ok(vec![]);
+        }
+        if !self.validate_timestamp(column)? {
+            return Ok(vec![]);
+        }
+
+        if let FrameBoundary::FrameId = self.frame_boundary {
+            return self.push_column_by_frame_id(column);
+        }
+
         let curr_fid = column.frame_id;
         let curr_mid = column.measurement_id;
         let curr_ts = column.timestamp;
         let curr_points = self.pcd_converter.column_to_points(column)?;
 
-        // If received column is not valid, update last_{fid,mid} only
-        if !column.valid() {
+        // If received column is not valid or has no points, update last_{fid,mid} only
+        if !column.valid() || curr_points.is_empty() {
             let (frame_opt, new_state) = match self.state.take() {
                 Some(mut state) => {
                     let frame_opt = match state.last_fid.cmp(&curr_fid) {
@@ -112,6 +251,7 @@ impl FrameConverter {
                                 timestamps
                             },
                             points: curr_points,
+                            columns_per_revolution: self.pcd_converter.columns_per_revolution(),
                         };
                         let mut new_state = FrameConverterState {
                             last_mid: curr_mid,
@@ -121,7 +261,7 @@ impl FrameConverter {
 
                         // Produce frame if measurement ID is exactly the latest ID of frame
                         let (second_frame_opt, new_state) =
-                            if curr_mid + 1 == self.pcd_converter.columns_per_revolution() {
+                            if curr_mid == self.pcd_converter.last_measurement_id() {
                                 (Some(second_frame), new_state)
                             } else {
                                 new_state.frame = Some(second_frame);
@@ -159,7 +299,7 @@ impl FrameConverter {
                         };
 
                         let (frame_opt, new_state) =
-                            if curr_mid + 1 == self.pcd_converter.columns_per_revolution() {
+                            if curr_mid == self.pcd_converter.last_measurement_id() {
                                 (Some(frame), new_state)
                             } else {
                                 new_state.frame = Some(frame);
@@ -187,6 +327,7 @@ impl FrameConverter {
                         timestamps
                     },
                     points: curr_points,
+                    columns_per_revolution: self.pcd_converter.columns_per_revolution(),
                 };
                 let mut new_state = FrameConverterState {
                     last_mid: curr_mid,
@@ -194,7 +335,7 @@ impl FrameConverter {
                     frame: None,
                 };
 
-                let frame_opt = if curr_mid + 1 == self.pcd_converter.columns_per_revolution() {
+                let frame_opt = if curr_mid == self.pcd_converter.last_measurement_id() {
                     Some(frame)
                 } else {
                     new_state.frame = Some(frame);
@@ -209,6 +350,95 @@ impl FrameConverter {
         Ok(output_frames)
     }
 
+    /// Checks that the timestamp of a valid column is not earlier than that of the previous
+    /// column in the same frame.
+    ///
+    /// It returns error in strict mode, or returns false in lenient mode if the timestamp goes backward.
+    fn validate_timestamp(&mut self, column: &Column) -> Result<bool> {
+        if !column.valid() {
+            return Ok(true);
+        }
+        let prev_timestamp = match &self.state {
+            Some(state) if state.last_fid == column.frame_id => state
+                .frame
+                .as_ref()
+                .and_then(|frame| frame.timestamps.last())
+                .map(|&(_, timestamp)| timestamp),
+            _ => None,
+        };
+        let curr_timestamp = column.timestamp;
+        match prev_timestamp {
+            Some(prev_timestamp) if curr_timestamp < prev_timestamp => {
+                self.pcd_converter.validation_level().check(
+                    self.diagnostics.as_mut(),
+                    Diagnostic::NonMonotonicTimestamp {
+                        measurement_id: column.measurement_id,
+                        prev_timestamp,
+                        curr_timestamp,
+                    },
+                )?;
+                Ok(false)
+            }
+            _ => Ok(true),
+        }
+    }
+
+    fn push_column_by_frame_id(&mut self, column: &Column) -> Result<Vec<Frame>> {
+        let curr_fid = column.frame_id;
+        let curr_mid = column.measurement_id;
+        let curr_ts = column.timestamp;
+        let curr_points = self.pcd_converter.column_to_points(column)?;
+
+        let columns_per_revolution = self.pcd_converter.columns_per_revolution();
+        let new_frame = |skipped_frame_ids| Frame {
+            frame_id: curr_fid,
+            skipped_frame_ids,
+            timestamps: Vec::with_capacity(COLUMNS_PER_PACKET),
+            points: vec![],
+            columns_per_revolution,
+        };
+
+        let (mut frame, output_frame_opt) = match self.state.take() {
+            Some(state) => {
+                let FrameConverterState {
+                    last_mid,
+                    last_fid,
+                    frame,
+                } = state;
+                let frame = frame.unwrap_or_else(|| {
+                    unreachable!("Please report bug to upstream");
+                });
+
+                if last_fid == curr_fid {
+                    ensure!(
+                        !column.valid() || last_mid < curr_mid,
+                        "Measurement ID of received column is less than that of previous column"
+                    );
+                    (frame, None)
+                } else if curr_fid.wrapping_sub(last_fid) <= u16::MAX / 2 {
+                    // Case: New frame ID, possibly wrapped around
+                    let skipped_frame_ids = last_fid.wrapping_add(1)..curr_fid;
+                    (new_frame(skipped_frame_ids), Some(frame))
+                } else {
+                    bail!("Frame ID of received column is less than that of previous column");
+                }
+            }
+            None => (new_frame(curr_fid..curr_fid), None),
+        };
+
+        if column.valid() && !curr_points.is_empty() {
+            frame.timestamps.push((curr_mid, curr_ts));
+            frame.points.extend(curr_points);
+        }
+
+        self.state = Some(FrameConverterState {
+            last_mid: curr_mid,
+            last_fid: curr_fid,
+            frame: Some(frame),
+        });
+        Ok(output_frame_opt.into_iter().collect())
+    }
+
     /// Pushes new [Packet] to converter.
     pub fn push_packet<P>(&mut self, packet: P) -> Result<Vec<Frame>>
     where
@@ -237,3 +467,300 @@ struct FrameConverterState {
     last_fid: u16,
     frame: Option<Frame>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ouster::consts::ENCODER_TICKS_PER_REV;
+
+    fn new_column(frame_id: u16, measurement_id: u16) -> Column {
+        let mut column: Column = unsafe { mem::zeroed() };
+        column.timestamp = frame_id as u64 * 100_000_000 + measurement_id as u64 * 1000;
+        column.frame_id = frame_id;
+        column.measurement_id = measurement_id;
+        column.raw_valid = 0xffffffff;
+        column
+    }
+
+    #[test]
+    fn frame_id_boundary_test() -> Result<()> {
+        let mut converter = FrameConverter::from_config(Config::os_1_config());
+        converter.frame_boundary(FrameBoundary::FrameId);
+
+        let mut frames = vec![];
+        for frame_id in [u16::MAX, 0] {
+            for measurement_id in 0..8 {
+                frames.extend(converter.push_column(&new_column(frame_id, measurement_id))?);
+            }
+        }
+        frames.extend(converter.finish());
+
+        assert_eq!(frames.len(), 2);
+        assert_eq!(frames[0].frame_id, u16::MAX);
+        assert_eq!(frames[1].frame_id, 0);
+        assert!(frames[1].skipped_frame_ids.is_empty());
+        for frame in frames {
+            assert_eq!(frame.timestamps.len(), 8);
+            assert_eq!(frame.points.len(), 8 * 64);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn measurement_id_out_of_range_test() -> Result<()> {
+        let mut converter = FrameConverter::from_config(Config::os_1_config());
+        converter.enable_diagnostics();
+        let mut frames = vec![];
+        for measurement_id in [0, 1, 5000, 2] {
+            frames.extend(converter.push_column(&new_column(0, measurement_id))?);
+        }
+        let diagnostics = converter.take_diagnostics();
+        frames.extend(converter.finish());
+
+        // the corrupted column is skipped and reported in lenient mode
+        assert_eq!(frames.len(), 1);
+        assert_eq!(frames[0].timestamps.len(), 3);
+        assert!(frames[0]
+            .timestamps
+            .iter()
+            .all(|&(measurement_id, _)| measurement_id < 1024));
+        assert_eq!(
+            diagnostics,
+            vec![Diagnostic::MeasurementIdOutOfRange {
+                measurement_id: 5000,
+                columns_per_revolution: 1024,
+            }]
+        );
+
+        // and it is an error in strict mode
+        let mut converter = FrameConverter::from_config(Config::os_1_config());
+        converter.set_validation_level(ValidationLevel::Strict);
+        converter.push_column(&new_column(0, 0))?;
+        assert!(converter.push_column(&new_column(0, 5000)).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn non_monotonic_timestamp_test() -> Result<()> {
+        // the third column carries a timestamp earlier than the second one
+        let mut columns: Vec<_> = (0..4)
+            .map(|measurement_id| new_column(0, measurement_id))
+            .collect();
+        columns[2].timestamp = columns[0].timestamp;
+
+        let mut converter = FrameConverter::from_config(Config::os_1_config());
+        converter.enable_diagnostics();
+        let mut frames = vec![];
+        for column in columns.iter() {
+            frames.extend(converter.push_column(column)?);
+        }
+        assert_eq!(
+            converter.take_diagnostics(),
+            vec![Diagnostic::NonMonotonicTimestamp {
+                measurement_id: 2,
+                prev_timestamp: columns[1].timestamp,
+                curr_timestamp: columns[0].timestamp,
+            }]
+        );
+        frames.extend(converter.finish());
+
+        // the column is skipped in lenient mode
+        assert_eq!(frames.len(), 1);
+        let measurement_ids: Vec<_> = frames[0]
+            .timestamps
+            .iter()
+            .map(|&(measurement_id, _)| measurement_id)
+            .collect();
+        assert_eq!(measurement_ids, vec![0, 1, 3]);
+
+        // and it is an error in strict mode
+        let mut converter = FrameConverter::from_config(Config::os_1_config());
+        converter.set_validation_level(ValidationLevel::Strict);
+        converter.push_column(&columns[0])?;
+        converter.push_column(&columns[1])?;
+        assert!(converter.push_column(&columns[2]).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn columns_per_rev_test() -> Result<()> {
+        let config = Config::os_1_config();
+        assert_eq!(config.columns_per_revolution(), 1024);
+
+        for &columns_per_rev in &[512u16, 2048] {
+            let mut config = Config::os_1_config();
+            config.columns_per_rev(columns_per_rev)?;
+            let mut converter = FrameConverter::from_config(config);
+            assert_eq!(converter.columns_per_revolution(), columns_per_rev);
+            assert_eq!(converter.resolution(), (columns_per_rev, 64));
+
+            // the azimuth step between adjacent columns
+            let step = 360.0 / columns_per_rev as f64;
+            let pcd_converter = &converter.pcd_converter;
+            assert!((pcd_converter.azimuth_step().get::<degree>() - step).abs() < 1e-9);
+            let azimuth = new_column(0, 3).nominal_azimuth_angle(columns_per_rev);
+            assert!((azimuth.get::<degree>() - step * 3.0).abs() < 1e-9);
+
+            // a frame is closed at the last column of revolution
+            let mut frames = vec![];
+            for measurement_id in 0..columns_per_rev {
+                frames.extend(converter.push_column(&new_column(0, measurement_id))?);
+            }
+            assert_eq!(frames.len(), 1);
+            assert_eq!(frames[0].timestamps.len(), columns_per_rev as usize);
+            assert_eq!(frames[0].points.len(), columns_per_rev as usize * 64);
+        }
+
+        let mut config = Config::os_1_config();
+        assert!(config.columns_per_rev(720).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn azimuth_window_test() -> Result<()> {
+        let mut config = Config::os_1_config();
+        assert_eq!(
+            FrameConverter::from_config(config.clone()).columns_per_frame(),
+            1024
+        );
+
+        config.azimuth_window((Angle::new::<degree>(0.0), Angle::new::<degree>(90.0)));
+        let window = config.azimuth_window_angles();
+        let mut converter = FrameConverter::from_config(config);
+        assert_eq!(converter.columns_per_frame(), 256);
+        assert_eq!(converter.resolution(), (256, 64));
+
+        // encoder ticks map to azimuth angles in the window
+        let mut column = new_column(0, 128);
+        column.encoder_ticks = ENCODER_TICKS_PER_REV / 8;
+        let azimuth = column.azimuth_angle_in_window(window).unwrap();
+        assert!((azimuth.get::<degree>() - 45.0).abs() < 1e-9);
+        column.encoder_ticks = ENCODER_TICKS_PER_REV / 2;
+        assert!(column.azimuth_angle_in_window(window).is_none());
+
+        // the frame is closed at the end of window
+        let mut frames = vec![];
+        for measurement_id in 0..256 {
+            frames.extend(converter.push_column(&new_column(0, measurement_id))?);
+        }
+        assert_eq!(frames.len(), 1);
+        assert_eq!(frames[0].timestamps.len(), 256);
+
+        Ok(())
+    }
+
+    #[test]
+    fn near_ir_autocontrast_test() -> Result<()> {
+        let config = Config::os_1_config();
+        let mut converter = FrameConverter::from_config(config.clone());
+
+        // the column shift of each beam due to the azimuth correction
+        let shifts: Vec<u16> = config
+            .beam_azimuth_angle_corrections
+            .iter()
+            .map(|correction| {
+                let shift = (correction.raw() / (360.0 / 1024.0)).round() as i32;
+                shift.rem_euclid(1024) as u16
+            })
+            .collect();
+        assert!(shifts.iter().any(|&shift| shift != 0));
+
+        // the ambient level is 100 to 200 over azimuths, with a few outliers
+        let level = |col_idx: u16| 100 + col_idx % 101;
+        let mut frames = vec![];
+        for measurement_id in 0..1024 {
+            let mut column = new_column(0, measurement_id);
+            column.encoder_ticks =
+                (measurement_id as u64 * ENCODER_TICKS_PER_REV as u64 / 1024) as u32;
+            izip!(column.pixels.iter_mut(), shifts.iter()).for_each(|(pixel, &shift)| {
+                pixel.noise_photons = level((measurement_id + shift) % 1024);
+            });
+            if measurement_id < 4 {
+                column.pixels[0].noise_photons = 0;
+                column.pixels[1].noise_photons = 10000;
+            }
+            frames.extend(converter.push_column(&column)?);
+        }
+        assert_eq!(frames.len(), 1);
+        let frame = &frames[0];
+
+        // the pixels of the rectified image are aligned to azimuths
+        let levels = frame.destagger(|point| point.noise_photons);
+        assert_eq!(levels.len(), 1024 * 64);
+        levels.chunks(1024).for_each(|row| {
+            row.iter().enumerate().for_each(|(col_idx, &value)| {
+                assert!(
+                    value == level(col_idx as u16) || value == 0 || value == 10000,
+                    "misaligned pixel at column {}",
+                    col_idx
+                );
+            });
+        });
+
+        // the stretch endpoints map to 0 and 255, and the outliers are clipped
+        let image = frame.near_ir_autocontrast();
+        assert_eq!(image.len(), levels.len());
+        izip!(image.iter(), levels.iter()).for_each(|(&pixel, &value)| match value {
+            0..=101 => assert!(pixel <= 5),
+            199..=u16::MAX => assert!(pixel >= 250),
+            _ => (),
+        });
+        assert_eq!(image.iter().min(), Some(&0));
+        assert_eq!(image.iter().max(), Some(&255));
+
+        Ok(())
+    }
+
+    #[test]
+    fn destagger_missing_column_test() -> Result<()> {
+        let config = Config::os_1_config();
+        let shifts: Vec<u16> = config
+            .beam_azimuth_angle_corrections
+            .iter()
+            .map(|correction| {
+                let shift = (correction.raw() / (360.0 / 1024.0)).round() as i32;
+                shift.rem_euclid(1024) as u16
+            })
+            .collect();
+
+        // the column 500 is dropped and the column 600 is invalid
+        let mut converter = FrameConverter::from_config(config);
+        let mut frames = vec![];
+        for measurement_id in (0..1024).filter(|&measurement_id| measurement_id != 500) {
+            let mut column = new_column(0, measurement_id);
+            column.encoder_ticks =
+                (measurement_id as u64 * ENCODER_TICKS_PER_REV as u64 / 1024) as u32;
+            izip!(column.pixels.iter_mut(), shifts.iter()).for_each(|(pixel, &shift)| {
+                pixel.noise_photons = 1 + (measurement_id + shift) % 1024;
+            });
+            if measurement_id == 600 {
+                column.raw_valid = 0;
+            }
+            frames.extend(converter.push_column(&column)?);
+        }
+        assert_eq!(frames.len(), 1);
+        let frame = &frames[0];
+        assert_eq!(frame.timestamps.len(), 1022);
+        assert_eq!(frame.points.len(), 1022 * 64);
+
+        // the pixels stay aligned after the missing columns, which are left empty
+        let image = frame.destagger(|point| point.noise_photons);
+        assert_eq!(image.len(), 1024 * 64);
+        izip!(image.chunks(1024), shifts.iter()).for_each(|(row, &shift)| {
+            row.iter().enumerate().for_each(|(col_idx, &value)| {
+                let col_idx = col_idx as u16;
+                if col_idx == (500 + shift) % 1024 || col_idx == (600 + shift) % 1024 {
+                    assert_eq!(value, 0);
+                } else {
+                    assert_eq!(value, 1 + col_idx);
+                }
+            });
+        });
+
+        Ok(())
+    }
+}
@@ -7,6 +7,7 @@ pub mod enums;
 pub mod frame_converter;
 pub mod packet;
 pub mod pcd_converter;
+pub mod pcd_frame;
 
 pub use client::*;
 pub use config::*;
@@ -14,3 +15,4 @@ pub use enums::*;
 pub use frame_converter::*;
 pub use packet::*;
 pub use pcd_converter::*;
+pub use pcd_frame::*;
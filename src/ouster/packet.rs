@@ -1,6 +1,12 @@
 //! Provides a set of _C-packed_ structs for Ouster packets.
 
-use super::consts::{COLUMNS_PER_PACKET, ENCODER_TICKS_PER_REV, PIXELS_PER_COLUMN};
+use super::{
+    config::Config,
+    consts::{
+        COLUMNS_PER_PACKET, COMPACT_RANGE_RESOLUTION_MILLIS, ENCODER_TICKS_PER_REV,
+        PIXELS_PER_COLUMN,
+    },
+};
 use crate::common::*;
 
 /// Represents a point of signal measurement.
@@ -24,6 +30,20 @@ impl Pixel {
     pub fn distance(&self) -> Length {
         Length::new::<millimeter>(self.distance_millimeter() as f64)
     }
+
+    /// Returns the reflectivity scaled according to the calibration status of config.
+    ///
+    /// In calibrated mode, the reflectivity takes the least significant 8 bits, where
+    /// 0 to 100 is the percent reflectivity of diffuse targets, and 101 to 255 indicates
+    /// retroreflective targets. The raw value is returned as is otherwise.
+    pub fn reflectivity_calibrated(&self, config: &Config) -> f64 {
+        let reflectivity = self.reflectivity;
+        if config.reflectivity_calibrated {
+            (reflectivity & 0x00ff) as f64
+        } else {
+            reflectivity as f64
+        }
+    }
 }
 
 /// Represents a list of [Pixel]s along with meta data.
@@ -56,20 +76,67 @@ impl Column {
         Time::new::<nanosecond>(self.timestamp as f64)
     }
 
+    /// Returns the encoder ticks wrapped into [0, [ENCODER_TICKS_PER_REV]).
+    ///
+    /// Some firmware reports the overflow tick equal to [ENCODER_TICKS_PER_REV] at the end
+    /// of rotation, which is the same direction as the zero tick. The
+    /// [PointCloudConverter](crate::ouster::pcd_converter::PointCloudConverter) reports
+    /// such columns as [EncoderOverflow](crate::diagnostic::Diagnostic::EncoderOverflow),
+    /// and converts them with the wrapped ticks in lenient mode.
+    pub fn normalized_encoder_ticks(&self) -> u32 {
+        self.encoder_ticks % ENCODER_TICKS_PER_REV
+    }
+
     /// Compute azimuth angle in degrees from encoder ticks.
+    ///
+    /// The overflow ticks are wrapped by [normalized_encoder_ticks](Column::normalized_encoder_ticks),
+    /// so that the angle is always less than 360 degrees.
     pub fn azimuth_angle_degrees(&self) -> f64 {
-        360.0 * self.encoder_ticks as f64 / ENCODER_TICKS_PER_REV as f64
+        360.0 * self.normalized_encoder_ticks() as f64 / ENCODER_TICKS_PER_REV as f64
     }
 
     /// Compute azimuth angle in radians from encoder ticks.
+    ///
+    /// The overflow ticks are wrapped by [normalized_encoder_ticks](Column::normalized_encoder_ticks),
+    /// so that the angle is always less than 2π.
     pub fn azimuth_angle_radians(&self) -> f64 {
-        2.0 * std::f64::consts::PI * self.encoder_ticks as f64 / ENCODER_TICKS_PER_REV as f64
+        2.0 * std::f64::consts::PI * self.normalized_encoder_ticks() as f64
+            / ENCODER_TICKS_PER_REV as f64
     }
 
     pub fn azimuth_angle(&self) -> Angle {
         Angle::new::<radian>(self.azimuth_angle_radians())
     }
 
+    /// Returns the nominal azimuth angle derived from measurement ID, given the
+    /// number of columns per revolution.
+    pub fn nominal_azimuth_angle(&self, columns_per_revolution: u16) -> Angle {
+        Angle::new::<degree>(360.0 * self.measurement_id as f64 / columns_per_revolution as f64)
+    }
+
+    /// Returns the elapsed time since the start of rotation, derived from encoder ticks
+    /// at the rotation rate `rpm` in revolutions per minute.
+    ///
+    /// Unlike [time](Column::time), the offset is relative to the frame, which is used
+    /// to deskew a frame under the constant rotation rate assumption.
+    pub fn time_offset_in_frame(&self, rpm: f64) -> Time {
+        let period = Time::new::<second>(60.0 / rpm);
+        period * (self.normalized_encoder_ticks() as f64 / ENCODER_TICKS_PER_REV as f64)
+    }
+
+    /// Returns the azimuth angle if it is within the `(start, end)` azimuth window.
+    /// The window wraps around zero if start is greater than end.
+    pub fn azimuth_angle_in_window(&self, azimuth_window: (Angle, Angle)) -> Option<Angle> {
+        let (start, end) = azimuth_window;
+        let angle = self.azimuth_angle();
+        let within = if start <= end {
+            start <= angle && angle <= end
+        } else {
+            start <= angle || angle <= end
+        };
+        within.then_some(angle)
+    }
+
     /// Return if this packet is marked valid.
     pub fn valid(&self) -> bool {
         self.raw_valid == 0xffffffff
@@ -100,11 +167,59 @@ impl Packet {
         Ok(Self::from_buffer(*buffer))
     }
 
+    /// Construct packet from a frame read by [PcapFileReader](crate::pcap_file_reader::PcapFileReader).
+    ///
+    /// The `offset` is the size of protocol headers preceding the packet.
+    #[cfg(feature = "pcap-file")]
+    pub fn from_pcap_file_packet(data: &[u8], offset: usize) -> Result<Packet> {
+        ensure!(
+            data.len() >= offset,
+            "The offset {} exceeds the frame size {}",
+            offset,
+            data.len(),
+        );
+        ensure!(
+            data.len() - offset == mem::size_of::<Packet>(),
+            "Input frame is not a valid Ouster Lidar packet",
+        );
+        Ok(*Self::from_slice(&data[offset..])?)
+    }
+
     /// Construct packet from binary buffer.
     pub fn from_buffer(buffer: [u8; mem::size_of::<Packet>()]) -> Packet {
         unsafe { std::mem::transmute::<_, Packet>(buffer) }
     }
 
+    /// Serializes the packet into the little-endian wire layout, which is
+    /// the inverse of [from_buffer](Packet::from_buffer).
+    pub fn to_bytes(&self) -> [u8; mem::size_of::<Packet>()] {
+        let mut bytes = [0u8; mem::size_of::<Packet>()];
+        let mut offset = 0;
+        let mut write = |src: &[u8]| {
+            bytes[offset..(offset + src.len())].copy_from_slice(src);
+            offset += src.len();
+        };
+
+        let columns = self.columns;
+        for column in columns.iter() {
+            write(&{ column.timestamp }.to_le_bytes());
+            write(&{ column.measurement_id }.to_le_bytes());
+            write(&{ column.frame_id }.to_le_bytes());
+            write(&{ column.encoder_ticks }.to_le_bytes());
+            let pixels = column.pixels;
+            for pixel in pixels.iter() {
+                write(&{ pixel.raw_distance }.to_le_bytes());
+                write(&{ pixel.reflectivity }.to_le_bytes());
+                write(&{ pixel.signal_photons }.to_le_bytes());
+                write(&{ pixel.noise_photons }.to_le_bytes());
+                write(&{ pixel._pad }.to_le_bytes());
+            }
+            write(&{ column.raw_valid }.to_le_bytes());
+        }
+
+        bytes
+    }
+
     /// Construct packet from slice of bytes. Error if the slice size is not correct.
     pub fn from_slice(buffer: &[u8]) -> Result<&Packet> {
         ensure!(
@@ -123,3 +238,228 @@ impl AsRef<Packet> for Packet {
         self
     }
 }
+
+/// Represents a pixel of the low data rate `RNG15_RFL8_NIR8` profile, which packs a
+/// single return into 4 bytes.
+#[repr(C, packed)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct CompactPixel {
+    /// The least significant 15 bits form the range in units of
+    /// [COMPACT_RANGE_RESOLUTION_MILLIS], followed by a reserved bit, the reflectivity
+    /// byte and the near-infrared byte.
+    pub raw: u32,
+}
+
+impl CompactPixel {
+    /// Extract distance in millimeters from the raw word.
+    pub fn distance_millimeter(&self) -> u32 {
+        (self.raw & 0x7fff) * COMPACT_RANGE_RESOLUTION_MILLIS
+    }
+
+    pub fn distance(&self) -> Length {
+        Length::new::<millimeter>(self.distance_millimeter() as f64)
+    }
+
+    pub fn reflectivity(&self) -> u8 {
+        (self.raw >> 16) as u8
+    }
+
+    /// The scaled-down near-infrared photons of ambient light.
+    pub fn near_ir(&self) -> u8 {
+        (self.raw >> 24) as u8
+    }
+}
+
+/// Represents a column of [CompactPixel]s, of which the header carries no encoder count.
+#[repr(C, packed)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct CompactColumn {
+    /// Unix timestamp in nanoseconds.
+    pub timestamp: u64,
+    /// The column index.
+    pub measurement_id: u16,
+    /// The least significant bit is set if the column is valid.
+    pub status: u16,
+    /// Array of pixels.
+    pub pixels: [CompactPixel; PIXELS_PER_COLUMN],
+}
+
+impl CompactColumn {
+    pub fn time(&self) -> Time {
+        Time::new::<nanosecond>(self.timestamp as f64)
+    }
+
+    /// Returns the nominal azimuth angle derived from measurement ID, given the
+    /// number of columns per revolution.
+    pub fn nominal_azimuth_angle(&self, columns_per_revolution: u16) -> Angle {
+        Angle::new::<degree>(360.0 * self.measurement_id as f64 / columns_per_revolution as f64)
+    }
+
+    /// Return if this column is marked valid.
+    pub fn valid(&self) -> bool {
+        self.status & 0x0001 != 0
+    }
+}
+
+/// Represents a data packet of the low data rate `RNG15_RFL8_NIR8` profile.
+#[repr(C, packed)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct CompactPacket {
+    pub packet_type: u16,
+    /// The frame index.
+    pub frame_id: u16,
+    _header: [u8; 28],
+    pub columns: [CompactColumn; COLUMNS_PER_PACKET],
+    _footer: [u8; 32],
+}
+
+impl CompactPacket {
+    /// Construct packet from [pcap's Packet](pcap::Packet).
+    #[cfg(feature = "pcap")]
+    pub fn from_pcap(packet: &pcap::Packet) -> Result<CompactPacket> {
+        let packet_header_size = 42;
+
+        let body_size = packet.header.len as usize - packet_header_size;
+        ensure!(
+            body_size == mem::size_of::<CompactPacket>(),
+            "Input pcap packet is not a valid Ouster Lidar packet of RNG15_RFL8_NIR8 profile",
+        );
+        Ok(*Self::from_slice(&packet.data[packet_header_size..])?)
+    }
+
+    /// Construct packet from slice of bytes. Error if the slice size is not correct.
+    pub fn from_slice(buffer: &[u8]) -> Result<&CompactPacket> {
+        ensure!(
+            buffer.len() == mem::size_of::<CompactPacket>(),
+            "Requre the slice length to be {}, but get {}",
+            mem::size_of::<CompactPacket>(),
+            buffer.len(),
+        );
+        let packet = unsafe { &*(buffer.as_ptr() as *const CompactPacket) };
+        Ok(packet)
+    }
+}
+
+impl AsRef<CompactPacket> for CompactPacket {
+    fn as_ref(&self) -> &CompactPacket {
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn to_bytes_round_trip_test() -> Result<()> {
+        let mut packet: Packet = unsafe { mem::zeroed() };
+        packet
+            .columns
+            .iter_mut()
+            .enumerate()
+            .for_each(|(idx, column)| {
+                column.timestamp = 1_600_000_000_000_000_000 + idx as u64;
+                column.measurement_id = idx as u16;
+                column.frame_id = 7;
+                column.encoder_ticks = idx as u32 * 88;
+                column.raw_valid = 0xffffffff;
+                column
+                    .pixels
+                    .iter_mut()
+                    .enumerate()
+                    .for_each(|(pixel_idx, pixel)| {
+                        pixel.raw_distance = (idx * 100_000 + pixel_idx) as u32;
+                        pixel.reflectivity = pixel_idx as u16;
+                        pixel.signal_photons = 300;
+                        pixel.noise_photons = 20;
+                    });
+            });
+
+        let bytes = packet.to_bytes();
+        assert_eq!(&bytes[8..10], &[0, 0]);
+        assert_eq!(*Packet::from_slice(&bytes)?, packet);
+
+        Ok(())
+    }
+
+    #[test]
+    fn compact_packet_test() -> Result<()> {
+        assert_eq!(mem::size_of::<CompactPacket>(), 4352);
+
+        let mut bytes = vec![0u8; mem::size_of::<CompactPacket>()];
+        bytes[2..4].copy_from_slice(&9u16.to_le_bytes());
+        // the first column starts after the 32-byte packet header
+        bytes[32..40].copy_from_slice(&1_000u64.to_le_bytes());
+        bytes[40..42].copy_from_slice(&5u16.to_le_bytes());
+        bytes[42..44].copy_from_slice(&1u16.to_le_bytes());
+        bytes[44..48].copy_from_slice(&[0xe8, 0x83, 50, 7]);
+
+        let packet = CompactPacket::from_slice(&bytes)?;
+        assert_eq!({ packet.frame_id }, 9);
+        let column = &packet.columns[0];
+        assert!(column.valid());
+        assert!(!packet.columns[1].valid());
+        assert_eq!({ column.measurement_id }, 5);
+        assert_eq!(column.time(), Time::new::<nanosecond>(1_000.0));
+
+        // the reserved bit is ignored
+        let pixel = column.pixels[0];
+        assert_eq!(pixel.distance_millimeter(), 1000 * 8);
+        assert_eq!(pixel.reflectivity(), 50);
+        assert_eq!(pixel.near_ir(), 7);
+
+        Ok(())
+    }
+
+    #[test]
+    fn time_offset_in_frame_test() {
+        let mut column: Column = unsafe { mem::zeroed() };
+        assert_eq!(column.time_offset_in_frame(600.0), Time::new::<second>(0.0));
+
+        // a quarter rotation at 600 RPM takes 25 ms
+        column.encoder_ticks = ENCODER_TICKS_PER_REV / 4;
+        let offset = column.time_offset_in_frame(600.0);
+        assert!((offset.get::<second>() - 0.025).abs() < 1e-12);
+
+        // a half rotation at 1200 RPM takes 25 ms
+        column.encoder_ticks = ENCODER_TICKS_PER_REV / 2;
+        let offset = column.time_offset_in_frame(1200.0);
+        assert!((offset.get::<second>() - 0.025).abs() < 1e-12);
+    }
+
+    #[test]
+    fn reflectivity_calibrated_test() {
+        let mut pixel: Pixel = unsafe { mem::zeroed() };
+        pixel.reflectivity = 0x0132;
+
+        // the raw value is kept for uncalibrated sensors
+        let mut config = Config::os_1_config();
+        assert_eq!(pixel.reflectivity_calibrated(&config), 306.0);
+
+        // the upper byte is discarded in calibrated mode
+        config.reflectivity_calibrated(true);
+        assert_eq!(pixel.reflectivity_calibrated(&config), 50.0);
+        pixel.reflectivity = 200;
+        assert_eq!(pixel.reflectivity_calibrated(&config), 200.0);
+    }
+
+    #[test]
+    fn encoder_overflow_test() {
+        let mut column: Column = unsafe { mem::zeroed() };
+        let full_angle = 2.0 * std::f64::consts::PI;
+
+        column.encoder_ticks = ENCODER_TICKS_PER_REV - 1;
+        assert!(column.azimuth_angle_radians() < full_angle);
+        assert!(column.azimuth_angle_degrees() < 360.0);
+
+        // the overflow tick wraps to the zero azimuth
+        column.encoder_ticks = ENCODER_TICKS_PER_REV;
+        assert_eq!(column.normalized_encoder_ticks(), 0);
+        assert_eq!(column.azimuth_angle_radians(), 0.0);
+        assert_eq!(column.azimuth_angle_degrees(), 0.0);
+        assert_eq!(column.azimuth_angle(), Angle::new::<radian>(0.0));
+
+        column.encoder_ticks = ENCODER_TICKS_PER_REV + ENCODER_TICKS_PER_REV / 4;
+        assert!((column.azimuth_angle_degrees() - 90.0).abs() < 1e-9);
+    }
+}
@@ -2,10 +2,13 @@
 
 use super::{
     config::Config,
-    consts::PIXELS_PER_COLUMN,
-    packet::{Column, Packet},
+    consts::{ENCODER_TICKS_PER_REV, PIXELS_PER_COLUMN},
+    packet::{Column, CompactColumn, CompactPacket, Packet},
+};
+use crate::{
+    common::*,
+    diagnostic::{Diagnostic, ValidationLevel},
 };
-use crate::common::*;
 
 fn spherical_to_xyz(range: Length, azimuth_angle: Angle, altitude_angle: Angle) -> [Length; 3] {
     let x = range * altitude_angle.sin() * azimuth_angle.cos();
@@ -33,15 +36,20 @@ pub struct PointCloudConverter {
     altitude_angles: [Angle; PIXELS_PER_COLUMN],
     azimuth_angle_corrections: [Angle; PIXELS_PER_COLUMN],
     columns_per_revolution: u16,
+    azimuth_window: (Angle, Angle),
+    measurement_id_window: (u16, u16),
+    validation_level: ValidationLevel,
 }
 
 impl PointCloudConverter {
     /// Create a converter from config.
     pub fn from_config(config: Config) -> Self {
+        let azimuth_window = config.azimuth_window_angles();
+        let columns_per_revolution = config.columns_per_revolution();
         let Config {
             beam_azimuth_angle_corrections,
             beam_altitude_angles,
-            lidar_mode,
+            ..
         } = config;
 
         let altitude_angles = {
@@ -67,12 +75,24 @@ impl PointCloudConverter {
             array
         };
 
-        let columns_per_revolution = lidar_mode.columns_per_revolution();
+        // first and last measurement IDs within the azimuth window
+        let measurement_id_window = {
+            let (start, end) = azimuth_window;
+            let to_column =
+                |angle: Angle| angle.get::<degree>() / 360.0 * columns_per_revolution as f64;
+            let first = to_column(start).ceil() as u16 % columns_per_revolution;
+            let last = (to_column(end).ceil() as u16 + columns_per_revolution - 1)
+                % columns_per_revolution;
+            (first, last)
+        };
 
         Self {
             altitude_angles,
             azimuth_angle_corrections,
             columns_per_revolution,
+            azimuth_window,
+            measurement_id_window,
+            validation_level: ValidationLevel::default(),
         }
     }
 
@@ -83,22 +103,103 @@ impl PointCloudConverter {
         self.columns_per_revolution
     }
 
+    /// Returns the nominal azimuth step between adjacent columns.
+    pub fn azimuth_step(&self) -> Angle {
+        Angle::new::<degree>(360.0 / self.columns_per_revolution as f64)
+    }
+
+    /// Returns the `(start, end)` azimuth window.
+    pub fn azimuth_window(&self) -> (Angle, Angle) {
+        self.azimuth_window
+    }
+
+    /// Returns the number of columns within the azimuth window in one revolution.
+    pub fn columns_per_frame(&self) -> u16 {
+        let (first, last) = self.measurement_id_window;
+        (last + self.columns_per_revolution - first) % self.columns_per_revolution + 1
+    }
+
+    /// Returns the last measurement ID within the azimuth window.
+    pub fn last_measurement_id(&self) -> u16 {
+        self.measurement_id_window.1
+    }
+
+    /// Sets how invalid columns, out-of-range measurement IDs and encoder overflows are handled.
+    ///
+    /// In [ValidationLevel::Strict] mode, they are errors. In [ValidationLevel::Lenient] mode,
+    /// which is the default, they are reported as [Diagnostic]s. The invalid columns and the
+    /// out-of-range measurement IDs are skipped, while the overflow encoder ticks are wrapped
+    /// by [normalized_encoder_ticks](Column::normalized_encoder_ticks) and converted as usual.
+    pub fn set_validation_level(&mut self, level: ValidationLevel) {
+        self.validation_level = level;
+    }
+
+    /// Returns the validation level.
+    pub fn validation_level(&self) -> ValidationLevel {
+        self.validation_level
+    }
+
+    /// Checks that the measurement ID of the column is less than the columns per revolution.
+    ///
+    /// If the ID is out of range, it returns error in strict mode, or reports a
+    /// [Diagnostic::MeasurementIdOutOfRange] and returns false in lenient mode.
+    pub(crate) fn validate_measurement_id(
+        &self,
+        measurement_id: u16,
+        diagnostics: Option<&mut Vec<Diagnostic>>,
+    ) -> Result<bool> {
+        if measurement_id < self.columns_per_revolution {
+            return Ok(true);
+        }
+        self.validation_level
+            .check(
+                diagnostics,
+                Diagnostic::MeasurementIdOutOfRange {
+                    measurement_id,
+                    columns_per_revolution: self.columns_per_revolution,
+                },
+            )
+            .map_err(|err| err.context("Is the lidar_mode configured correctly?"))?;
+        Ok(false)
+    }
+
     /// Compute point locations from column returned from lidar.
     ///
     /// The method takes [Column.measurement_id](Column.measurement_id) as column index.
-    /// It returns error if the index is out of bound.
+    /// If the index is out of bound, it returns error in strict mode, or skips the column
+    /// in lenient mode, which is reported by [convert_with_diagnostics](Self::convert_with_diagnostics).
     pub(crate) fn column_to_points(&self, column: &Column) -> Result<Vec<Point>> {
+        self.column_to_points_with_diagnostics(column, None)
+    }
+
+    fn column_to_points_with_diagnostics(
+        &self,
+        column: &Column,
+        mut diagnostics: Option<&mut Vec<Diagnostic>>,
+    ) -> Result<Vec<Point>> {
         // sanity check
         let col_index = column.measurement_id;
-        ensure!(
-            col_index < self.columns_per_revolution,
-            "measurement_id {} is exceeds the upper bound {}. Is the lidar_mode configured correctly?",
-            col_index,
-            self.columns_per_revolution,
-        );
+        if !self.validate_measurement_id(col_index, diagnostics.as_deref_mut())? {
+            return Ok(vec![]);
+        }
+
+        // the overflow ticks are wrapped into a revolution in lenient mode
+        let encoder_count = column.encoder_ticks;
+        if encoder_count >= ENCODER_TICKS_PER_REV {
+            self.validation_level.check(
+                diagnostics.as_deref_mut(),
+                Diagnostic::EncoderOverflow { encoder_count },
+            )?;
+        }
 
         // return empty list if the column is not valid
         if !column.valid() {
+            self.validation_level.check(
+                diagnostics,
+                Diagnostic::InvalidColumn {
+                    measurement_id: col_index,
+                },
+            )?;
             return Ok(vec![]);
         }
 
@@ -137,8 +238,85 @@ impl PointCloudConverter {
         Ok(points)
     }
 
+    fn compact_column_to_points(
+        &self,
+        column: &CompactColumn,
+        mut diagnostics: Option<&mut Vec<Diagnostic>>,
+    ) -> Result<Vec<Point>> {
+        let col_index = column.measurement_id;
+        if !self.validate_measurement_id(col_index, diagnostics.as_deref_mut())? {
+            return Ok(vec![]);
+        }
+        if !column.valid() {
+            self.validation_level.check(
+                diagnostics,
+                Diagnostic::InvalidColumn {
+                    measurement_id: col_index,
+                },
+            )?;
+            return Ok(vec![]);
+        }
+
+        // the column carries no encoder count, and the azimuth is derived from measurement ID
+        let azimuth_angle = column.nominal_azimuth_angle(self.columns_per_revolution);
+        let pixels = column.pixels;
+
+        let points = izip!(
+            pixels.iter(),
+            self.altitude_angles.iter(),
+            self.azimuth_angle_corrections.iter(),
+            0..
+        )
+        .map(
+            |(pixel, altitude_angle, azimuth_angle_correction, laser_id)| {
+                let clockwise_azimuth_angle = azimuth_angle + *azimuth_angle_correction;
+                let counter_clockwise_azimuth_angle =
+                    Angle::new::<radian>(std::f64::consts::PI * 2.0) - clockwise_azimuth_angle;
+                let distance = pixel.distance();
+                let point =
+                    spherical_to_xyz(distance, counter_clockwise_azimuth_angle, *altitude_angle);
+
+                Point {
+                    timestamp: column.time(),
+                    reflectivity: pixel.reflectivity() as u16,
+                    signal_photons: 0,
+                    noise_photons: pixel.near_ir() as u16,
+                    azimuth_angle: clockwise_azimuth_angle,
+                    distance,
+                    laser_id,
+                    point,
+                }
+            },
+        )
+        .collect();
+        Ok(points)
+    }
+
     /// Compute point positions from a packet.
     pub fn convert<P>(&self, packet: P) -> Result<Vec<Point>>
+    where
+        P: AsRef<Packet>,
+    {
+        self.convert_impl(packet, None)
+    }
+
+    /// Compute point positions from a packet, and reports skipped columns to `diagnostics`.
+    pub fn convert_with_diagnostics<P>(
+        &self,
+        packet: P,
+        diagnostics: &mut Vec<Diagnostic>,
+    ) -> Result<Vec<Point>>
+    where
+        P: AsRef<Packet>,
+    {
+        self.convert_impl(packet, Some(diagnostics))
+    }
+
+    fn convert_impl<P>(
+        &self,
+        packet: P,
+        mut diagnostics: Option<&mut Vec<Diagnostic>>,
+    ) -> Result<Vec<Point>>
     where
         P: AsRef<Packet>,
     {
@@ -146,7 +324,50 @@ impl PointCloudConverter {
             .as_ref()
             .columns
             .iter()
-            .map(|col| self.column_to_points(col))
+            .map(|col| self.column_to_points_with_diagnostics(col, diagnostics.as_deref_mut()))
+            .collect::<Result<Vec<_>>>()?
+            .into_iter()
+            .flatten()
+            .collect();
+        Ok(points)
+    }
+
+    /// Compute point positions from a packet of the low data rate `RNG15_RFL8_NIR8` profile.
+    ///
+    /// The profile does not carry signal photons, which are set to zero, and the
+    /// near-infrared byte is reported as `noise_photons`.
+    pub fn convert_compact<P>(&self, packet: P) -> Result<Vec<Point>>
+    where
+        P: AsRef<CompactPacket>,
+    {
+        self.convert_compact_impl(packet, None)
+    }
+
+    /// Compute point positions from a packet of the `RNG15_RFL8_NIR8` profile, and reports
+    /// skipped columns to `diagnostics`.
+    pub fn convert_compact_with_diagnostics<P>(
+        &self,
+        packet: P,
+        diagnostics: &mut Vec<Diagnostic>,
+    ) -> Result<Vec<Point>>
+    where
+        P: AsRef<CompactPacket>,
+    {
+        self.convert_compact_impl(packet, Some(diagnostics))
+    }
+
+    fn convert_compact_impl<P>(
+        &self,
+        packet: P,
+        mut diagnostics: Option<&mut Vec<Diagnostic>>,
+    ) -> Result<Vec<Point>>
+    where
+        P: AsRef<CompactPacket>,
+    {
+        let columns = packet.as_ref().columns;
+        let points: Vec<_> = columns
+            .iter()
+            .map(|col| self.compact_column_to_points(col, diagnostics.as_deref_mut()))
             .collect::<Result<Vec<_>>>()?
             .into_iter()
             .flatten()
@@ -154,3 +375,120 @@ impl PointCloudConverter {
         Ok(points)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ouster::consts::COLUMNS_PER_PACKET;
+
+    #[test]
+    fn validation_level_test() -> Result<()> {
+        let mut packet: Packet = unsafe { mem::zeroed() };
+        packet.columns[1].raw_valid = 0xffffffff;
+        packet.columns[1].measurement_id = 1;
+
+        // the invalid columns are skipped in lenient mode
+        let mut converter = PointCloudConverter::from_config(Config::os_1_config());
+        assert_eq!(converter.validation_level(), ValidationLevel::Lenient);
+        let mut diagnostics = vec![];
+        let points = converter.convert_with_diagnostics(packet, &mut diagnostics)?;
+        assert_eq!(points.len(), PIXELS_PER_COLUMN);
+        assert_eq!(
+            diagnostics,
+            (0..COLUMNS_PER_PACKET)
+                .filter(|&idx| idx != 1)
+                .map(|_| Diagnostic::InvalidColumn { measurement_id: 0 })
+                .collect::<Vec<_>>()
+        );
+
+        // and it is an error in strict mode
+        converter.set_validation_level(ValidationLevel::Strict);
+        assert!(converter.convert(packet).is_err());
+
+        // so is the encoder overflow
+        packet.columns.iter_mut().for_each(|column| {
+            column.raw_valid = 0xffffffff;
+        });
+        assert!(converter.convert(packet).is_ok());
+        packet.columns[0].encoder_ticks = ENCODER_TICKS_PER_REV + 100;
+        assert!(converter.convert(packet).is_err());
+
+        // which is wrapped into a revolution in lenient mode
+        converter.set_validation_level(ValidationLevel::Lenient);
+        let mut diagnostics = vec![];
+        let points = converter.convert_with_diagnostics(packet, &mut diagnostics)?;
+        assert_eq!(points.len(), COLUMNS_PER_PACKET * PIXELS_PER_COLUMN);
+        assert_eq!(
+            diagnostics,
+            vec![Diagnostic::EncoderOverflow {
+                encoder_count: ENCODER_TICKS_PER_REV + 100
+            }]
+        );
+        let mut wrapped = packet;
+        wrapped.columns[0].encoder_ticks = 100;
+        let expect_points = converter.convert(wrapped)?;
+        izip!(
+            &points[..PIXELS_PER_COLUMN],
+            &expect_points[..PIXELS_PER_COLUMN]
+        )
+        .for_each(|(point, expect)| {
+            assert_eq!(point.azimuth_angle, expect.azimuth_angle);
+            assert_eq!(point.point, expect.point);
+        });
+        converter.set_validation_level(ValidationLevel::Strict);
+
+        // and the measurement ID beyond the columns per revolution
+        packet.columns[0].encoder_ticks = 0;
+        packet.columns[0].measurement_id = 1024;
+        assert!(converter.convert(packet).is_err());
+
+        converter.set_validation_level(ValidationLevel::Lenient);
+        let mut diagnostics = vec![];
+        let points = converter.convert_with_diagnostics(packet, &mut diagnostics)?;
+        assert_eq!(points.len(), (COLUMNS_PER_PACKET - 1) * PIXELS_PER_COLUMN);
+        assert_eq!(
+            diagnostics,
+            vec![Diagnostic::MeasurementIdOutOfRange {
+                measurement_id: 1024,
+                columns_per_revolution: 1024,
+            }]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn convert_compact_test() -> Result<()> {
+        let converter = PointCloudConverter::from_config(Config::os_1_config());
+
+        // a valid column at the zero azimuth where every pixel ranges 10 m
+        let mut bytes = vec![0u8; mem::size_of::<CompactPacket>()];
+        bytes[42..44].copy_from_slice(&1u16.to_le_bytes());
+        let raw: u32 = 1250 | 20 << 16 | 3 << 24;
+        (0..PIXELS_PER_COLUMN).for_each(|idx| {
+            let offset = 44 + idx * 4;
+            bytes[offset..(offset + 4)].copy_from_slice(&raw.to_le_bytes());
+        });
+        let packet = CompactPacket::from_slice(&bytes)?;
+
+        let mut diagnostics = vec![];
+        let points = converter.convert_compact_with_diagnostics(packet, &mut diagnostics)?;
+        assert_eq!(points.len(), PIXELS_PER_COLUMN);
+        assert_eq!(diagnostics.len(), COLUMNS_PER_PACKET - 1);
+
+        let altitude_angles = Config::os_1_config().beam_altitude_angles;
+        izip!(points.iter(), altitude_angles.iter()).for_each(|(point, altitude)| {
+            assert_eq!(point.distance, Length::new::<meter>(10.0));
+            assert_eq!(point.reflectivity, 20);
+            assert_eq!(point.noise_photons, 3);
+
+            let [x, y, z] = point.point;
+            let norm = (x * x + y * y + z * z).sqrt();
+            assert!((norm.get::<meter>() - 10.0).abs() < 1e-9);
+            let expect_z = 10.0 * altitude.raw().to_radians().sin();
+            assert!((z.get::<meter>() - expect_z).abs() < 1e-9);
+        });
+
+        Ok(())
+    }
+}
@@ -0,0 +1,144 @@
+//! Adapts Ouster packets to the frame type of Velodyne.
+
+use super::{
+    config::Config,
+    consts::PIXELS_PER_COLUMN,
+    packet::Packet,
+    pcd_converter::{Point, PointCloudConverter},
+};
+use crate::{
+    common::*,
+    velodyne::{
+        frame_converter::PcdFrame,
+        point::{LidarFrameEntry, PointData, ReturnKind, SingleReturnPoint},
+    },
+};
+
+/// Converts Ouster packets into an organized [PcdFrame] of Velodyne points,
+/// so that a single pipeline consumes both sensors.
+///
+/// The frame has a row per laser in pixel order, that is, from the top beam to the bottom
+/// beam, and a column per valid column of the packets in order. The invalid columns are
+/// skipped in lenient mode, or it fails in strict mode. The reflectivity is saturated
+/// into the intensity byte, and the positions are kept in the Ouster sensor frame.
+pub fn to_pcd_frame<I, P>(packets: I, config: Config) -> Result<PcdFrame<SingleReturnPoint>>
+where
+    I: IntoIterator<Item = P>,
+    P: AsRef<Packet>,
+{
+    let converter = PointCloudConverter::from_config(config);
+    let mut data = vec![];
+
+    for packet in packets {
+        let points = converter.convert(packet)?;
+        points.chunks(PIXELS_PER_COLUMN).for_each(|column| {
+            let col_idx = data.len() / PIXELS_PER_COLUMN;
+            data.extend(
+                column
+                    .iter()
+                    .map(|point| to_single_return_point(point, col_idx)),
+            );
+        });
+    }
+
+    Ok(PcdFrame {
+        height: PIXELS_PER_COLUMN,
+        width: data.len() / PIXELS_PER_COLUMN,
+        data,
+    })
+}
+
+fn to_single_return_point(point: &Point, col_idx: usize) -> SingleReturnPoint {
+    let Point {
+        timestamp,
+        azimuth_angle,
+        distance,
+        reflectivity,
+        laser_id,
+        point: position,
+        ..
+    } = *point;
+
+    // the pixels of a column are measured at the same time
+    SingleReturnPoint {
+        laser_id,
+        timestamp,
+        firing_time_offset: Time::new::<second>(0.0),
+        original_azimuth_angle: azimuth_angle,
+        corrected_azimuth_angle: azimuth_angle,
+        data: PointData {
+            distance,
+            intensity: reflectivity.min(u8::MAX as u16) as u8,
+            intensity_value: reflectivity as f64,
+            position,
+            suspect: false,
+        },
+        lidar_frame_entry: LidarFrameEntry {
+            row_idx: laser_id as usize,
+            col_idx,
+        },
+        return_kind: ReturnKind::Strongest,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ouster::consts::COLUMNS_PER_PACKET;
+
+    #[test]
+    fn to_pcd_frame_test() -> Result<()> {
+        let new_packet = |packet_idx: usize| {
+            let mut packet: Packet = unsafe { mem::zeroed() };
+            packet
+                .columns
+                .iter_mut()
+                .enumerate()
+                .for_each(|(idx, column)| {
+                    let measurement_id = packet_idx * COLUMNS_PER_PACKET + idx;
+                    column.raw_valid = 0xffffffff;
+                    column.measurement_id = measurement_id as u16;
+                    column.encoder_ticks = measurement_id as u32 * 88;
+                    column.timestamp = measurement_id as u64 * 1000;
+                    column
+                        .pixels
+                        .iter_mut()
+                        .enumerate()
+                        .for_each(|(laser_id, pixel)| {
+                            pixel.raw_distance = 1000 + laser_id as u32;
+                            pixel.reflectivity = if laser_id == 0 { 300 } else { 7 };
+                        });
+                });
+            packet
+        };
+
+        // the last column of the second packet is invalid
+        let mut packets = vec![new_packet(0), new_packet(1)];
+        packets[1].columns[COLUMNS_PER_PACKET - 1].raw_valid = 0;
+
+        let frame = to_pcd_frame(&packets, Config::os_1_config())?;
+        assert_eq!(frame.height, 64);
+        assert_eq!(frame.width, COLUMNS_PER_PACKET * 2 - 1);
+        assert_eq!(frame.data.len(), frame.width * frame.height);
+
+        for col_idx in 0..frame.width {
+            for row_idx in 0..frame.height {
+                let point = frame.point_at(row_idx, col_idx)?;
+                assert_eq!(point.laser_id as usize, row_idx);
+                assert_eq!(point.lidar_frame_entry.row_idx, row_idx);
+                assert_eq!(point.lidar_frame_entry.col_idx, col_idx);
+                assert_eq!(
+                    point.timestamp,
+                    Time::new::<nanosecond>(col_idx as f64 * 1000.0)
+                );
+                assert_eq!(
+                    point.data.distance,
+                    Length::new::<millimeter>(1000.0 + row_idx as f64)
+                );
+                assert_eq!(point.data.intensity, if row_idx == 0 { 255 } else { 7 });
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,53 @@
+//! Reads pcap files in pure Rust with the [pcap-file](pcap_file) crate,
+//! which does not require libpcap.
+
+use crate::common::*;
+use pcap_file::PcapReader;
+
+/// An iterator of captured frames in a pcap file.
+///
+/// Each item is a raw link layer frame. The LiDAR packet can be decoded by
+/// `from_pcap_file_packet()` with the size of protocol headers as offset.
+pub struct PcapFileReader<R>
+where
+    R: Read,
+{
+    reader: PcapReader<R>,
+}
+
+impl PcapFileReader<File> {
+    /// Opens a pcap file.
+    pub fn open<P>(path: P) -> Result<Self>
+    where
+        P: AsRef<Path>,
+    {
+        Self::new(File::open(path)?)
+    }
+}
+
+impl<R> PcapFileReader<R>
+where
+    R: Read,
+{
+    /// Creates a reader from a pcap formatted data source.
+    pub fn new(reader: R) -> Result<Self> {
+        let reader = PcapReader::new(reader).map_err(|err| format_err!("{}", err))?;
+        Ok(Self { reader })
+    }
+}
+
+impl<R> Iterator for PcapFileReader<R>
+where
+    R: Read,
+{
+    type Item = Result<Vec<u8>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let packet = self.reader.next()?;
+        Some(
+            packet
+                .map(|packet| packet.data.into_owned())
+                .map_err(|err| format_err!("{}", err)),
+        )
+    }
+}
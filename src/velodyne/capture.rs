@@ -0,0 +1,277 @@
+//! Batch conversion of captured pcap files.
+
+use super::{
+    config::Config,
+    frame_converter::FrameConverter,
+    marker::{DynamicModel, ModelMarker, ReturnTypeMarker},
+    packet::{DataPacket, ProductID},
+};
+use crate::{
+    common::*,
+    diagnostic::{Diagnostic, ValidationLevel},
+};
+use pcap::Capture;
+use std::{fs, path::PathBuf};
+
+const UDP_HEADER_SIZE: usize = 42;
+const NUM_DETECTION_PACKETS: usize = 10;
+
+/// Converts all data packets in a pcap file into frames.
+///
+/// The trailing partial frame is flushed at the end of file.
+/// The frame converter type has to be specified, for example,
+///
+/// ```no_run
+/// use lidar_utils::velodyne::{pcap_to_frames, Config, Vlp16_Strongest_FrameConverter};
+///
+/// let frames = pcap_to_frames::<Vlp16_Strongest_FrameConverter, _, _, _>(
+///     "velodyne_vlp16.pcap",
+///     Config::vlp_16_strongest_return(),
+/// )?;
+/// # Ok::<_, anyhow::Error>(())
+/// ```
+pub fn pcap_to_frames<Converter, Model, ReturnType, P>(
+    path: P,
+    config: Config<Model, ReturnType>,
+) -> Result<Vec<Converter::Frame>>
+where
+    Converter: FrameConverter<Model, ReturnType>,
+    Model: ModelMarker,
+    ReturnType: ReturnTypeMarker,
+    P: AsRef<Path>,
+{
+    let mut converter = Converter::from_config(config);
+    let mut frames = vec![];
+    convert_pcap_file(path, &mut converter, &mut frames)?;
+    frames.extend(converter.finish());
+
+    Ok(frames)
+}
+
+/// Converts the pcap segments in a directory into frames, in the natural order of file names.
+///
+/// The segments of a long capture are usually numbered, such as `segment_9.pcap` and
+/// `segment_10.pcap`, where the numeric parts of the names are compared by value.
+/// The remaining points are carried across segments, so that the frame at the boundary
+/// is not broken, and the trailing partial frame is flushed after the last segment.
+/// Only files with the `pcap` extension are read.
+///
+/// ```no_run
+/// use lidar_utils::velodyne::{pcap_dir_to_frames, Config, Vlp16_Strongest_FrameConverter};
+///
+/// let frames = pcap_dir_to_frames::<Vlp16_Strongest_FrameConverter, _, _, _>(
+///     "segments",
+///     Config::vlp_16_strongest_return(),
+/// )?;
+/// # Ok::<_, anyhow::Error>(())
+/// ```
+pub fn pcap_dir_to_frames<Converter, Model, ReturnType, P>(
+    dir: P,
+    config: Config<Model, ReturnType>,
+) -> Result<Vec<Converter::Frame>>
+where
+    Converter: FrameConverter<Model, ReturnType>,
+    Model: ModelMarker,
+    ReturnType: ReturnTypeMarker,
+    P: AsRef<Path>,
+{
+    let mut paths = vec![];
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        let is_pcap = matches!(path.extension().and_then(|ext| ext.to_str()), Some("pcap"));
+        if path.is_file() && is_pcap {
+            paths.push(path);
+        }
+    }
+    paths.sort_by_cached_key(|path| natural_sort_key(&path.file_name().unwrap().to_string_lossy()));
+
+    let mut converter = Converter::from_config(config);
+    let mut frames = vec![];
+    for path in paths {
+        convert_pcap_file(&path, &mut converter, &mut frames)?;
+    }
+    frames.extend(converter.finish());
+
+    Ok(frames)
+}
+
+fn convert_pcap_file<Converter, Model, ReturnType>(
+    path: impl AsRef<Path>,
+    converter: &mut Converter,
+    frames: &mut Vec<Converter::Frame>,
+) -> Result<()>
+where
+    Converter: FrameConverter<Model, ReturnType>,
+    Model: ModelMarker,
+    ReturnType: ReturnTypeMarker,
+{
+    let mut cap = Capture::from_file(path)?;
+    cap.filter("udp", true)?;
+
+    while let Ok(packet) = cap.next() {
+        if packet.data.len() != mem::size_of::<DataPacket>() + UDP_HEADER_SIZE {
+            continue;
+        }
+        let packet = DataPacket::from_pcap(&packet)?;
+        frames.extend(converter.convert(packet));
+    }
+
+    Ok(())
+}
+
+/// Splits a capture into pcap files, one per rotation, for example, to curate datasets.
+///
+/// The data packets are fed to a frame converter, and the packet on which a frame is emitted
+/// starts the new group, so that the files follow the frames of the converter. The boundary
+/// is per packet rather than per firing: the firings before the azimuth wraps in that packet
+/// belong to the emitted frame, but the packet is written to the new file. The position and
+/// other packets are kept in the file open at the time. The groups are written to
+/// `frame_0.pcap`, `frame_1.pcap`, ... in the output directory, which is created if it does
+/// not exist, and the paths of files are returned in order. The frame converter type has to
+/// be specified, for example,
+///
+/// ```no_run
+/// use lidar_utils::velodyne::{split_pcap_by_frame, Config, Vlp16_Strongest_FrameConverter};
+///
+/// let paths = split_pcap_by_frame::<Vlp16_Strongest_FrameConverter, _, _, _, _>(
+///     "velodyne_vlp16.pcap",
+///     "frames",
+///     Config::vlp_16_strongest_return(),
+/// )?;
+/// # Ok::<_, anyhow::Error>(())
+/// ```
+pub fn split_pcap_by_frame<Converter, Model, ReturnType, P, Q>(
+    input_path: P,
+    output_dir: Q,
+    config: Config<Model, ReturnType>,
+) -> Result<Vec<PathBuf>>
+where
+    Converter: FrameConverter<Model, ReturnType>,
+    Model: ModelMarker,
+    ReturnType: ReturnTypeMarker,
+    P: AsRef<Path>,
+    Q: AsRef<Path>,
+{
+    let output_dir = output_dir.as_ref();
+    fs::create_dir_all(output_dir)?;
+
+    let mut cap = Capture::from_file(input_path)?;
+
+    let mut converter = Converter::from_config(config);
+    let mut paths = vec![];
+    let mut savefile = None;
+
+    while let Ok(packet) = cap.next() {
+        let (header, data) = (*packet.header, packet.data.to_vec());
+        let (frame_emitted, bytes) = if data.len() == mem::size_of::<DataPacket>() + UDP_HEADER_SIZE
+        {
+            let packet = DataPacket::from_slice(&data[UDP_HEADER_SIZE..])?;
+            let mut bytes = data[..UDP_HEADER_SIZE].to_vec();
+            bytes.extend_from_slice(&packet.to_bytes());
+            (converter.convert(packet).is_some(), bytes)
+        } else {
+            (false, data)
+        };
+
+        // the previous file is closed when it is replaced
+        if savefile.is_none() || frame_emitted {
+            let path = output_dir.join(format!("frame_{}.pcap", paths.len()));
+            savefile = Some(cap.savefile(&path)?);
+            paths.push(path);
+        }
+
+        savefile
+            .as_mut()
+            .unwrap()
+            .write(&pcap::Packet::new(&header, &bytes));
+    }
+
+    Ok(paths)
+}
+
+/// Splits the name into alternating text and numeric parts, where the numbers are
+/// compared by value.
+fn natural_sort_key(name: &str) -> Vec<(String, u128)> {
+    let mut key = vec![];
+    let mut chars = name.chars().peekable();
+    while chars.peek().is_some() {
+        let mut text = String::new();
+        while let Some(&ch) = chars.peek() {
+            if ch.is_ascii_digit() {
+                break;
+            }
+            text.push(ch);
+            chars.next();
+        }
+        let mut number: u128 = 0;
+        while let Some(digit) = chars.peek().and_then(|ch| ch.to_digit(10)) {
+            number = number.saturating_mul(10).saturating_add(digit as u128);
+            chars.next();
+        }
+        key.push((text, number));
+    }
+    key
+}
+
+/// Identifies the sensor model from the first few data packets in a pcap file.
+///
+/// It checks the packet length and the factory bytes of the packets, and fails if
+/// no data packet is found, the product ID is not supported, or the factory bytes are
+/// inconsistent across packets. Since some 16-laser sensors report the HDL-32E product ID,
+/// such captures are resolved by the firing layout of the majority of packets, see
+/// [DataPacket::model], and it fails for an actual HDL-32E, which has no model in the crate.
+pub fn detect_model_from_pcap<P>(path: P) -> Result<DynamicModel>
+where
+    P: AsRef<Path>,
+{
+    let mut cap = Capture::from_file(path)?;
+    cap.filter("udp", true)?;
+
+    let mut packets = vec![];
+    let mut diagnostics: Vec<Diagnostic> = vec![];
+    while packets.len() < NUM_DETECTION_PACKETS {
+        let packet = match cap.next() {
+            Ok(packet) => packet,
+            Err(_) => break,
+        };
+        if packet.data.len() != mem::size_of::<DataPacket>() + UDP_HEADER_SIZE {
+            continue;
+        }
+        let packet = DataPacket::from_slice_validated(
+            &packet.data[UDP_HEADER_SIZE..],
+            ValidationLevel::Strict,
+            &mut diagnostics,
+        )?
+        .unwrap();
+        packets.push(packet);
+    }
+
+    let first = *packets
+        .first()
+        .ok_or_else(|| format_err!("no Velodyne data packets are found"))?;
+    ensure!(
+        packets.iter().all(|packet| {
+            packet.product_id == first.product_id && packet.return_mode == first.return_mode
+        }),
+        "inconsistent factory bytes across packets"
+    );
+
+    if let Some(model) = first.product_id.model() {
+        return Ok(model);
+    }
+    let model = match first.product_id {
+        ProductID::HDL32E => {
+            let num_vlp_16_packets = packets
+                .iter()
+                .filter(|packet| matches!(packet.model(), Some(DynamicModel::Vlp16)))
+                .count();
+            ensure!(
+                num_vlp_16_packets * 2 > packets.len(),
+                "the capture is from HDL-32E, which is not supported"
+            );
+            DynamicModel::Vlp16
+        }
+        _ => bail!("the model of {:?} is not supported", first.product_id),
+    };
+    Ok(model)
+}
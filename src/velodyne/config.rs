@@ -9,11 +9,12 @@ use super::{
         VLP_32C_AZIMUTH_OFFSETS, VLP_32C_ELEVAION_DEGREES, VLP_32C_HORIZONTAL_OFFSETS,
         VLP_32C_VERTICAL_OFFSETS,
     },
+    intensity::IntensityMode,
     marker::{
-        DualReturn, DynamicModel, DynamicReturn, LastReturn, ModelMarker, ReturnTypeMarker,
+        DualReturn, DynamicModel, DynamicReturn, Hdl64, LastReturn, ModelMarker, ReturnTypeMarker,
         StrongestReturn, Vlp16, Vlp32,
     },
-    packet::ReturnMode,
+    packet::{DataPacket, DistanceMode, ProductID, ReturnMode},
 };
 use crate::common::*;
 
@@ -35,6 +36,27 @@ mod config_ {
         pub lasers: Model::ParamArray,
         pub return_type: ReturnType,
         pub distance_resolution: Length,
+        /// Overrides the row index of each laser for sensors with non-standard beam wiring.
+        /// It is set by [Config::with_elevation_index], which validates the table.
+        pub(crate) elevation_index: Option<Vec<usize>>,
+        /// Determines how the intensity of points is reported.
+        pub intensity_mode: IntensityMode,
+        /// The clockwise angle from the encoder zero to the physical front of sensor,
+        /// which is added to the azimuth of all points. It is distinct from the per-laser offsets.
+        pub azimuth_zero_offset: Angle,
+        /// Negates the elevation of points to follow the convention where downward is positive.
+        ///
+        /// The crate follows the Velodyne convention by default, where the elevation angle
+        /// is positive above the horizontal plane. When it is set, the z coordinates of points
+        /// are negated, while the row order of frames is kept from top to bottom.
+        pub flip_elevation_sign: bool,
+        /// Rounds the distances to the nearest multiple of the quantum after the temperature
+        /// correction and before the Cartesian transform, which reduces the entropy for
+        /// downstream compressors. It is off by default.
+        pub range_quantum: Option<Length>,
+        /// Overrides the distance resolution for sensors configured with a non-default
+        /// distance mode, which is not flagged in packets.
+        pub distance_mode: Option<DistanceMode>,
     }
 
     #[allow(non_camel_case_types)]
@@ -54,6 +76,10 @@ mod config_ {
     #[allow(non_camel_case_types)]
     pub type Vlp32_Dynamic_Config = Config<Vlp32, DynamicReturn>;
     #[allow(non_camel_case_types)]
+    pub type Hdl64_Strongest_Config = Config<Hdl64, StrongestReturn>;
+    #[allow(non_camel_case_types)]
+    pub type Hdl64_Last_Config = Config<Hdl64, LastReturn>;
+    #[allow(non_camel_case_types)]
     pub type Dynamic_Config = Config<DynamicModel, DynamicReturn>;
 
     impl<Model, ReturnType> Config<Model, ReturnType>
@@ -67,6 +93,12 @@ mod config_ {
                 lasers,
                 return_type,
                 distance_resolution,
+                elevation_index,
+                intensity_mode,
+                azimuth_zero_offset,
+                flip_elevation_sign,
+                range_quantum,
+                distance_mode,
             } = self;
 
             Dynamic_Config {
@@ -74,185 +106,457 @@ mod config_ {
                 lasers: Model::to_dynamic_params(lasers),
                 return_type: return_type.into_dynamic(),
                 distance_resolution,
+                elevation_index,
+                intensity_mode,
+                azimuth_zero_offset,
+                flip_elevation_sign,
+                range_quantum,
+                distance_mode,
+            }
+        }
+
+        /// Creates a config with the default options, which are changed by the `with_*` setters.
+        fn default_options(
+            model: Model,
+            lasers: Model::ParamArray,
+            distance_resolution: Length,
+            return_type: ReturnType,
+        ) -> Self {
+            Self {
+                model,
+                lasers,
+                return_type,
+                distance_resolution,
+                elevation_index: None,
+                intensity_mode: IntensityMode::default(),
+                azimuth_zero_offset: Angle::new::<radian>(0.0),
+                flip_elevation_sign: false,
+                range_quantum: None,
+                distance_mode: None,
+            }
+        }
+
+        /// Returns the expected number of columns per frame, that is, firings per revolution,
+        /// at the given rotation rate in RPM.
+        pub fn columns_per_frame(&self, rpm: f64) -> usize {
+            let rotation_period = 60.0 / rpm;
+            let firing_period = self.model.firing_period().get::<second>();
+            (rotation_period / firing_period).round() as usize
+        }
+
+        /// Returns the azimuth angle between consecutive firings at the given rotation rate in RPM.
+        pub fn azimuth_resolution(&self, rpm: f64) -> Angle {
+            let rotation_period = 60.0 / rpm;
+            let firing_period = self.model.firing_period().get::<second>();
+            Angle::new::<degree>(360.0 * firing_period / rotation_period)
+        }
+
+        /// Returns the elevation gaps between adjacent beams, ordered from the top beam to
+        /// the bottom beam as the rows of frames. The profile has one entry less than lasers.
+        ///
+        /// The beams of VLP-32C are densely packed around the horizon, so the rows of range
+        /// images are not evenly spaced in elevation.
+        pub fn beam_spacing_profile(&self) -> Vec<Angle> {
+            let mut elevations: Vec<Angle> = self
+                .lasers
+                .as_ref()
+                .iter()
+                .map(|laser| laser.elevation_angle)
+                .collect();
+            elevations.sort_by(|lhs, rhs| rhs.partial_cmp(lhs).unwrap());
+            elevations
+                .windows(2)
+                .map(|pair| pair[0] - pair[1])
+                .collect()
+        }
+
+        /// Returns the overridden row index of each laser if any.
+        pub fn elevation_index(&self) -> Option<&[usize]> {
+            self.elevation_index.as_deref()
+        }
+
+        /// Overrides the row index of each laser. The index must be a permutation of laser IDs.
+        pub fn with_elevation_index(mut self, elevation_index: Vec<usize>) -> Result<Self> {
+            let num_lasers = self.lasers.as_ref().len();
+            ensure!(
+                elevation_index.len() == num_lasers,
+                "expect {} entries in elevation index, but get {}",
+                num_lasers,
+                elevation_index.len()
+            );
+
+            let mut visited = vec![false; num_lasers];
+            for &row_idx in elevation_index.iter() {
+                ensure!(
+                    row_idx < num_lasers && !visited[row_idx],
+                    "elevation index is not a permutation of 0..{}",
+                    num_lasers
+                );
+                visited[row_idx] = true;
             }
+
+            self.elevation_index = Some(elevation_index);
+            Ok(self)
+        }
+
+        /// Sets the intensity mode. The calibrated mode must have parameters for all lasers.
+        pub fn with_intensity_mode(mut self, intensity_mode: IntensityMode) -> Result<Self> {
+            intensity_mode.validate(self.lasers.as_ref().len())?;
+            self.intensity_mode = intensity_mode;
+            Ok(self)
+        }
+
+        /// Sets the clockwise angle from the encoder zero to the physical front of sensor.
+        pub fn with_azimuth_zero_offset(mut self, azimuth_zero_offset: Angle) -> Self {
+            self.azimuth_zero_offset = azimuth_zero_offset;
+            self
+        }
+
+        /// Negates the elevation of points if `flip` is true. See [Config::flip_elevation_sign].
+        pub fn with_flip_elevation_sign(mut self, flip: bool) -> Self {
+            self.flip_elevation_sign = flip;
+            self
+        }
+
+        /// Rounds the distances to the nearest multiple of `quantum`. See [Config::range_quantum].
+        ///
+        /// It fails if the quantum is not positive.
+        pub fn with_range_quantum(mut self, quantum: Length) -> Result<Self> {
+            ensure!(
+                quantum > Length::new::<meter>(0.0),
+                "the range quantum must be positive, but get {} m",
+                quantum.get::<meter>()
+            );
+            self.range_quantum = Some(quantum);
+            Ok(self)
+        }
+
+        /// Scales raw distances according to the distance mode. See [Config::distance_mode].
+        pub fn with_distance_mode(mut self, distance_mode: DistanceMode) -> Self {
+            self.distance_mode = Some(distance_mode);
+            self
+        }
+
+        /// Returns the length of one raw distance unit, which is determined by the distance
+        /// mode if it is set.
+        pub fn effective_distance_resolution(&self) -> Length {
+            self.distance_mode
+                .map(|mode| mode.distance_resolution())
+                .unwrap_or(self.distance_resolution)
         }
     }
 
     impl Vlp16_Last_Config {
         pub fn vlp_16_last_return() -> Self {
-            Config {
-                model: Vlp16,
-                lasers: vlp_16_laser_params(),
-                distance_resolution: Length::new::<millimeter>(VLP_16_DISTANCE_RESOLUTION_MILLIS),
-                return_type: LastReturn,
-            }
+            Self::default_options(
+                Vlp16,
+                vlp_16_laser_params(),
+                Length::new::<millimeter>(VLP_16_DISTANCE_RESOLUTION_MILLIS),
+                LastReturn,
+            )
         }
 
         pub fn puck_hires_last_return() -> Self {
-            Config {
-                model: Vlp16,
-                lasers: puck_hires_laser_params(),
-                distance_resolution: Length::new::<millimeter>(
-                    PUCK_HIRES_DISTANCE_RESOLUTION_MILLIS,
-                ),
-                return_type: LastReturn,
-            }
+            Self::default_options(
+                Vlp16,
+                puck_hires_laser_params(),
+                Length::new::<millimeter>(PUCK_HIRES_DISTANCE_RESOLUTION_MILLIS),
+                LastReturn,
+            )
         }
 
         pub fn puck_lite_last_return() -> Self {
-            Config {
-                model: Vlp16,
-                lasers: puck_lite_laser_params(),
-                distance_resolution: Length::new::<millimeter>(
-                    PUCK_LITE_DISTANCE_RESOLUTION_MILLIS,
-                ),
-                return_type: LastReturn,
-            }
+            Self::default_options(
+                Vlp16,
+                puck_lite_laser_params(),
+                Length::new::<millimeter>(PUCK_LITE_DISTANCE_RESOLUTION_MILLIS),
+                LastReturn,
+            )
         }
     }
 
     impl Vlp16_Strongest_Config {
         pub fn vlp_16_strongest_return() -> Self {
-            Config {
-                model: Vlp16,
-                lasers: vlp_16_laser_params(),
-                distance_resolution: Length::new::<millimeter>(VLP_16_DISTANCE_RESOLUTION_MILLIS),
-                return_type: StrongestReturn,
-            }
+            Self::default_options(
+                Vlp16,
+                vlp_16_laser_params(),
+                Length::new::<millimeter>(VLP_16_DISTANCE_RESOLUTION_MILLIS),
+                StrongestReturn,
+            )
         }
 
         pub fn puck_hires_strongest_return() -> Self {
-            Config {
-                model: Vlp16,
-                lasers: puck_hires_laser_params(),
-                distance_resolution: Length::new::<millimeter>(
-                    PUCK_HIRES_DISTANCE_RESOLUTION_MILLIS,
-                ),
-                return_type: StrongestReturn,
-            }
+            Self::default_options(
+                Vlp16,
+                puck_hires_laser_params(),
+                Length::new::<millimeter>(PUCK_HIRES_DISTANCE_RESOLUTION_MILLIS),
+                StrongestReturn,
+            )
         }
 
         pub fn puck_lite_strongest_return() -> Self {
-            Config {
-                model: Vlp16,
-                lasers: puck_lite_laser_params(),
-                distance_resolution: Length::new::<millimeter>(
-                    PUCK_LITE_DISTANCE_RESOLUTION_MILLIS,
-                ),
-                return_type: StrongestReturn,
-            }
+            Self::default_options(
+                Vlp16,
+                puck_lite_laser_params(),
+                Length::new::<millimeter>(PUCK_LITE_DISTANCE_RESOLUTION_MILLIS),
+                StrongestReturn,
+            )
         }
     }
 
     impl Vlp16_Dual_Config {
         pub fn vlp_16_dual_return() -> Self {
-            Config {
-                model: Vlp16,
-                lasers: vlp_16_laser_params(),
-                distance_resolution: Length::new::<millimeter>(VLP_16_DISTANCE_RESOLUTION_MILLIS),
-                return_type: DualReturn,
-            }
+            Self::default_options(
+                Vlp16,
+                vlp_16_laser_params(),
+                Length::new::<millimeter>(VLP_16_DISTANCE_RESOLUTION_MILLIS),
+                DualReturn,
+            )
         }
 
         pub fn puck_hires_dual_return() -> Self {
-            Config {
-                model: Vlp16,
-                lasers: puck_hires_laser_params(),
-                distance_resolution: Length::new::<millimeter>(
-                    PUCK_HIRES_DISTANCE_RESOLUTION_MILLIS,
-                ),
-                return_type: DualReturn,
-            }
+            Self::default_options(
+                Vlp16,
+                puck_hires_laser_params(),
+                Length::new::<millimeter>(PUCK_HIRES_DISTANCE_RESOLUTION_MILLIS),
+                DualReturn,
+            )
         }
 
         pub fn puck_lite_dual_return() -> Self {
-            Config {
-                model: Vlp16,
-                lasers: puck_lite_laser_params(),
-                distance_resolution: Length::new::<millimeter>(
-                    PUCK_LITE_DISTANCE_RESOLUTION_MILLIS,
-                ),
-                return_type: DualReturn,
-            }
+            Self::default_options(
+                Vlp16,
+                puck_lite_laser_params(),
+                Length::new::<millimeter>(PUCK_LITE_DISTANCE_RESOLUTION_MILLIS),
+                DualReturn,
+            )
         }
     }
 
     impl Vlp16_Dynamic_Config {
         pub fn vlp_16_dynamic_return(return_mode: ReturnMode) -> Self {
-            Config {
-                model: Vlp16,
-                lasers: vlp_16_laser_params(),
-                distance_resolution: Length::new::<millimeter>(VLP_16_DISTANCE_RESOLUTION_MILLIS),
-                return_type: DynamicReturn::from(return_mode),
-            }
+            Self::default_options(
+                Vlp16,
+                vlp_16_laser_params(),
+                Length::new::<millimeter>(VLP_16_DISTANCE_RESOLUTION_MILLIS),
+                DynamicReturn::from(return_mode),
+            )
         }
 
         pub fn puck_hires_dynamic_return(return_mode: ReturnMode) -> Self {
-            Config {
-                model: Vlp16,
-                lasers: puck_hires_laser_params(),
-                distance_resolution: Length::new::<millimeter>(
-                    PUCK_HIRES_DISTANCE_RESOLUTION_MILLIS,
-                ),
-                return_type: DynamicReturn::from(return_mode),
-            }
+            Self::default_options(
+                Vlp16,
+                puck_hires_laser_params(),
+                Length::new::<millimeter>(PUCK_HIRES_DISTANCE_RESOLUTION_MILLIS),
+                DynamicReturn::from(return_mode),
+            )
         }
 
         pub fn puck_lite_dynamic_return(return_mode: ReturnMode) -> Self {
-            Config {
-                model: Vlp16,
-                lasers: puck_lite_laser_params(),
-                distance_resolution: Length::new::<millimeter>(
-                    PUCK_LITE_DISTANCE_RESOLUTION_MILLIS,
-                ),
-                return_type: DynamicReturn::from(return_mode),
+            Self::default_options(
+                Vlp16,
+                puck_lite_laser_params(),
+                Length::new::<millimeter>(PUCK_LITE_DISTANCE_RESOLUTION_MILLIS),
+                DynamicReturn::from(return_mode),
+            )
+        }
+
+        /// Selects the config of 16-laser sensor by product ID.
+        ///
+        /// The products in [ProductID::VLP_16_IDS] are accepted.
+        /// It fails if the product is not a 16-laser sensor, including the byte 0x21, which is
+        /// resolved by [from_packet](Self::from_packet) instead.
+        pub fn from_product_id(product_id: ProductID, return_mode: ReturnMode) -> Result<Self> {
+            let config = match product_id {
+                ProductID::VLP16 => Self::vlp_16_dynamic_return(return_mode),
+                ProductID::PuckHiRes => Self::puck_hires_dynamic_return(return_mode),
+                ProductID::PuckLite => Self::puck_lite_dynamic_return(return_mode),
+                ProductID::HDL32E | ProductID::VLP32C | ProductID::Velarray | ProductID::VLS128 => {
+                    bail!("{:?} is not a 16-laser sensor", product_id)
+                }
+            };
+            Ok(config)
+        }
+
+        /// Selects the config of 16-laser sensor by the product ID and return mode of a packet.
+        ///
+        /// The byte 0x21 of early VLP-16 firmwares is accepted if the packet has the firing
+        /// layout of 16-laser sensors, see [DataPacket::model].
+        pub fn from_packet(packet: &DataPacket) -> Result<Self> {
+            let DataPacket {
+                product_id,
+                return_mode,
+                ..
+            } = *packet;
+            if product_id == ProductID::HDL32E
+                && matches!(packet.model(), Some(DynamicModel::Vlp16))
+            {
+                return Ok(Self::vlp_16_dynamic_return(return_mode));
             }
+            Self::from_product_id(product_id, return_mode)
         }
     }
 
     impl Vlp32_Last_Config {
         pub fn vlp_32c_last_return() -> Self {
-            Config {
-                model: Vlp32,
-                lasers: vlp_32c_laser_params(),
-                distance_resolution: Length::new::<millimeter>(VLP_32C_DISTANCE_RESOLUTION_MILLIS),
-                return_type: LastReturn,
-            }
+            Self::default_options(
+                Vlp32,
+                vlp_32c_laser_params(),
+                Length::new::<millimeter>(VLP_32C_DISTANCE_RESOLUTION_MILLIS),
+                LastReturn,
+            )
         }
     }
 
     impl Vlp32_Strongest_Config {
         pub fn vlp_32c_strongest_return() -> Self {
-            Config {
-                model: Vlp32,
-                lasers: vlp_32c_laser_params(),
-                distance_resolution: Length::new::<millimeter>(VLP_32C_DISTANCE_RESOLUTION_MILLIS),
-                return_type: StrongestReturn,
-            }
+            Self::default_options(
+                Vlp32,
+                vlp_32c_laser_params(),
+                Length::new::<millimeter>(VLP_32C_DISTANCE_RESOLUTION_MILLIS),
+                StrongestReturn,
+            )
         }
     }
 
     impl Vlp32_Dual_Config {
         pub fn vlp_32c_dual_return() -> Self {
-            Config {
-                model: Vlp32,
-                lasers: vlp_32c_laser_params(),
-                distance_resolution: Length::new::<millimeter>(VLP_32C_DISTANCE_RESOLUTION_MILLIS),
-                return_type: DualReturn,
-            }
+            Self::default_options(
+                Vlp32,
+                vlp_32c_laser_params(),
+                Length::new::<millimeter>(VLP_32C_DISTANCE_RESOLUTION_MILLIS),
+                DualReturn,
+            )
         }
     }
 
     impl Vlp32_Dynamic_Config {
         pub fn vlp_32c_dynamic_return(return_mode: ReturnMode) -> Self {
-            Config {
-                model: Vlp32,
-                lasers: vlp_32c_laser_params(),
-                distance_resolution: Length::new::<millimeter>(VLP_32C_DISTANCE_RESOLUTION_MILLIS),
-                return_type: DynamicReturn::from(return_mode),
-            }
+            Self::default_options(
+                Vlp32,
+                vlp_32c_laser_params(),
+                Length::new::<millimeter>(VLP_32C_DISTANCE_RESOLUTION_MILLIS),
+                DynamicReturn::from(return_mode),
+            )
+        }
+    }
+
+    impl Hdl64_Last_Config {
+        /// Creates a HDL-64E config from calibration parameters, which are unique to each unit.
+        pub fn hdl_64e_last_return(params: &ParamsConfig) -> Result<Self> {
+            Ok(Self::default_options(
+                Hdl64,
+                hdl_64e_laser_params(params)?,
+                params.distance_resolution(),
+                LastReturn,
+            ))
+        }
+    }
+
+    impl Hdl64_Strongest_Config {
+        /// Creates a HDL-64E config from calibration parameters, which are unique to each unit.
+        pub fn hdl_64e_strongest_return(params: &ParamsConfig) -> Result<Self> {
+            Ok(Self::default_options(
+                Hdl64,
+                hdl_64e_laser_params(params)?,
+                params.distance_resolution(),
+                StrongestReturn,
+            ))
         }
     }
+
+    /// Assembles configs from raw laser parameters.
+    #[derive(Debug, Clone, Copy)]
+    pub struct ConfigBuilder;
+
+    impl ConfigBuilder {
+        /// Creates a config from elevation angles in degrees, one per laser.
+        ///
+        /// The azimuth, vertical and horizontal offsets are set to zeros. The model is
+        /// resolved from the number of lasers as [from_embedded_yaml](Self::from_embedded_yaml).
+        /// The converters decode the firing layouts of VLP-16, VLP-32C and HDL-64E only, and
+        /// reject a [CustomModel](super::marker::CustomModel) or [DynamicModel::Custom] with another number of lasers.
+        pub fn from_elevations<Model, ReturnType>(
+            elevations: &[f64],
+            distance_resolution: Length,
+            return_type: ReturnType,
+        ) -> Result<Config<Model, ReturnType>>
+        where
+            Model: ModelMarker,
+            ReturnType: ReturnTypeMarker,
+            Model::ParamArray: TryFrom<Vec<LaserParameter>>,
+        {
+            let num_lasers = elevations.len();
+            ensure!(num_lasers > 0, "expect at least one elevation angle");
+            let model = Model::from_num_lasers(num_lasers).ok_or_else(|| {
+                format_err!("the model does not have {} elevation angles", num_lasers)
+            })?;
+
+            let lasers: Vec<_> = elevations
+                .iter()
+                .map(|&elevation| LaserParameter {
+                    elevation_angle: Angle::new::<degree>(elevation),
+                    azimuth_offset: Angle::new::<degree>(0.0),
+                    vertical_offset: Length::new::<millimeter>(0.0),
+                    horizontal_offset: Length::new::<millimeter>(0.0),
+                })
+                .collect();
+            let lasers = lasers.try_into().map_err(|_| {
+                format_err!("the model does not have {} elevation angles", num_lasers)
+            })?;
+
+            Ok(Config::default_options(
+                model,
+                lasers,
+                distance_resolution,
+                return_type,
+            ))
+        }
+
+        /// Creates a config from a calibration YAML embedded in the binary, for example,
+        /// `ConfigBuilder::from_embedded_yaml(include_str!("32db.yaml"), StrongestReturn)`.
+        ///
+        /// The text is baked in at build time by `include_str!`, while it is parsed
+        /// by [ParamsConfig] at runtime. The model is resolved from the number of lasers, so
+        /// that a [Dynamic_Config] gets VLP-16, VLP-32C or HDL-64E for 16, 32 or 64 lasers,
+        /// and the model of a typed config must match it. It fails if the YAML is invalid or
+        /// the number of lasers mismatches the model.
+        pub fn from_embedded_yaml<Model, ReturnType>(
+            text: &'static str,
+            return_type: ReturnType,
+        ) -> Result<Config<Model, ReturnType>>
+        where
+            Model: ModelMarker,
+            ReturnType: ReturnTypeMarker,
+            Model::ParamArray: TryFrom<Vec<LaserParameter>>,
+        {
+            let params = ParamsConfig::from_str(text)?;
+            let lasers = params.laser_params();
+            let num_lasers = lasers.len();
+            ensure!(num_lasers > 0, "the calibration has no lasers");
+
+            let model = Model::from_num_lasers(num_lasers)
+                .ok_or_else(|| format_err!("the model does not have {} lasers", num_lasers))?;
+            let lasers = lasers
+                .try_into()
+                .map_err(|_| format_err!("the model does not have {} lasers", num_lasers))?;
+
+            Ok(Config::default_options(
+                model,
+                lasers,
+                params.distance_resolution(),
+                return_type,
+            ))
+        }
+    }
+
+    fn hdl_64e_laser_params(params: &ParamsConfig) -> Result<[LaserParameter; 64]> {
+        let num_lasers = params.num_lasers();
+        params
+            .laser_params()
+            .try_into()
+            .map_err(|_| format_err!("expect 64 lasers for HDL-64E, but get {}", num_lasers))
+    }
 }
 
 mod params {
@@ -393,6 +697,24 @@ mod param_config {
     }
 
     impl ParamsConfig {
+        pub fn num_lasers(&self) -> usize {
+            self.num_lasers
+        }
+
+        pub fn distance_resolution(&self) -> Length {
+            Length::new::<meter>(self.distance_resolution)
+        }
+
+        /// Converts the calibration of each laser to [LaserParameter].
+        pub fn laser_params(&self) -> Vec<LaserParameter> {
+            self.lasers.iter().map(LaserParameter::from).collect()
+        }
+
+        /// Returns the calibration entries of lasers.
+        pub fn lasers(&self) -> &[LaserConfig] {
+            &self.lasers
+        }
+
         pub fn load<P>(path: P) -> Result<Self>
         where
             P: AsRef<Path>,
@@ -413,6 +735,20 @@ mod param_config {
         }
     }
 
+    impl From<&LaserConfig> for LaserParameter {
+        fn from(config: &LaserConfig) -> Self {
+            // rot_correction is measured in the opposite direction of the azimuth
+            Self {
+                elevation_angle: Angle::new::<radian>(config.vert_correction),
+                azimuth_offset: Angle::new::<radian>(-config.rot_correction),
+                vertical_offset: Length::new::<meter>(config.vert_offset_correction),
+                horizontal_offset: Length::new::<meter>(
+                    config.horiz_offset_correction.unwrap_or(0.0),
+                ),
+            }
+        }
+    }
+
     impl FromStr for ParamsConfig {
         type Err = Error;
 
@@ -444,6 +780,12 @@ mod param_config {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::velodyne::{
+        marker::CustomModel,
+        pcd_converter::{Dynamic_PcdConverter, PointCloudConverter, Vlp32_Strongest_PcdConverter},
+        point::DynamicReturnPoints,
+        test_support::PacketBuilder,
+    };
     use anyhow::Result;
 
     #[test]
@@ -464,9 +806,229 @@ mod tests {
         let _: Vlp32_Strongest_Config = Config::vlp_32c_strongest_return();
         let _: Vlp32_Dual_Config = Config::vlp_32c_dual_return();
 
+        let params = ParamsConfig::from_str(include_str!("params/64e_utexas.yaml"))?;
+        let _: Hdl64_Last_Config = Config::hdl_64e_last_return(&params)?;
+        let _: Hdl64_Strongest_Config = Config::hdl_64e_strongest_return(&params)?;
+
+        let params = ParamsConfig::from_str(include_str!("params/VLP16db.yaml"))?;
+        assert!(Hdl64_Strongest_Config::hdl_64e_strongest_return(&params).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn config_builder_test() -> Result<()> {
+        let elevations: Vec<f64> = (0..40).map(|idx| -20.0 + idx as f64).collect();
+        let config: Config<CustomModel<40>, LastReturn> = ConfigBuilder::from_elevations(
+            &elevations,
+            Length::new::<millimeter>(2.0),
+            LastReturn,
+        )?;
+        assert_eq!(config.lasers.len(), 40);
+        assert!(
+            izip!(config.lasers.iter(), elevations.iter()).all(|(laser, &elevation)| {
+                (laser.elevation_angle.get::<degree>() - elevation).abs() < 1e-9
+                    && laser.azimuth_offset.get::<degree>() == 0.0
+            })
+        );
+        assert!(matches!(config.into_dyn().model, DynamicModel::Custom(40)));
+
+        assert!(ConfigBuilder::from_elevations::<CustomModel<32>, _>(
+            &elevations,
+            Length::new::<millimeter>(2.0),
+            LastReturn
+        )
+        .is_err());
+
+        // 16 elevations make a VLP-16 config, which is accepted by the converter
+        let elevations: Vec<f64> = (0..16).map(|idx| -15.0 + idx as f64 * 2.0).collect();
+        let config: Dynamic_Config = ConfigBuilder::from_elevations(
+            &elevations,
+            Length::new::<millimeter>(2.0),
+            DynamicReturn::LastReturn,
+        )?;
+        assert!(matches!(config.model, DynamicModel::Vlp16));
+
+        let packet = PacketBuilder::new()
+            .return_mode(ReturnMode::LastReturn)
+            .channels(500, 10)
+            .build();
+        let points = match Dynamic_PcdConverter::from_config(config).convert(packet)? {
+            DynamicReturnPoints::Single(points) => points,
+            DynamicReturnPoints::Dual(_) => unreachable!(),
+        };
+        assert!(!points.is_empty());
+        points.iter().for_each(|point| {
+            let elevation = elevations[point.laser_id as usize].to_radians();
+            let [_, _, z] = point.data.position;
+            let expect = Length::new::<meter>(1.0) * elevation.sin();
+            assert!((z - expect).abs() < Length::new::<millimeter>(1e-6));
+        });
+
         Ok(())
     }
 
+    #[test]
+    fn embedded_yaml_config_test() -> Result<()> {
+        const PARAMS: &str = include_str!("params/VeloView-VLP-32C.yaml");
+        let config: Vlp32_Strongest_Config =
+            ConfigBuilder::from_embedded_yaml(PARAMS, StrongestReturn)?;
+        let params = ParamsConfig::from_str(PARAMS)?;
+        assert_eq!(config.distance_resolution, params.distance_resolution());
+        assert!(
+            izip!(config.lasers.iter(), params.laser_params().iter()).all(|(lhs, rhs)| {
+                lhs.elevation_angle == rhs.elevation_angle
+                    && lhs.azimuth_offset == rhs.azimuth_offset
+            })
+        );
+
+        // the dynamic model is resolved from the number of lasers
+        let config: Dynamic_Config =
+            ConfigBuilder::from_embedded_yaml(PARAMS, DynamicReturn::StrongestReturn)?;
+        assert!(matches!(config.model, DynamicModel::Vlp32));
+
+        // the dynamic converter accepts the config and agrees with the typed one
+        let packet = PacketBuilder::new()
+            .product_id(ProductID::VLP32C)
+            .channels(500, 10)
+            .azimuth_counts(0, 20)
+            .build();
+        let expect = Vlp32_Strongest_PcdConverter::from_config(ConfigBuilder::from_embedded_yaml(
+            PARAMS,
+            StrongestReturn,
+        )?)
+        .convert(packet)?;
+        let points = match Dynamic_PcdConverter::from_config(config).convert(packet)? {
+            DynamicReturnPoints::Single(points) => points,
+            DynamicReturnPoints::Dual(_) => unreachable!(),
+        };
+        assert_eq!(points.len(), expect.len());
+        assert!(izip!(points.iter(), expect.iter())
+            .all(|(lhs, rhs)| lhs.data.position == rhs.data.position));
+        let config: Dynamic_Config = ConfigBuilder::from_embedded_yaml(
+            include_str!("params/VLP16db.yaml"),
+            DynamicReturn::StrongestReturn,
+        )?;
+        assert!(matches!(config.model, DynamicModel::Vlp16));
+
+        assert!(ConfigBuilder::from_embedded_yaml::<Vlp16, _>(PARAMS, LastReturn).is_err());
+        assert!(
+            ConfigBuilder::from_embedded_yaml::<DynamicModel, _>("lasers: []", LastReturn).is_err()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn product_id_config_test() -> Result<()> {
+        let packet = PacketBuilder::new().build();
+
+        // overwrite the product ID byte at the end of packet
+        let mut buffer: [u8; mem::size_of::<DataPacket>()] = unsafe { mem::transmute(packet) };
+        *buffer.last_mut().unwrap() = 0x24;
+        let packet = DataPacket::from_buffer(buffer);
+        assert_eq!(packet.product_id, ProductID::PuckHiRes);
+
+        let config = Vlp16_Dynamic_Config::from_packet(&packet)?;
+        assert!(matches!(config.return_type, DynamicReturn::StrongestReturn));
+        assert!(
+            izip!(config.lasers.iter(), puck_hires_laser_params().iter())
+                .all(|(lhs, rhs)| lhs.elevation_angle == rhs.elevation_angle)
+        );
+        assert!(izip!(config.lasers.iter(), vlp_16_laser_params().iter())
+            .any(|(lhs, rhs)| lhs.elevation_angle != rhs.elevation_angle));
+
+        let config =
+            Vlp16_Dynamic_Config::from_product_id(ProductID::VLP16, ReturnMode::LastReturn)?;
+        assert!(izip!(config.lasers.iter(), vlp_16_laser_params().iter())
+            .all(|(lhs, rhs)| lhs.elevation_angle == rhs.elevation_angle));
+        assert!(
+            Vlp16_Dynamic_Config::from_product_id(ProductID::VLP32C, ReturnMode::LastReturn)
+                .is_err()
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn vlp_16_product_ids_test() -> Result<()> {
+        let packet = PacketBuilder::new()
+            .return_mode(ReturnMode::DualReturn)
+            .build();
+        let expects = [
+            vlp_16_laser_params(),
+            puck_lite_laser_params(),
+            puck_hires_laser_params(),
+        ];
+
+        for (&byte, expect) in izip!(ProductID::VLP_16_IDS.iter(), expects.iter()) {
+            let mut buffer = packet.to_bytes();
+            *buffer.last_mut().unwrap() = byte;
+            let packet = DataPacket::from_buffer(buffer);
+            assert!(packet.product_id.is_vlp16());
+            assert!(matches!(
+                packet.product_id.model(),
+                Some(DynamicModel::Vlp16)
+            ));
+
+            let config = Vlp16_Dynamic_Config::from_packet(&packet)?;
+            assert!(matches!(config.return_type, DynamicReturn::DualReturn));
+            assert!(
+                izip!(config.lasers.iter(), expect.iter()).all(|(lhs, rhs)| {
+                    lhs.elevation_angle == rhs.elevation_angle
+                        && lhs.azimuth_offset == rhs.azimuth_offset
+                })
+            );
+        }
+
+        // the byte shared by HDL-32E and early VLP-16 firmwares is ambiguous
+        assert!(!ProductID::HDL32E.is_vlp16());
+        assert!(ProductID::HDL32E.model().is_none());
+        assert!(ProductID::from_u8(0x00).is_none());
+
+        Ok(())
+    }
+
+    #[test]
+    fn columns_per_frame_test() {
+        let config = Config::vlp_16_strongest_return();
+        let columns = config.columns_per_frame(600.0);
+        assert!((1800..=1810).contains(&columns));
+
+        let resolution = config.azimuth_resolution(600.0).get::<degree>();
+        assert!((resolution - 0.2).abs() < 0.01);
+    }
+
+    #[test]
+    fn beam_spacing_profile_test() {
+        // VLP-16 beams are evenly spaced by 2 degrees
+        let profile = Config::vlp_16_strongest_return().beam_spacing_profile();
+        assert_eq!(profile.len(), 15);
+        assert!(profile
+            .iter()
+            .all(|spacing| (spacing.get::<degree>() - 2.0).abs() < 1e-9));
+
+        // VLP-32C beams are dense around the horizon and sparse at the edges
+        let profile = Config::vlp_32c_strongest_return().beam_spacing_profile();
+        let profile: Vec<f64> = profile
+            .iter()
+            .map(|spacing| spacing.get::<degree>())
+            .collect();
+        assert_eq!(profile.len(), 31);
+        assert!(profile.iter().all(|&spacing| spacing > 0.0));
+
+        let min = profile.iter().cloned().fold(f64::INFINITY, f64::min);
+        let max = profile.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+        assert!((min - 0.333).abs() < 0.01);
+        assert!(max > 9.0);
+
+        // the top and bottom gaps are the widest
+        assert!((profile[0] - 4.667).abs() < 0.01);
+        assert!((profile[30] - 9.361).abs() < 0.01);
+        assert!(profile[10..22]
+            .iter()
+            .all(|&spacing| (spacing - 0.333).abs() < 0.01));
+    }
+
     #[test]
     fn load_yaml_params_test() -> Result<()> {
         ParamsConfig::from_str(include_str!("params/32db.yaml"))?;
@@ -18,6 +18,21 @@ pub const CHANNEL_PERIOD: f64 = 2.304; // microseconds
 /// Period of one vertical scan in microseconds.
 pub const FIRING_PERIOD: f64 = 55.296; // microseconds
 
+/// Period of one simultaneous upper and lower block firing of HDL-64E in microseconds.
+pub const HDL_64E_FIRING_PERIOD: f64 = 48.0; // microseconds
+
+/// Period of one laser return of HDL-32E in microseconds.
+pub const HDL_32E_CHANNEL_PERIOD: f64 = 1.152; // microseconds
+
+/// Period of one firing sequence of HDL-32E in microseconds.
+pub const HDL_32E_FIRING_PERIOD: f64 = 46.08; // microseconds
+
+/// The maximum motor speed of the supported sensors in revolutions per minute.
+pub const MAX_RPM: f64 = 1200.0;
+
+/// Period of packet timestamps in microseconds, which wrap around at the top of hour.
+pub const TIMESTAMP_PERIOD: i64 = 3_600_000_000; // microseconds
+
 // VLP-16 parameters
 
 /// Elevaion angles of VLP-16.
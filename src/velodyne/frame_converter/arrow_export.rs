@@ -0,0 +1,83 @@
+use super::converter::PcdFrame;
+use crate::export::{self, ExportablePoint};
+use arrow::record_batch::RecordBatch;
+
+impl<P> PcdFrame<P>
+where
+    P: Copy + ExportablePoint,
+{
+    /// Converts the frame into an Arrow [RecordBatch], which can be loaded by
+    /// DataFusion or Polars.
+    ///
+    /// The columns are `x`, `y`, `z` in meters as `Float64`, `intensity` as `Float32`,
+    /// `ring` (the row index) as `UInt16` and `timestamp` in seconds as `Float64`.
+    /// There is one row per point ordered as `data`, including the points without returns.
+    /// See [export::to_record_batch] for points of other sensors.
+    pub fn to_record_batch(&self) -> RecordBatch {
+        export::to_record_batch(&self.data)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{common::*, velodyne::test_support::PointBuilder};
+    use arrow::{
+        array::{Array, Float32Array, Float64Array, UInt16Array},
+        datatypes::DataType,
+    };
+
+    #[test]
+    fn to_record_batch_test() {
+        let data: Vec<_> = (0..6)
+            .map(|idx| {
+                PointBuilder::new()
+                    .laser_id((idx % 2) as u32)
+                    .timestamp(Time::new::<second>(1.5))
+                    .position([1.0, 2.0, idx as f64])
+                    .distance(Length::new::<meter>(1.0))
+                    .intensity(7)
+                    .entry(idx % 2, idx / 2)
+                    .build()
+            })
+            .collect();
+        let frame = PcdFrame {
+            height: 2,
+            width: 3,
+            data,
+        };
+
+        let batch = frame.to_record_batch();
+        let fields: Vec<_> = batch
+            .schema()
+            .fields()
+            .iter()
+            .map(|field| (field.name().clone(), field.data_type().clone()))
+            .collect();
+        assert_eq!(
+            fields,
+            vec![
+                ("x".to_string(), DataType::Float64),
+                ("y".to_string(), DataType::Float64),
+                ("z".to_string(), DataType::Float64),
+                ("intensity".to_string(), DataType::Float32),
+                ("ring".to_string(), DataType::UInt16),
+                ("timestamp".to_string(), DataType::Float64),
+            ]
+        );
+        assert_eq!(batch.num_rows(), 6);
+
+        let column = |name: &str| batch.column_by_name(name).unwrap().clone();
+        let z = column("z");
+        let z = z.as_any().downcast_ref::<Float64Array>().unwrap();
+        assert_eq!(z.value(3), 3.0);
+        let intensity = column("intensity");
+        let intensity = intensity.as_any().downcast_ref::<Float32Array>().unwrap();
+        assert_eq!(intensity.value(0), 7.0);
+        let ring = column("ring");
+        let ring = ring.as_any().downcast_ref::<UInt16Array>().unwrap();
+        assert_eq!(ring.value(3), 1);
+        let timestamp = column("timestamp");
+        assert_eq!(timestamp.null_count(), 0);
+    }
+}
@@ -1,26 +1,32 @@
 use super::impls;
 use crate::{
     common::*,
+    export::{self, ExportablePoint},
     velodyne::{
         config::{
             Config, Dynamic_Config, Vlp16_Dual_Config, Vlp16_Dynamic_Config, Vlp16_Last_Config,
             Vlp16_Strongest_Config, Vlp32_Dual_Config, Vlp32_Dynamic_Config, Vlp32_Last_Config,
             Vlp32_Strongest_Config,
         },
+        consts::{FIRING_PERIOD, MAX_RPM},
         marker::{
             DualReturn, DynamicModel, DynamicReturn, LastReturn, ModelMarker, ReturnTypeMarker,
             StrongestReturn, Vlp16, Vlp32,
         },
-        packet::DataPacket,
+        packet::{DataPacket, ReturnMode},
         pcd_converter::{
             Dynamic_PcdConverter, PointCloudConverter, Vlp16_Dual_PcdConverter,
             Vlp16_Dynamic_PcdConverter, Vlp16_Last_PcdConverter, Vlp16_Strongest_PcdConverter,
             Vlp32_Dual_PcdConverter, Vlp32_Dynamic_PcdConverter, Vlp32_Last_PcdConverter,
             Vlp32_Strongest_PcdConverter,
         },
-        point::{DualReturnPoint, DynamicReturnFrame, DynamicReturnPoints, SingleReturnPoint},
+        point::{
+            DualReturnPoint, DynamicReturnFrame, DynamicReturnPoints, LidarFrameMsg, PointData,
+            PrimaryData, ReturnKind, RotateAzimuth, SingleReturnPoint, VelodynePoint,
+        },
     },
 };
+use std::collections::BTreeMap;
 
 pub use converter_impls::*;
 pub use definitions::*;
@@ -42,11 +48,52 @@ mod definitions {
         fn from_config(config: Config<Model, ReturnType>) -> Self;
 
         /// Converts a packet into a collection of frames of points.
+        ///
+        /// A frame is emitted only when the azimuth wraps around, so the last partial
+        /// rotation stays in the converter until [finish](FrameConverter::finish) is called.
+        /// [FrameIter] and `pcap_to_frames` do so at end of input.
         fn convert<P>(&mut self, packet: P) -> Option<Self::Frame>
         where
             P: Borrow<DataPacket>;
 
         fn pop_remaining(&mut self) -> Option<Self::Remain>;
+
+        /// Flushes the remaining points into a partial frame, which is typically called at end of input.
+        fn finish(&mut self) -> Option<Self::Frame>;
+
+        /// Discards the first frame if it does not span a full rotation, which happens
+        /// when the stream starts mid-rotation. It is disabled by default.
+        ///
+        /// See [PcdFrame::spans_full_rotation] for the criterion.
+        fn set_skip_first_partial(&mut self, skip: bool);
+
+        /// Converts packets into frames and sends them to a bounded channel.
+        ///
+        /// It blocks when the channel is full, and flushes the trailing partial frame
+        /// at end of input. It fails if the receiver is disconnected.
+        fn run_to_channel<I, P>(
+            &mut self,
+            packets: I,
+            sender: SyncSender<Self::Frame>,
+        ) -> Result<()>
+        where
+            Self: Sized,
+            I: IntoIterator<Item = P>,
+            P: Borrow<DataPacket>,
+        {
+            let disconnected = |_| format_err!("the frame receiver is disconnected");
+
+            for packet in packets {
+                if let Some(frame) = self.convert(packet) {
+                    sender.send(frame).map_err(disconnected)?;
+                }
+            }
+            if let Some(frame) = self.finish() {
+                sender.send(frame).map_err(disconnected)?;
+            }
+
+            Ok(())
+        }
     }
 
     #[derive(Debug)]
@@ -73,6 +120,7 @@ mod definitions {
     pub struct Dynamic_FrameConverter {
         pub(crate) pcd_converter: Dynamic_PcdConverter,
         pub(crate) remaining_points: RemainingPoints,
+        pub(crate) skip_first_partial: bool,
     }
 
     #[derive(Debug)]
@@ -80,6 +128,7 @@ mod definitions {
     pub struct Vlp16_Last_FrameConverter {
         pub(crate) pcd_converter: Vlp16_Last_PcdConverter,
         pub(crate) remaining_points: Vec<SingleReturnPoint>,
+        pub(crate) skip_first_partial: bool,
     }
 
     #[derive(Debug)]
@@ -87,6 +136,7 @@ mod definitions {
     pub struct Vlp16_Strongest_FrameConverter {
         pub(crate) pcd_converter: Vlp16_Strongest_PcdConverter,
         pub(crate) remaining_points: Vec<SingleReturnPoint>,
+        pub(crate) skip_first_partial: bool,
     }
 
     #[derive(Debug)]
@@ -94,6 +144,7 @@ mod definitions {
     pub struct Vlp16_Dual_FrameConverter {
         pub(crate) pcd_converter: Vlp16_Dual_PcdConverter,
         pub(crate) remaining_points: Vec<DualReturnPoint>,
+        pub(crate) skip_first_partial: bool,
     }
 
     #[derive(Debug)]
@@ -101,6 +152,7 @@ mod definitions {
     pub struct Vlp16_Dynamic_FrameConverter {
         pub(crate) pcd_converter: Vlp16_Dynamic_PcdConverter,
         pub(crate) remaining_points: RemainingPoints,
+        pub(crate) skip_first_partial: bool,
     }
 
     #[derive(Debug)]
@@ -108,6 +160,7 @@ mod definitions {
     pub struct Vlp32_Last_FrameConverter {
         pub(crate) pcd_converter: Vlp32_Last_PcdConverter,
         pub(crate) remaining_points: Vec<SingleReturnPoint>,
+        pub(crate) skip_first_partial: bool,
     }
 
     #[derive(Debug)]
@@ -115,6 +168,7 @@ mod definitions {
     pub struct Vlp32_Strongest_FrameConverter {
         pub(crate) pcd_converter: Vlp32_Strongest_PcdConverter,
         pub(crate) remaining_points: Vec<SingleReturnPoint>,
+        pub(crate) skip_first_partial: bool,
     }
 
     #[derive(Debug)]
@@ -122,6 +176,7 @@ mod definitions {
     pub struct Vlp32_Dual_FrameConverter {
         pub(crate) pcd_converter: Vlp32_Dual_PcdConverter,
         pub(crate) remaining_points: Vec<DualReturnPoint>,
+        pub(crate) skip_first_partial: bool,
     }
 
     #[derive(Debug)]
@@ -129,6 +184,7 @@ mod definitions {
     pub struct Vlp32_Dynamic_FrameConverter {
         pub(crate) pcd_converter: Vlp32_Dynamic_PcdConverter,
         pub(crate) remaining_points: RemainingPoints,
+        pub(crate) skip_first_partial: bool,
     }
 
     #[derive(Debug, Clone)]
@@ -141,6 +197,48 @@ mod definitions {
         pub data: Vec<P>,
     }
 
+    /// The representative position of points in a voxel for [PcdFrame::voxel_downsample].
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+    pub enum CentroidMode {
+        /// The mean position of points.
+        #[default]
+        Geometric,
+        /// The mean position weighted by intensities, which is biased toward bright points.
+        /// It falls back to the geometric centroid if all points have zero intensity.
+        IntensityWeighted,
+    }
+
+    /// The summary geometry of a ring given by [PcdFrame::ring_summary].
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub struct RingSummary {
+        /// The number of valid returns.
+        pub count: usize,
+        pub mean_range: Length,
+        /// The mean of intensity bytes.
+        pub mean_intensity: f64,
+        /// The mean position of valid returns.
+        pub centroid: [Length; 3],
+        /// The smallest arc covering the corrected azimuth angles of valid returns.
+        pub azimuth_span: Angle,
+    }
+
+    /// The per-frame summary given by [PcdFrame::metadata], which is written as a JSON
+    /// sidecar alongside the point data in datasets.
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    pub struct FrameMetadata {
+        pub frame_id: u64,
+        /// The timestamp of the earliest point in seconds, or zero for empty frame.
+        pub timestamp: f64,
+        /// The number of valid returns.
+        pub point_count: usize,
+        pub width: usize,
+        pub height: usize,
+        pub model: String,
+        pub return_mode: String,
+        /// The azimuth angle swept by the columns in degrees, which is at most 360.
+        pub azimuth_coverage: f64,
+    }
+
     impl<P> PcdFrame<P>
     where
         P: Copy,
@@ -157,6 +255,1083 @@ mod definitions {
             }
         }
     }
+
+    impl<P> PcdFrame<P>
+    where
+        P: Copy + RotateAzimuth,
+    {
+        /// Rotates every point counter-clockwise about the Z axis by `delta`, for example,
+        /// to align frames of sensors mounted at different yaws.
+        ///
+        /// The azimuth angles of points decrease by `delta` and wrap around into [0, 2π).
+        pub fn rotate_azimuth(&mut self, delta: Angle) {
+            self.data
+                .iter_mut()
+                .for_each(|point| point.rotate_azimuth(delta));
+        }
+    }
+
+    impl<P> PcdFrame<P>
+    where
+        P: Copy + ExportablePoint,
+    {
+        /// Packs the points into a contiguous buffer for GPU upload.
+        ///
+        /// Each point takes 4 floats in the order `[x, y, z, intensity]`, that is,
+        /// a stride of 16 bytes without padding. The position is in meters, and the
+        /// intensity is the raw value from 0 to 255. The buffer has exactly
+        /// `4 * data.len()` floats in the same order as `data`.
+        pub fn to_interleaved_f32(&self) -> Vec<f32> {
+            export::to_interleaved_f32(&self.data)
+        }
+
+        /// Packs the positions in meters into planar arrays for column-major (Fortran order)
+        /// numeric libraries.
+        ///
+        /// The buffer has `3 * height * width` doubles, where all x's come first, then all
+        /// y's and all z's. Each plane is a column-major `height` by `width` matrix, that is,
+        /// the coordinate `c` of the point at `(row_idx, col_idx)` is at index
+        /// `c * height * width + col_idx * height + row_idx`. The points without returns are
+        /// kept in place to preserve the organized layout.
+        pub fn to_column_major_xyz(&self) -> Vec<f64> {
+            let plane_size = self.height * self.width;
+            let mut buffer = vec![0.0; 3 * plane_size];
+            let (xs, rest) = buffer.split_at_mut(plane_size);
+            let (ys, zs) = rest.split_at_mut(plane_size);
+
+            izip!(self.data.iter(), xs, ys, zs).for_each(|(point, x, y, z)| {
+                let [px, py, pz] = point.xyz();
+                *x = px;
+                *y = py;
+                *z = pz;
+            });
+            buffer
+        }
+
+        /// Returns parallel vectors of positions in meters and intensities of valid points,
+        /// which are the array shapes expected by Python bindings such as Open3D.
+        ///
+        /// The points without returns are skipped.
+        pub fn to_xyz_intensity_vecs(&self) -> (Vec<[f64; 3]>, Vec<f64>) {
+            export::to_xyz_intensity_vecs(&self.data)
+        }
+    }
+
+    impl<P> PcdFrame<P>
+    where
+        P: Copy + PrimaryData,
+    {
+        /// Counts the valid returns of each ring, indexed by the row index in elevation order.
+        ///
+        /// A ring with near-zero count indicates a blocked or failing beam.
+        pub fn per_ring_counts(&self) -> Vec<usize> {
+            let mut counts = vec![0; self.height];
+            if self.height == 0 {
+                return counts;
+            }
+            self.data.chunks(self.height).for_each(|column| {
+                izip!(counts.iter_mut(), column.iter())
+                    .filter(|(_, point)| point.primary_data().distance > Length::new::<meter>(0.0))
+                    .for_each(|(count, _)| *count += 1);
+            });
+            counts
+        }
+
+        /// Computes the convex hull of valid points projected onto the XY plane in meters.
+        ///
+        /// The vertices are in counter-clockwise order starting from the point with the
+        /// smallest x, and collinear points on the edges are excluded. It returns less than
+        /// three vertices if the points are collinear.
+        pub fn ground_footprint(&self) -> Vec<[f64; 2]> {
+            let mut points: Vec<[f64; 2]> = self
+                .data
+                .iter()
+                .map(|point| point.primary_data())
+                .filter(|data| data.distance > Length::new::<meter>(0.0))
+                .map(|data| {
+                    let [x, y, _] = data.position;
+                    [x.get::<meter>(), y.get::<meter>()]
+                })
+                .collect();
+            points.sort_by(|lhs, rhs| lhs.partial_cmp(rhs).unwrap());
+            points.dedup();
+            if points.len() < 3 {
+                return points;
+            }
+
+            // Andrew's monotone chain
+            let cross = |o: &[f64; 2], a: &[f64; 2], b: &[f64; 2]| {
+                (a[0] - o[0]) * (b[1] - o[1]) - (a[1] - o[1]) * (b[0] - o[0])
+            };
+            let mut hull: Vec<[f64; 2]> = Vec::with_capacity(points.len() + 1);
+            let push = |hull: &mut Vec<[f64; 2]>, min_len: usize, point: &[f64; 2]| {
+                while hull.len() >= min_len
+                    && cross(&hull[hull.len() - 2], &hull[hull.len() - 1], point) <= 0.0
+                {
+                    hull.pop();
+                }
+                hull.push(*point);
+            };
+            // lower hull, then upper hull which keeps the lower hull intact
+            points.iter().for_each(|point| push(&mut hull, 2, point));
+            let min_len = hull.len() + 1;
+            points
+                .iter()
+                .rev()
+                .skip(1)
+                .for_each(|point| push(&mut hull, min_len, point));
+            hull.pop();
+            hull
+        }
+
+        /// Converts valid points into sparse voxel entries `(i, j, k, value)` for occupancy grids,
+        /// where the value is the mean intensity of points in the voxel.
+        ///
+        /// The voxel index along each axis is the coordinate divided by `voxel_size` rounded
+        /// toward negative infinity. The entries are sorted by indices.
+        /// It panics if `voxel_size` is not positive.
+        pub fn to_voxel_coo(&self, voxel_size: Length) -> Vec<(i32, i32, i32, f32)> {
+            assert!(
+                voxel_size > Length::new::<meter>(0.0),
+                "the voxel size must be positive"
+            );
+            let mut voxels: BTreeMap<(i32, i32, i32), (f64, usize)> = BTreeMap::new();
+
+            self.data
+                .iter()
+                .map(|point| point.primary_data())
+                .filter(|data| data.distance > Length::new::<meter>(0.0))
+                .for_each(|data| {
+                    let [i, j, k] = data
+                        .position
+                        .map(|component| (component / voxel_size).value.floor() as i32);
+                    let (sum, count) = voxels.entry((i, j, k)).or_insert((0.0, 0));
+                    *sum += data.intensity as f64;
+                    *count += 1;
+                });
+
+            voxels
+                .into_iter()
+                .map(|((i, j, k), (sum, count))| (i, j, k, (sum / count as f64) as f32))
+                .collect()
+        }
+
+        /// Downsamples valid points to one point per voxel located at the centroid given by `mode`.
+        ///
+        /// The voxels are indexed as [to_voxel_coo](PcdFrame::to_voxel_coo). The output point takes
+        /// the other fields from the first point in the voxel, while the distance is recomputed
+        /// from the centroid and the intensity is averaged. The organized structure is lost, so the
+        /// returned frame has a single row sorted by voxel indices.
+        /// It panics if `voxel_size` is not positive.
+        pub fn voxel_downsample(&self, voxel_size: Length, mode: CentroidMode) -> Self {
+            assert!(
+                voxel_size > Length::new::<meter>(0.0),
+                "the voxel size must be positive"
+            );
+
+            struct Voxel<P> {
+                first: P,
+                sum: [f64; 3],
+                weighted_sum: [f64; 3],
+                total_intensity: f64,
+                total_intensity_value: f64,
+                count: usize,
+            }
+
+            let mut voxels: BTreeMap<(i32, i32, i32), Voxel<P>> = BTreeMap::new();
+            self.data
+                .iter()
+                .filter(|point| point.primary_data().distance > Length::new::<meter>(0.0))
+                .for_each(|point| {
+                    let data = point.primary_data();
+                    let position = data.position.map(|component| component.get::<meter>());
+                    let [i, j, k] = data
+                        .position
+                        .map(|component| (component / voxel_size).value.floor() as i32);
+                    let voxel = voxels.entry((i, j, k)).or_insert_with(|| Voxel {
+                        first: *point,
+                        sum: [0.0; 3],
+                        weighted_sum: [0.0; 3],
+                        total_intensity: 0.0,
+                        total_intensity_value: 0.0,
+                        count: 0,
+                    });
+                    let weight = data.intensity as f64;
+                    izip!(
+                        voxel.sum.iter_mut(),
+                        voxel.weighted_sum.iter_mut(),
+                        position.iter()
+                    )
+                    .for_each(|(sum, weighted_sum, value)| {
+                        *sum += value;
+                        *weighted_sum += value * weight;
+                    });
+                    voxel.total_intensity += weight;
+                    voxel.total_intensity_value += data.intensity_value;
+                    voxel.count += 1;
+                });
+
+            let data: Vec<P> = voxels
+                .into_values()
+                .map(|voxel| {
+                    let Voxel {
+                        mut first,
+                        sum,
+                        weighted_sum,
+                        total_intensity,
+                        total_intensity_value,
+                        count,
+                    } = voxel;
+                    let centroid = match mode {
+                        CentroidMode::IntensityWeighted if total_intensity > 0.0 => {
+                            weighted_sum.map(|value| value / total_intensity)
+                        }
+                        _ => sum.map(|value| value / count as f64),
+                    };
+
+                    let data = first.primary_data_mut();
+                    let [x, y, z] = centroid;
+                    data.position = centroid.map(Length::new::<meter>);
+                    data.distance = Length::new::<meter>((x * x + y * y + z * z).sqrt());
+                    data.intensity = (total_intensity / count as f64).round() as u8;
+                    data.intensity_value = total_intensity_value / count as f64;
+                    first
+                })
+                .collect();
+
+            Self {
+                height: 1,
+                width: data.len(),
+                data,
+            }
+        }
+
+        /// Drops the points with intensity below `min`, which are often noise on some surfaces.
+        ///
+        /// The organized structure is lost, so the returned frame has a single row.
+        /// Use [mask_intensity](PcdFrame::mask_intensity) to keep the frame organized.
+        pub fn filter_intensity(&self, min: u8) -> Self {
+            let data: Vec<_> = self
+                .data
+                .iter()
+                .filter(|point| point.primary_data().intensity >= min)
+                .copied()
+                .collect();
+            Self {
+                height: 1,
+                width: data.len(),
+                data,
+            }
+        }
+
+        /// Drops the near-duplicate points within `tolerance` of the last kept point of the same
+        /// ring, such as the shadow returns a few millimeters apart near edges.
+        ///
+        /// Each ring is swept once in column order, so only the adjacent duplicates are removed.
+        /// The points without returns are kept and not compared. The organized structure is lost,
+        /// so the returned frame has a single row in the original order.
+        pub fn dedup_spatial(&self, tolerance: Length) -> Self {
+            let mut last_kept: Vec<Option<[Length; 3]>> = vec![None; self.height];
+
+            let data: Vec<_> = self
+                .data
+                .iter()
+                .enumerate()
+                .filter(|(idx, point)| {
+                    let data = point.primary_data();
+                    if data.distance <= Length::new::<meter>(0.0) {
+                        return true;
+                    }
+
+                    let last = &mut last_kept[idx % self.height];
+                    let is_duplicate = match *last {
+                        Some([lx, ly, lz]) => {
+                            let [x, y, z] = data.position;
+                            let (dx, dy, dz) = (x - lx, y - ly, z - lz);
+                            (dx * dx + dy * dy + dz * dz).sqrt() <= tolerance
+                        }
+                        None => false,
+                    };
+                    if !is_duplicate {
+                        *last = Some(data.position);
+                    }
+                    !is_duplicate
+                })
+                .map(|(_, point)| *point)
+                .collect();
+
+            Self {
+                height: 1,
+                width: data.len(),
+                data,
+            }
+        }
+
+        /// Marks the points with intensity below `min` as invalid, that is, without returns,
+        /// while keeping the dimensions of the frame.
+        ///
+        /// The distance and position of the invalid points are set to zeros.
+        pub fn mask_intensity(&self, min: u8) -> Self {
+            let mut frame = self.clone();
+            frame
+                .data
+                .iter_mut()
+                .map(|point| point.primary_data_mut())
+                .filter(|data| data.intensity < min)
+                .for_each(|data| {
+                    data.distance = Length::new::<meter>(0.0);
+                    data.position = [Length::new::<meter>(0.0); 3];
+                });
+            frame
+        }
+
+        /// Fills the runs of invalid points on each ring, that is, points without returns,
+        /// by linear interpolation between the adjacent valid points of the same ring.
+        ///
+        /// Only the runs of at most `max_gap` columns with valid points on both sides are
+        /// filled, while larger gaps and the runs at frame edges are left untouched. The distance
+        /// and intensity are interpolated linearly, and the position lies on the interpolated
+        /// direction of the adjacent points.
+        pub fn interpolate_gaps(&mut self, max_gap: usize) {
+            let height = self.height;
+            if height == 0 || max_gap == 0 {
+                return;
+            }
+            let width = self.data.len() / height;
+            let is_valid = |point: &P| point.primary_data().distance > Length::new::<meter>(0.0);
+
+            for row_idx in 0..height {
+                let mut prev_valid: Option<usize> = None;
+                for col_idx in 0..width {
+                    if !is_valid(&self.data[col_idx * height + row_idx]) {
+                        continue;
+                    }
+                    if let Some(prev_col) = prev_valid {
+                        let gap = col_idx - prev_col - 1;
+                        if gap > 0 && gap <= max_gap {
+                            let lhs = *self.data[prev_col * height + row_idx].primary_data();
+                            let rhs = *self.data[col_idx * height + row_idx].primary_data();
+                            (1..=gap).for_each(|offset| {
+                                let ratio = offset as f64 / (gap + 1) as f64;
+                                let index = (prev_col + offset) * height + row_idx;
+                                *self.data[index].primary_data_mut() =
+                                    interpolate_point_data(&lhs, &rhs, ratio);
+                            });
+                        }
+                    }
+                    prev_valid = Some(col_idx);
+                }
+            }
+        }
+
+        /// Smooths the organized range image with a bilateral filter, which denoises flat
+        /// surfaces while preserving the discontinuities at object edges.
+        ///
+        /// The distance of each valid point is replaced by the weighted mean of the valid points
+        /// within `2 * spatial_sigma` rows and columns, where the weight is the product of
+        /// Gaussians of the pixel distance with `spatial_sigma` in pixels and the range difference
+        /// with `range_sigma`. The positions are rescaled along their directions accordingly.
+        /// It panics if any of the sigmas is not positive.
+        pub fn bilateral_smooth(&self, spatial_sigma: f64, range_sigma: Length) -> Self {
+            let range_sigma = range_sigma.get::<meter>();
+            assert!(
+                spatial_sigma > 0.0 && range_sigma > 0.0,
+                "the sigmas must be positive"
+            );
+            let height = self.height;
+            let mut frame = self.clone();
+            if height == 0 {
+                return frame;
+            }
+            let width = self.data.len() / height;
+            let radius = (spatial_sigma * 2.0).ceil() as isize;
+            let ranges: Vec<f64> = self
+                .data
+                .iter()
+                .map(|point| point.primary_data().distance.get::<meter>())
+                .collect();
+
+            for col_idx in 0..width {
+                for row_idx in 0..height {
+                    let center = ranges[col_idx * height + row_idx];
+                    if center <= 0.0 {
+                        continue;
+                    }
+
+                    let mut weight_sum = 0.0;
+                    let mut range_sum = 0.0;
+                    for col_offset in -radius..=radius {
+                        for row_offset in -radius..=radius {
+                            let col = col_idx as isize + col_offset;
+                            let row = row_idx as isize + row_offset;
+                            if col < 0 || col >= width as isize || row < 0 || row >= height as isize
+                            {
+                                continue;
+                            }
+                            let range = ranges[col as usize * height + row as usize];
+                            if range <= 0.0 {
+                                continue;
+                            }
+
+                            let pixel_dist2 = (col_offset.pow(2) + row_offset.pow(2)) as f64;
+                            let weight = (-pixel_dist2 / (2.0 * spatial_sigma.powi(2))
+                                - (range - center).powi(2) / (2.0 * range_sigma.powi(2)))
+                            .exp();
+                            weight_sum += weight;
+                            range_sum += weight * range;
+                        }
+                    }
+
+                    let smoothed = range_sum / weight_sum;
+                    let data = frame.data[col_idx * height + row_idx].primary_data_mut();
+                    let ratio = smoothed / center;
+                    data.distance = Length::new::<meter>(smoothed);
+                    data.position
+                        .iter_mut()
+                        .for_each(|component| *component *= ratio);
+                }
+            }
+
+            frame
+        }
+
+        /// Marks the returns with distance out of `range` as invalid, while keeping the
+        /// dimensions of the frame. The returns of dual return points are masked separately,
+        /// and the points without returns are left invalid.
+        ///
+        /// The distance and position of the invalid returns are set to zeros.
+        pub fn mask_range(&self, range: Range<Length>) -> Self {
+            let mut frame = self.clone();
+            frame.data.iter_mut().for_each(|point| {
+                point.for_each_return_mut(|data| {
+                    if !range.contains(&data.distance) {
+                        data.distance = Length::new::<meter>(0.0);
+                        data.position = [Length::new::<meter>(0.0); 3];
+                    }
+                })
+            });
+            frame
+        }
+
+        /// Computes the local curvature of each point of a ring in column order, which is the
+        /// building block to select edge and planar features as LOAM.
+        ///
+        /// The curvature of the point at column `i` is the absolute sum of range second
+        /// differences `|Σ (r[i - k] + r[i + k] - 2 r[i])|` over `k` from 1 to `window`,
+        /// divided by `2 * window * r[i]`, so that it is scale invariant. The value is NaN
+        /// if the point or any of its neighbors has no return, or the point is within
+        /// `window` columns from the frame borders. It returns an empty vector if the ring
+        /// is out of range or `window` is zero.
+        pub fn ring_curvature(&self, ring: u16, window: usize) -> Vec<f64> {
+            let ring = ring as usize;
+            if ring >= self.height || window == 0 {
+                return vec![];
+            }
+
+            let ranges: Vec<Option<f64>> = self
+                .data
+                .chunks(self.height)
+                .map(|column| {
+                    let distance = column[ring].primary_data().distance.get::<meter>();
+                    if distance > 0.0 {
+                        Some(distance)
+                    } else {
+                        None
+                    }
+                })
+                .collect();
+
+            (0..ranges.len())
+                .map(|col_idx| {
+                    if col_idx < window || col_idx + window >= ranges.len() {
+                        return f64::NAN;
+                    }
+                    let center = match ranges[col_idx] {
+                        Some(center) => center,
+                        None => return f64::NAN,
+                    };
+                    let sum = (1..=window).try_fold(0.0, |sum, offset| {
+                        let prev = ranges[col_idx - offset]?;
+                        let next = ranges[col_idx + offset]?;
+                        Some(sum + prev + next - 2.0 * center)
+                    });
+                    match sum {
+                        Some(sum) => sum.abs() / (2.0 * window as f64 * center),
+                        None => f64::NAN,
+                    }
+                })
+                .collect()
+        }
+    }
+
+    impl<P> PcdFrame<P>
+    where
+        P: Copy + VelodynePoint,
+    {
+        /// Returns the `(start, end)` timestamps of the earliest and latest points.
+        ///
+        /// It returns `None` if the frame has no points.
+        pub fn time_span(&self) -> Option<(Time, Time)> {
+            let mut timestamps = self.data.iter().map(|point| point.timestamp());
+            let first = timestamps.next()?;
+            let span = timestamps.fold((first, first), |(start, end), timestamp| {
+                let start = if timestamp < start { timestamp } else { start };
+                let end = if timestamp > end { timestamp } else { end };
+                (start, end)
+            });
+            Some(span)
+        }
+
+        /// Returns the elapsed time from the earliest to the latest point, or zero for empty frame.
+        ///
+        /// Under the constant-velocity assumption, the ego-motion within the frame is
+        /// estimated by multiplying it with an external velocity, for example,
+        /// `velocity * frame.intra_frame_duration()`. Frames with a large motion
+        /// can be selected for deskewing.
+        pub fn intra_frame_duration(&self) -> Time {
+            self.time_span()
+                .map(|(start, end)| end - start)
+                .unwrap_or_else(|| Time::new::<second>(0.0))
+        }
+
+        /// Returns the median of the column timestamps as the frame time, or zero for empty frame.
+        ///
+        /// The timestamp of a column is that of its earliest point. Unlike the timestamp of the
+        /// first point, the median is not shifted by a few columns with corrupted timestamps.
+        pub fn robust_timestamp(&self) -> Time {
+            if self.height == 0 {
+                return Time::new::<second>(0.0);
+            }
+
+            let mut timestamps: Vec<Time> = self
+                .data
+                .chunks(self.height)
+                .filter_map(|column| {
+                    column
+                        .iter()
+                        .map(|point| point.timestamp())
+                        .min_by(|lhs, rhs| lhs.partial_cmp(rhs).unwrap())
+                })
+                .collect();
+            if timestamps.is_empty() {
+                return Time::new::<second>(0.0);
+            }
+            timestamps.sort_by(|lhs, rhs| lhs.partial_cmp(rhs).unwrap());
+
+            let mid = timestamps.len() / 2;
+            if timestamps.len() % 2 == 1 {
+                timestamps[mid]
+            } else {
+                (timestamps[mid - 1] + timestamps[mid]) / 2.0
+            }
+        }
+
+        /// Checks if the azimuth of the columns covers a full rotation, allowing a gap of two
+        /// azimuth steps given by [observed_azimuth_resolution](PcdFrame::observed_azimuth_resolution).
+        ///
+        /// The coverage is the sum of azimuth steps between adjacent columns, so it is not
+        /// affected by where the frame crosses the zero azimuth.
+        pub fn spans_full_rotation(&self) -> bool {
+            let (_start, coverage) = match self.azimuth_coverage() {
+                Some(coverage) => coverage,
+                None => return false,
+            };
+            let tolerance = self.observed_azimuth_resolution().get::<degree>() * 2.0;
+            coverage > 0.0 && coverage >= 360.0 - tolerance
+        }
+
+        /// Computes the overlap ratio of the azimuth coverage of two frames, which is the
+        /// intersection over union of the covered arcs, ranging from 0.0 to 1.0.
+        ///
+        /// A ratio near 1.0 on consecutive frames suggests a stalled sensor, while a low ratio
+        /// suggests dropped data. It returns 0.0 if either frame has less than two columns.
+        pub fn azimuth_overlap<Q>(&self, other: &PcdFrame<Q>) -> f64
+        where
+            Q: Copy + VelodynePoint,
+        {
+            let (lhs_start, lhs_len, rhs_start, rhs_len) =
+                match (self.azimuth_coverage(), other.azimuth_coverage()) {
+                    (Some((lhs_start, lhs_len)), Some((rhs_start, rhs_len))) => {
+                        (lhs_start, lhs_len.min(360.0), rhs_start, rhs_len.min(360.0))
+                    }
+                    _ => return 0.0,
+                };
+
+            // intersect the arcs on the unrolled circle
+            let rhs_start = lhs_start + (rhs_start - lhs_start).rem_euclid(360.0);
+            let intersection: f64 = [rhs_start - 360.0, rhs_start]
+                .iter()
+                .map(|&start| {
+                    let lower = start.max(lhs_start);
+                    let upper = (start + rhs_len).min(lhs_start + lhs_len);
+                    (upper - lower).max(0.0)
+                })
+                .sum();
+            let intersection = intersection.min(lhs_len).min(rhs_len);
+            let union = lhs_len + rhs_len - intersection;
+
+            if union > 0.0 {
+                intersection / union
+            } else {
+                0.0
+            }
+        }
+
+        /// Returns the starting azimuth and the swept angle in degrees of the columns.
+        fn azimuth_coverage(&self) -> Option<(f64, f64)> {
+            if self.height == 0 {
+                return None;
+            }
+
+            let azimuths: Vec<f64> = self
+                .data
+                .chunks(self.height)
+                .map(|column| column[0].original_azimuth_angle().get::<degree>())
+                .collect();
+            let start = *azimuths.first()?;
+            let coverage: f64 = azimuths
+                .windows(2)
+                .map(|pair| (pair[1] - pair[0]).rem_euclid(360.0))
+                .sum();
+            Some((start.rem_euclid(360.0), coverage))
+        }
+
+        /// Returns the median azimuth step between adjacent columns, which varies with
+        /// the actual motor speed. It returns zero if the frame has less than two columns.
+        ///
+        /// Comparing it with the nominal resolution helps detecting RPM instability.
+        pub fn observed_azimuth_resolution(&self) -> Angle {
+            if self.height == 0 {
+                return Angle::new::<radian>(0.0);
+            }
+
+            let azimuths: Vec<f64> = self
+                .data
+                .chunks(self.height)
+                .map(|column| column[0].original_azimuth_angle().get::<degree>())
+                .collect();
+            let mut steps: Vec<f64> = azimuths
+                .windows(2)
+                .map(|pair| (pair[1] - pair[0]).rem_euclid(360.0))
+                .collect();
+            if steps.is_empty() {
+                return Angle::new::<radian>(0.0);
+            }
+            steps.sort_by(|lhs, rhs| lhs.partial_cmp(rhs).unwrap());
+
+            // average the two middle elements for even length
+            let len = steps.len();
+            let median = (steps[(len - 1) / 2] + steps[len / 2]) / 2.0;
+            Angle::new::<degree>(median)
+        }
+
+        /// Returns the `(start, end)` azimuths of the intervals between adjacent columns
+        /// that are wider than `min_gap`, in the order of columns.
+        ///
+        /// The azimuths are wrapped into [0, 360) degrees, so the end is less than the start
+        /// if the gap crosses the zero azimuth. The interval from the last column back to
+        /// the first column is also considered. It returns no gaps if the frame has less
+        /// than two columns.
+        pub fn azimuth_gaps(&self, min_gap: Angle) -> Vec<(Angle, Angle)> {
+            if self.height == 0 {
+                return vec![];
+            }
+
+            let azimuths: Vec<f64> = self
+                .data
+                .chunks(self.height)
+                .map(|column| {
+                    column[0]
+                        .original_azimuth_angle()
+                        .get::<degree>()
+                        .rem_euclid(360.0)
+                })
+                .collect();
+            if azimuths.len() < 2 {
+                return vec![];
+            }
+            let min_gap = min_gap.get::<degree>();
+
+            izip!(azimuths.iter(), azimuths.iter().cycle().skip(1))
+                .filter(|&(&start, &end)| (end - start).rem_euclid(360.0) > min_gap)
+                .map(|(&start, &end)| (Angle::new::<degree>(start), Angle::new::<degree>(end)))
+                .collect()
+        }
+    }
+
+    impl PcdFrame<DualReturnPoint> {
+        /// Keeps the nearer of the strongest and last returns of each point,
+        /// preserving the dimensions of the frame.
+        ///
+        /// A return with zero distance is considered missing, so the other return is kept.
+        pub fn nearest_only(&self) -> PcdFrame<SingleReturnPoint> {
+            let data = self
+                .data
+                .iter()
+                .map(|point| {
+                    let DualReturnPoint {
+                        laser_id,
+                        timestamp,
+                        firing_time_offset,
+                        original_azimuth_angle,
+                        corrected_azimuth_angle,
+                        strongest_return_data,
+                        last_return_data,
+                        lidar_frame_entry,
+                        ..
+                    } = *point;
+
+                    let zero = Length::new::<meter>(0.0);
+                    let last_is_nearer = strongest_return_data.distance == zero
+                        || (last_return_data.distance != zero
+                            && last_return_data.distance < strongest_return_data.distance);
+                    let (data, return_kind) = if last_is_nearer {
+                        (last_return_data, ReturnKind::Last)
+                    } else {
+                        (strongest_return_data, ReturnKind::Strongest)
+                    };
+
+                    SingleReturnPoint {
+                        laser_id,
+                        timestamp,
+                        firing_time_offset,
+                        original_azimuth_angle,
+                        corrected_azimuth_angle,
+                        data,
+                        lidar_frame_entry,
+                        return_kind,
+                    }
+                })
+                .collect();
+
+            PcdFrame {
+                height: self.height,
+                width: self.width,
+                data,
+            }
+        }
+    }
+
+    impl<P> PcdFrame<P>
+    where
+        P: Copy + LidarFrameMsg,
+    {
+        /// Keeps every `factor`-th ring in elevation order, for example, to reduce a 128-beam
+        /// frame to 64 beams.
+        ///
+        /// The rings 0, `factor`, `2 * factor` and so on are kept, and the row indices of points
+        /// are renumbered. If `factor` does not divide the height, the trailing rings are
+        /// truncated, that is, the height becomes `height / factor` rounded up.
+        /// It panics if `factor` is zero.
+        pub fn decimate_rings(&self, factor: usize) -> Self {
+            assert!(factor > 0, "the decimation factor must be nonzero");
+            if self.height == 0 {
+                return self.clone();
+            }
+
+            let height = self.height.div_ceil(factor);
+            let data: Vec<_> = self
+                .data
+                .chunks(self.height)
+                .flat_map(|column| {
+                    column
+                        .iter()
+                        .step_by(factor)
+                        .enumerate()
+                        .map(|(row_idx, point)| {
+                            let mut point = *point;
+                            point.set_row_idx(row_idx);
+                            point
+                        })
+                })
+                .collect();
+
+            Self {
+                height,
+                width: self.width,
+                data,
+            }
+        }
+    }
+
+    impl<P> PcdFrame<P>
+    where
+        P: Copy + VelodynePoint + PrimaryData,
+    {
+        /// Extracts the measurements of a ring, that is, a row of the frame, as a polar scan
+        /// of `(azimuth, range)` pairs ordered by the corrected azimuth angle.
+        ///
+        /// The points without returns are kept with zero range, so that the scan has one
+        /// measurement per column. It returns an empty scan if the ring is out of range.
+        pub fn ring_as_scan(&self, ring: u16) -> Vec<(Angle, Length)> {
+            let ring = ring as usize;
+            if ring >= self.height {
+                return vec![];
+            }
+
+            let mut scan: Vec<_> = self
+                .data
+                .chunks(self.height)
+                .map(|column| {
+                    let point = &column[ring];
+                    (
+                        point.corrected_azimuth_angle(),
+                        point.primary_data().distance,
+                    )
+                })
+                .collect();
+            scan.sort_by(|(lhs, _), (rhs, _)| lhs.partial_cmp(rhs).unwrap());
+            scan
+        }
+
+        /// Summarizes the valid returns of a ring, that is, a row of the frame,
+        /// for fault detection and auto-exposure.
+        ///
+        /// The means are zero if the ring has no valid returns or is out of range.
+        pub fn ring_summary(&self, ring: u16) -> RingSummary {
+            let zero = Length::new::<meter>(0.0);
+            let ring = ring as usize;
+            let points: Vec<&P> = if ring < self.height {
+                self.data
+                    .chunks(self.height)
+                    .map(|column| &column[ring])
+                    .filter(|point| point.primary_data().distance > zero)
+                    .collect()
+            } else {
+                vec![]
+            };
+
+            let count = points.len();
+            if count == 0 {
+                return RingSummary {
+                    count,
+                    mean_range: zero,
+                    mean_intensity: 0.0,
+                    centroid: [zero; 3],
+                    azimuth_span: Angle::new::<degree>(0.0),
+                };
+            }
+
+            let mut range_sum = zero;
+            let mut intensity_sum = 0.0;
+            let mut position_sum = [zero; 3];
+            points.iter().for_each(|point| {
+                let data = point.primary_data();
+                range_sum += data.distance;
+                intensity_sum += data.intensity as f64;
+                izip!(position_sum.iter_mut(), data.position.iter())
+                    .for_each(|(sum, &component)| *sum += component);
+            });
+
+            // the span is the complement of the widest gap between adjacent azimuths
+            let mut azimuths: Vec<f64> = points
+                .iter()
+                .map(|point| {
+                    point
+                        .corrected_azimuth_angle()
+                        .get::<degree>()
+                        .rem_euclid(360.0)
+                })
+                .collect();
+            azimuths.sort_by(|lhs, rhs| lhs.partial_cmp(rhs).unwrap());
+            let widest_gap = izip!(azimuths.iter(), azimuths.iter().cycle().skip(1))
+                .map(|(&lhs, &rhs)| (rhs - lhs).rem_euclid(360.0))
+                .fold(0.0, f64::max);
+            let azimuth_span = if count > 1 { 360.0 - widest_gap } else { 0.0 };
+
+            RingSummary {
+                count,
+                mean_range: range_sum / count as f64,
+                mean_intensity: intensity_sum / count as f64,
+                centroid: position_sum.map(|sum| sum / count as f64),
+                azimuth_span: Angle::new::<degree>(azimuth_span),
+            }
+        }
+
+        /// Summarizes the frame for dataset packaging. The frame ID, model and return mode are
+        /// given by the caller since frames do not keep them.
+        pub fn metadata(
+            &self,
+            frame_id: u64,
+            model: DynamicModel,
+            return_mode: ReturnMode,
+        ) -> FrameMetadata {
+            let timestamp = self
+                .time_span()
+                .map(|(start, _)| start.get::<second>())
+                .unwrap_or(0.0);
+            let point_count = self
+                .data
+                .iter()
+                .filter(|point| point.primary_data().distance > Length::new::<meter>(0.0))
+                .count();
+            let azimuth_coverage = self
+                .azimuth_coverage()
+                .map(|(_, coverage)| coverage.min(360.0))
+                .unwrap_or(0.0);
+
+            FrameMetadata {
+                frame_id,
+                timestamp,
+                point_count,
+                width: self.width,
+                height: self.height,
+                model: format!("{:?}", model),
+                return_mode: format!("{:?}", return_mode),
+                azimuth_coverage,
+            }
+        }
+
+        /// Serializes the [metadata](PcdFrame::metadata) of the frame into a JSON object.
+        pub fn metadata_json(
+            &self,
+            frame_id: u64,
+            model: DynamicModel,
+            return_mode: ReturnMode,
+        ) -> String {
+            serde_json::to_string(&self.metadata(frame_id, model, return_mode)).unwrap()
+        }
+
+        /// Returns the nearest range in each of `sectors` equal-width azimuth sectors
+        /// for simple collision checks.
+        ///
+        /// The sector `i` spans the corrected azimuth angles in [i, i + 1) × 360° / `sectors`
+        /// clockwise from the front of sensor. Points without returns are ignored, and the
+        /// sectors without points are infinite. It panics if `sectors` is zero.
+        pub fn min_range_per_sector(&self, sectors: usize) -> Vec<Length> {
+            assert!(sectors > 0, "the number of sectors must be nonzero");
+            let mut ranges = vec![Length::new::<meter>(f64::INFINITY); sectors];
+
+            self.data
+                .iter()
+                .filter(|point| point.primary_data().distance > Length::new::<meter>(0.0))
+                .for_each(|point| {
+                    let azimuth = point
+                        .corrected_azimuth_angle()
+                        .get::<degree>()
+                        .rem_euclid(360.0);
+                    let sector = ((azimuth / 360.0 * sectors as f64) as usize).min(sectors - 1);
+                    let distance = point.primary_data().distance;
+                    if distance < ranges[sector] {
+                        ranges[sector] = distance;
+                    }
+                });
+
+            ranges
+        }
+    }
+
+    impl<P> PcdFrame<P>
+    where
+        P: Copy + VelodynePoint + LidarFrameMsg,
+    {
+        /// Concatenates a frame that immediately follows this frame, such as the partial
+        /// frames at the boundary of two consecutive captures.
+        ///
+        /// It fails if the frame heights differ, or the azimuth ranges are not contiguous.
+        pub fn try_merge(mut self, other: Self) -> Result<Self> {
+            if self.data.is_empty() {
+                return Ok(other);
+            }
+            if other.data.is_empty() {
+                return Ok(self);
+            }
+            ensure!(
+                self.height == other.height,
+                "cannot merge frames with heights {} and {}",
+                self.height,
+                other.height
+            );
+
+            let column_azimuths = |frame: &Self| -> Vec<Angle> {
+                frame
+                    .data
+                    .chunks(frame.height)
+                    .map(|column| column[0].original_azimuth_angle())
+                    .collect()
+            };
+            let lhs_azimuths = column_azimuths(&self);
+            let rhs_azimuths = column_azimuths(&other);
+            let lhs_last = *lhs_azimuths.last().unwrap();
+            let rhs_first = rhs_azimuths[0];
+            ensure!(
+                lhs_last < rhs_first,
+                "the azimuth of merged frame does not follow the previous frame"
+            );
+
+            // the gap between frames must be comparable to the column spacing, which falls back
+            // to the widest spacing of supported sensors if no frame has adjacent columns
+            let max_step = lhs_azimuths
+                .windows(2)
+                .chain(rhs_azimuths.windows(2))
+                .map(|pair| pair[1] - pair[0])
+                .fold(None, |max: Option<Angle>, step| match max {
+                    Some(max) if max >= step => Some(max),
+                    _ => Some(step),
+                })
+                .unwrap_or_else(|| {
+                    Angle::new::<degree>(MAX_RPM / 60.0 * 360.0 * FIRING_PERIOD * 1e-6)
+                });
+            ensure!(
+                rhs_first - lhs_last <= max_step * 2.0,
+                "the azimuth ranges of frames are not contiguous"
+            );
+
+            let Self {
+                width: other_width,
+                data: mut other_data,
+                ..
+            } = other;
+            let col_offset = self.width;
+            other_data
+                .iter_mut()
+                .for_each(|point| point.set_col_idx(point.col_idx() + col_offset));
+
+            self.width += other_width;
+            self.data.append(&mut other_data);
+            Ok(self)
+        }
+    }
+
+    /// Interpolates the distance, intensity and direction of two valid returns.
+    fn interpolate_point_data(lhs: &PointData, rhs: &PointData, ratio: f64) -> PointData {
+        let distance = lhs.distance + (rhs.distance - lhs.distance) * ratio;
+        let intensity_value =
+            lhs.intensity_value + (rhs.intensity_value - lhs.intensity_value) * ratio;
+        let intensity = (lhs.intensity as f64
+            + (rhs.intensity as f64 - lhs.intensity as f64) * ratio)
+            .round() as u8;
+
+        let direction = |data: &PointData| -> [f64; 3] {
+            let [x, y, z] = data.position;
+            let [x, y, z] = [x.get::<meter>(), y.get::<meter>(), z.get::<meter>()];
+            let norm = (x * x + y * y + z * z).sqrt();
+            [x / norm, y / norm, z / norm]
+        };
+        let mut direction = {
+            let lhs = direction(lhs);
+            let rhs = direction(rhs);
+            let mut direction = [0.0; 3];
+            izip!(direction.iter_mut(), lhs.iter(), rhs.iter())
+                .for_each(|(value, lhs, rhs)| *value = lhs + (rhs - lhs) * ratio);
+            direction
+        };
+        let norm = direction
+            .iter()
+            .map(|value| value * value)
+            .sum::<f64>()
+            .sqrt();
+        direction.iter_mut().for_each(|value| *value /= norm);
+        let [x, y, z] = direction;
+
+        PointData {
+            distance,
+            intensity,
+            intensity_value,
+            position: [distance * x, distance * y, distance * z],
+            suspect: false,
+        }
+    }
 }
 
 mod converter_impls {
@@ -171,6 +1346,7 @@ mod converter_impls {
             Self {
                 pcd_converter: Dynamic_PcdConverter::from_config(config),
                 remaining_points,
+                skip_first_partial: false,
             }
         }
 
@@ -181,9 +1357,16 @@ mod converter_impls {
             let Self {
                 pcd_converter,
                 remaining_points,
+                skip_first_partial,
             } = self;
 
-            impls::convert_dynamic_return(pcd_converter, remaining_points, packet.borrow())
+            let frame =
+                impls::convert_dynamic_return(pcd_converter, remaining_points, packet.borrow());
+            impls::skip_first_partial(
+                skip_first_partial,
+                frame,
+                DynamicReturnFrame::spans_full_rotation,
+            )
         }
 
         fn pop_remaining(&mut self) -> Option<Self::Remain> {
@@ -194,6 +1377,26 @@ mod converter_impls {
                 Some(remaining)
             }
         }
+
+        fn finish(&mut self) -> Option<Self::Frame> {
+            let frame = match self.remaining_points.take() {
+                DynamicReturnPoints::Single(points) => {
+                    impls::remaining_to_frame(points).map(DynamicReturnFrame::Single)
+                }
+                DynamicReturnPoints::Dual(points) => {
+                    impls::remaining_to_frame(points).map(DynamicReturnFrame::Dual)
+                }
+            };
+            impls::skip_first_partial(
+                &mut self.skip_first_partial,
+                frame,
+                DynamicReturnFrame::spans_full_rotation,
+            )
+        }
+
+        fn set_skip_first_partial(&mut self, skip: bool) {
+            self.skip_first_partial = skip;
+        }
     }
 
     impl FrameConverter<Vlp16, LastReturn> for Vlp16_Last_FrameConverter {
@@ -204,6 +1407,7 @@ mod converter_impls {
             Self {
                 pcd_converter: Vlp16_Last_PcdConverter::from_config(config),
                 remaining_points: vec![],
+                skip_first_partial: false,
             }
         }
 
@@ -214,9 +1418,12 @@ mod converter_impls {
             let Self {
                 pcd_converter,
                 remaining_points,
+                skip_first_partial,
             } = self;
 
-            impls::convert_single_return(pcd_converter, remaining_points, packet.borrow())
+            let frame =
+                impls::convert_single_return(pcd_converter, remaining_points, packet.borrow());
+            impls::skip_first_partial(skip_first_partial, frame, PcdFrame::spans_full_rotation)
         }
 
         fn pop_remaining(&mut self) -> Option<Self::Remain> {
@@ -226,6 +1433,19 @@ mod converter_impls {
                 Some(mem::take(&mut self.remaining_points))
             }
         }
+
+        fn finish(&mut self) -> Option<Self::Frame> {
+            let frame = impls::remaining_to_frame(mem::take(&mut self.remaining_points));
+            impls::skip_first_partial(
+                &mut self.skip_first_partial,
+                frame,
+                PcdFrame::spans_full_rotation,
+            )
+        }
+
+        fn set_skip_first_partial(&mut self, skip: bool) {
+            self.skip_first_partial = skip;
+        }
     }
 
     impl FrameConverter<Vlp16, StrongestReturn> for Vlp16_Strongest_FrameConverter {
@@ -236,6 +1456,7 @@ mod converter_impls {
             Self {
                 pcd_converter: Vlp16_Strongest_PcdConverter::from_config(config),
                 remaining_points: vec![],
+                skip_first_partial: false,
             }
         }
 
@@ -246,9 +1467,12 @@ mod converter_impls {
             let Self {
                 pcd_converter,
                 remaining_points,
+                skip_first_partial,
             } = self;
 
-            impls::convert_single_return(pcd_converter, remaining_points, packet.borrow())
+            let frame =
+                impls::convert_single_return(pcd_converter, remaining_points, packet.borrow());
+            impls::skip_first_partial(skip_first_partial, frame, PcdFrame::spans_full_rotation)
         }
 
         fn pop_remaining(&mut self) -> Option<Self::Remain> {
@@ -258,6 +1482,19 @@ mod converter_impls {
                 Some(mem::take(&mut self.remaining_points))
             }
         }
+
+        fn finish(&mut self) -> Option<Self::Frame> {
+            let frame = impls::remaining_to_frame(mem::take(&mut self.remaining_points));
+            impls::skip_first_partial(
+                &mut self.skip_first_partial,
+                frame,
+                PcdFrame::spans_full_rotation,
+            )
+        }
+
+        fn set_skip_first_partial(&mut self, skip: bool) {
+            self.skip_first_partial = skip;
+        }
     }
 
     impl FrameConverter<Vlp16, DualReturn> for Vlp16_Dual_FrameConverter {
@@ -268,6 +1505,7 @@ mod converter_impls {
             Self {
                 pcd_converter: Vlp16_Dual_PcdConverter::from_config(config),
                 remaining_points: vec![],
+                skip_first_partial: false,
             }
         }
 
@@ -278,9 +1516,12 @@ mod converter_impls {
             let Self {
                 pcd_converter,
                 remaining_points,
+                skip_first_partial,
             } = self;
 
-            impls::convert_dual_return(pcd_converter, remaining_points, packet.borrow())
+            let frame =
+                impls::convert_dual_return(pcd_converter, remaining_points, packet.borrow());
+            impls::skip_first_partial(skip_first_partial, frame, PcdFrame::spans_full_rotation)
         }
 
         fn pop_remaining(&mut self) -> Option<Self::Remain> {
@@ -290,6 +1531,19 @@ mod converter_impls {
                 Some(mem::take(&mut self.remaining_points))
             }
         }
+
+        fn finish(&mut self) -> Option<Self::Frame> {
+            let frame = impls::remaining_to_frame(mem::take(&mut self.remaining_points));
+            impls::skip_first_partial(
+                &mut self.skip_first_partial,
+                frame,
+                PcdFrame::spans_full_rotation,
+            )
+        }
+
+        fn set_skip_first_partial(&mut self, skip: bool) {
+            self.skip_first_partial = skip;
+        }
     }
 
     impl FrameConverter<Vlp16, DynamicReturn> for Vlp16_Dynamic_FrameConverter {
@@ -301,6 +1555,7 @@ mod converter_impls {
             Self {
                 pcd_converter: Vlp16_Dynamic_PcdConverter::from_config(config),
                 remaining_points,
+                skip_first_partial: false,
             }
         }
 
@@ -311,9 +1566,16 @@ mod converter_impls {
             let Self {
                 pcd_converter,
                 remaining_points,
+                skip_first_partial,
             } = self;
 
-            impls::convert_dynamic_return(pcd_converter, remaining_points, packet.borrow())
+            let frame =
+                impls::convert_dynamic_return(pcd_converter, remaining_points, packet.borrow());
+            impls::skip_first_partial(
+                skip_first_partial,
+                frame,
+                DynamicReturnFrame::spans_full_rotation,
+            )
         }
 
         fn pop_remaining(&mut self) -> Option<Self::Remain> {
@@ -324,6 +1586,26 @@ mod converter_impls {
                 Some(remaining)
             }
         }
+
+        fn finish(&mut self) -> Option<Self::Frame> {
+            let frame = match self.remaining_points.take() {
+                DynamicReturnPoints::Single(points) => {
+                    impls::remaining_to_frame(points).map(DynamicReturnFrame::Single)
+                }
+                DynamicReturnPoints::Dual(points) => {
+                    impls::remaining_to_frame(points).map(DynamicReturnFrame::Dual)
+                }
+            };
+            impls::skip_first_partial(
+                &mut self.skip_first_partial,
+                frame,
+                DynamicReturnFrame::spans_full_rotation,
+            )
+        }
+
+        fn set_skip_first_partial(&mut self, skip: bool) {
+            self.skip_first_partial = skip;
+        }
     }
 
     impl FrameConverter<Vlp32, LastReturn> for Vlp32_Last_FrameConverter {
@@ -334,6 +1616,7 @@ mod converter_impls {
             Self {
                 pcd_converter: Vlp32_Last_PcdConverter::from_config(config),
                 remaining_points: vec![],
+                skip_first_partial: false,
             }
         }
 
@@ -344,9 +1627,12 @@ mod converter_impls {
             let Self {
                 pcd_converter,
                 remaining_points,
+                skip_first_partial,
             } = self;
 
-            impls::convert_single_return(pcd_converter, remaining_points, packet.borrow())
+            let frame =
+                impls::convert_single_return(pcd_converter, remaining_points, packet.borrow());
+            impls::skip_first_partial(skip_first_partial, frame, PcdFrame::spans_full_rotation)
         }
 
         fn pop_remaining(&mut self) -> Option<Self::Remain> {
@@ -356,6 +1642,19 @@ mod converter_impls {
                 Some(mem::take(&mut self.remaining_points))
             }
         }
+
+        fn finish(&mut self) -> Option<Self::Frame> {
+            let frame = impls::remaining_to_frame(mem::take(&mut self.remaining_points));
+            impls::skip_first_partial(
+                &mut self.skip_first_partial,
+                frame,
+                PcdFrame::spans_full_rotation,
+            )
+        }
+
+        fn set_skip_first_partial(&mut self, skip: bool) {
+            self.skip_first_partial = skip;
+        }
     }
 
     impl FrameConverter<Vlp32, StrongestReturn> for Vlp32_Strongest_FrameConverter {
@@ -366,6 +1665,7 @@ mod converter_impls {
             Self {
                 pcd_converter: Vlp32_Strongest_PcdConverter::from_config(config),
                 remaining_points: vec![],
+                skip_first_partial: false,
             }
         }
 
@@ -376,9 +1676,12 @@ mod converter_impls {
             let Self {
                 pcd_converter,
                 remaining_points,
+                skip_first_partial,
             } = self;
 
-            impls::convert_single_return(pcd_converter, remaining_points, packet.borrow())
+            let frame =
+                impls::convert_single_return(pcd_converter, remaining_points, packet.borrow());
+            impls::skip_first_partial(skip_first_partial, frame, PcdFrame::spans_full_rotation)
         }
 
         fn pop_remaining(&mut self) -> Option<Self::Remain> {
@@ -388,6 +1691,19 @@ mod converter_impls {
                 Some(mem::take(&mut self.remaining_points))
             }
         }
+
+        fn finish(&mut self) -> Option<Self::Frame> {
+            let frame = impls::remaining_to_frame(mem::take(&mut self.remaining_points));
+            impls::skip_first_partial(
+                &mut self.skip_first_partial,
+                frame,
+                PcdFrame::spans_full_rotation,
+            )
+        }
+
+        fn set_skip_first_partial(&mut self, skip: bool) {
+            self.skip_first_partial = skip;
+        }
     }
 
     impl FrameConverter<Vlp32, DualReturn> for Vlp32_Dual_FrameConverter {
@@ -398,6 +1714,7 @@ mod converter_impls {
             Self {
                 pcd_converter: Vlp32_Dual_PcdConverter::from_config(config),
                 remaining_points: vec![],
+                skip_first_partial: false,
             }
         }
 
@@ -408,9 +1725,12 @@ mod converter_impls {
             let Self {
                 pcd_converter,
                 remaining_points,
+                skip_first_partial,
             } = self;
 
-            impls::convert_dual_return(pcd_converter, remaining_points, packet.borrow())
+            let frame =
+                impls::convert_dual_return(pcd_converter, remaining_points, packet.borrow());
+            impls::skip_first_partial(skip_first_partial, frame, PcdFrame::spans_full_rotation)
         }
 
         fn pop_remaining(&mut self) -> Option<Self::Remain> {
@@ -420,6 +1740,19 @@ mod converter_impls {
                 Some(mem::take(&mut self.remaining_points))
             }
         }
+
+        fn finish(&mut self) -> Option<Self::Frame> {
+            let frame = impls::remaining_to_frame(mem::take(&mut self.remaining_points));
+            impls::skip_first_partial(
+                &mut self.skip_first_partial,
+                frame,
+                PcdFrame::spans_full_rotation,
+            )
+        }
+
+        fn set_skip_first_partial(&mut self, skip: bool) {
+            self.skip_first_partial = skip;
+        }
     }
 
     impl FrameConverter<Vlp32, DynamicReturn> for Vlp32_Dynamic_FrameConverter {
@@ -431,6 +1764,7 @@ mod converter_impls {
             Self {
                 pcd_converter: Vlp32_Dynamic_PcdConverter::from_config(config),
                 remaining_points,
+                skip_first_partial: false,
             }
         }
 
@@ -441,9 +1775,16 @@ mod converter_impls {
             let Self {
                 pcd_converter,
                 remaining_points,
+                skip_first_partial,
             } = self;
 
-            impls::convert_dynamic_return(pcd_converter, remaining_points, packet.borrow())
+            let frame =
+                impls::convert_dynamic_return(pcd_converter, remaining_points, packet.borrow());
+            impls::skip_first_partial(
+                skip_first_partial,
+                frame,
+                DynamicReturnFrame::spans_full_rotation,
+            )
         }
 
         fn pop_remaining(&mut self) -> Option<Self::Remain> {
@@ -454,5 +1795,898 @@ mod converter_impls {
                 Some(remaining)
             }
         }
+
+        fn finish(&mut self) -> Option<Self::Frame> {
+            let frame = match self.remaining_points.take() {
+                DynamicReturnPoints::Single(points) => {
+                    impls::remaining_to_frame(points).map(DynamicReturnFrame::Single)
+                }
+                DynamicReturnPoints::Dual(points) => {
+                    impls::remaining_to_frame(points).map(DynamicReturnFrame::Dual)
+                }
+            };
+            impls::skip_first_partial(
+                &mut self.skip_first_partial,
+                frame,
+                DynamicReturnFrame::spans_full_rotation,
+            )
+        }
+
+        fn set_skip_first_partial(&mut self, skip: bool) {
+            self.skip_first_partial = skip;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::velodyne::test_support::{PacketBuilder, PointBuilder};
+
+    fn new_frame(azimuth_degrees: impl Iterator<Item = f64>) -> PcdFrame<SingleReturnPoint> {
+        let height = 2;
+        let data: Vec<_> = azimuth_degrees
+            .enumerate()
+            .flat_map(|(col_idx, azimuth)| {
+                (0..height).map(move |row_idx| {
+                    PointBuilder::new()
+                        .laser_id(row_idx as u32)
+                        .timestamp(Time::new::<microsecond>(col_idx as f64))
+                        .azimuth(Angle::new::<degree>(azimuth))
+                        .distance(Length::new::<meter>(1.0))
+                        .entry(row_idx, col_idx)
+                        .build()
+                })
+            })
+            .collect();
+
+        PcdFrame {
+            height,
+            width: data.len() / height,
+            data,
+        }
+    }
+
+    /// Generates VLP-16 packets with constant distance, starting from the azimuth count
+    /// and advancing 0.4 degrees per block.
+    fn vlp_16_packets(start_azimuth_count: usize, num_packets: u32) -> Vec<DataPacket> {
+        use crate::velodyne::consts::BLOCKS_PER_PACKET;
+
+        (0..num_packets)
+            .map(|packet_idx| {
+                PacketBuilder::new()
+                    .timestamp(packet_idx * 1000)
+                    .channels(500, 0)
+                    .azimuth_counts(
+                        start_azimuth_count + packet_idx as usize * BLOCKS_PER_PACKET * 40,
+                        40,
+                    )
+                    .build()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn mask_range_test() {
+        let strongest_frame = new_frame((0..4).map(|col_idx| col_idx as f64));
+        let data: Vec<_> = strongest_frame
+            .data
+            .iter()
+            .map(|&strongest| {
+                let mut last = strongest;
+                last.data.distance = Length::new::<meter>(5.0);
+                last.data.position = [Length::new::<meter>(5.0); 3];
+                last.return_kind = ReturnKind::Last;
+                DualReturnPoint::try_from_pair(strongest, last).unwrap()
+            })
+            .collect();
+        let frame = PcdFrame {
+            height: strongest_frame.height,
+            width: strongest_frame.width,
+            data,
+        };
+
+        // the last returns are beyond the range while the strongest returns are kept
+        let masked = frame.mask_range(Length::new::<meter>(0.5)..Length::new::<meter>(2.0));
+        assert_eq!(masked.data.len(), frame.data.len());
+        masked.data.iter().for_each(|point| {
+            assert_eq!(
+                point.strongest_return_data.distance,
+                Length::new::<meter>(1.0)
+            );
+            assert_eq!(point.last_return_data.distance, Length::new::<meter>(0.0));
+            assert_eq!(
+                point.last_return_data.position,
+                [Length::new::<meter>(0.0); 3]
+            );
+        });
+    }
+
+    #[test]
+    fn intra_frame_duration_test() {
+        let frame = new_frame((0..360).map(|deg| deg as f64));
+        let duration = frame.intra_frame_duration();
+        assert!((duration.get::<microsecond>() - 359.0).abs() < 1e-9);
+
+        // the motion at 10 m/s within the frame
+        let velocity = 10.0;
+        let motion = velocity * duration.get::<second>();
+        assert!((motion - 3.59e-3).abs() < 1e-9);
+
+        let empty = PcdFrame::<SingleReturnPoint>::new();
+        assert_eq!(empty.intra_frame_duration(), Time::new::<second>(0.0));
+    }
+
+    #[test]
+    fn robust_timestamp_test() {
+        let mut frame = new_frame((0..5).map(|deg| deg as f64));
+        assert_eq!(frame.robust_timestamp(), Time::new::<microsecond>(2.0));
+
+        // a wild timestamp in the fourth column does not shift the median
+        frame.data[6].timestamp = Time::new::<second>(1e6);
+        frame.data[7].timestamp = Time::new::<second>(1e6);
+        assert_eq!(frame.robust_timestamp(), Time::new::<microsecond>(2.0));
+
+        // the middle two columns are averaged for even width
+        frame.data.truncate(8);
+        frame.width = 4;
+        assert_eq!(frame.robust_timestamp(), Time::new::<microsecond>(1.5));
+
+        let empty = PcdFrame::<SingleReturnPoint>::new();
+        assert_eq!(empty.robust_timestamp(), Time::new::<second>(0.0));
+    }
+
+    #[test]
+    fn to_interleaved_f32_test() {
+        let mut frame = new_frame((0..4).map(|deg| deg as f64));
+        frame.data[0].data = PointData {
+            distance: Length::new::<meter>(3.0),
+            intensity: 42,
+            intensity_value: 42.0,
+            position: [
+                Length::new::<meter>(1.0),
+                Length::new::<meter>(-2.0),
+                Length::new::<millimeter>(500.0),
+            ],
+            suspect: false,
+        };
+
+        let buffer = frame.to_interleaved_f32();
+        assert_eq!(buffer.len(), 4 * frame.data.len());
+        assert_eq!(buffer[0..4], [1.0, -2.0, 0.5, 42.0]);
+    }
+
+    #[test]
+    fn to_column_major_xyz_test() {
+        let mut frame = new_frame((0..4).map(|deg| deg as f64));
+        let (height, width) = (frame.height, frame.width);
+
+        // the point at row 1 of column 2
+        let index = 2 * height + 1;
+        frame.data[index].data.position = [
+            Length::new::<meter>(1.0),
+            Length::new::<meter>(-2.0),
+            Length::new::<millimeter>(500.0),
+        ];
+
+        let buffer = frame.to_column_major_xyz();
+        let stride = height * width;
+        assert_eq!(buffer.len(), 3 * stride);
+        assert_eq!(buffer[index], 1.0);
+        assert_eq!(buffer[stride + index], -2.0);
+        assert_eq!(buffer[2 * stride + index], 0.5);
+    }
+
+    #[test]
+    fn decimate_rings_test() {
+        let height = 128;
+        let width = 3;
+        let mut frame = new_frame((0..width).map(|deg| deg as f64));
+        frame.height = height;
+        frame.data = (0..width)
+            .flat_map(|col_idx| {
+                let mut point = frame.data[col_idx * 2];
+                (0..height).map(move |row_idx| {
+                    point.laser_id = row_idx as u32;
+                    point.set_row_idx(row_idx);
+                    point
+                })
+            })
+            .collect();
+
+        let decimated = frame.decimate_rings(2);
+        assert_eq!(decimated.height, 64);
+        assert_eq!(decimated.width, width);
+        assert_eq!(decimated.data.len(), 64 * width);
+        for col_idx in 0..width {
+            for row_idx in 0..64 {
+                let point = decimated.point_at(row_idx, col_idx).unwrap();
+                assert_eq!(point.laser_id, row_idx as u32 * 2);
+                assert_eq!(point.row_idx(), row_idx);
+                assert_eq!(point.col_idx(), col_idx);
+            }
+        }
+
+        // the trailing ring is truncated
+        assert_eq!(frame.decimate_rings(3).height, 43);
+    }
+
+    #[test]
+    fn to_xyz_intensity_vecs_test() {
+        let mut frame = new_frame((0..3).map(|deg| deg as f64));
+        frame.data.iter_mut().enumerate().for_each(|(idx, point)| {
+            point.data.intensity = idx as u8;
+            point.data.position = [
+                Length::new::<meter>(idx as f64),
+                Length::new::<millimeter>(500.0),
+                Length::new::<meter>(-1.0),
+            ];
+        });
+        // the first point has no return
+        frame.data[0].data.distance = Length::new::<meter>(0.0);
+
+        let (positions, intensities) = frame.to_xyz_intensity_vecs();
+        assert_eq!(positions.len(), intensities.len());
+        assert_eq!(positions.len(), frame.data.len() - 1);
+        assert_eq!(positions[0], [1.0, 0.5, -1.0]);
+        assert_eq!(intensities[0], 1.0);
+        assert_eq!(positions[4], [5.0, 0.5, -1.0]);
+        assert_eq!(intensities[4], 5.0);
+    }
+
+    #[test]
+    fn per_ring_counts_test() {
+        let mut frame = new_frame((0..10).map(|deg| deg as f64));
+        assert_eq!(frame.per_ring_counts(), vec![10, 10]);
+
+        // the second ring has no returns
+        frame
+            .data
+            .iter_mut()
+            .filter(|point| point.row_idx() == 1)
+            .for_each(|point| point.data.distance = Length::new::<meter>(0.0));
+        assert_eq!(frame.per_ring_counts(), vec![10, 0]);
+
+        assert!(PcdFrame::<SingleReturnPoint>::new()
+            .per_ring_counts()
+            .is_empty());
+    }
+
+    #[test]
+    fn ring_summary_test() {
+        // ring 0 spans from 350 to 30 degrees across the zero azimuth
+        let mut frame = new_frame((350..=390).step_by(10).map(|deg| (deg % 360) as f64));
+        frame
+            .data
+            .iter_mut()
+            .filter(|point| point.row_idx() == 0)
+            .enumerate()
+            .for_each(|(idx, point)| {
+                point.data.distance = Length::new::<meter>(2.0 + idx as f64);
+                point.data.intensity = 10 * idx as u8;
+                point.data.position = [
+                    Length::new::<meter>(idx as f64),
+                    Length::new::<meter>(1.0),
+                    Length::new::<meter>(0.0),
+                ];
+            });
+        // a point without return is ignored
+        frame.data[8].data.distance = Length::new::<meter>(0.0);
+
+        let summary = frame.ring_summary(0);
+        assert_eq!(summary.count, 4);
+        // the ranges are 2, 3, 4 and 5 meters
+        assert!((summary.mean_range.get::<meter>() - 3.5).abs() < 1e-9);
+        assert!((summary.mean_intensity - 15.0).abs() < 1e-9);
+        assert!((summary.centroid[0].get::<meter>() - 1.5).abs() < 1e-9);
+        assert!((summary.centroid[1].get::<meter>() - 1.0).abs() < 1e-9);
+        assert!((summary.azimuth_span.get::<degree>() - 30.0).abs() < 1e-9);
+
+        let summary = frame.ring_summary(2);
+        assert_eq!(summary.count, 0);
+        assert_eq!(summary.mean_range, Length::new::<meter>(0.0));
+    }
+
+    #[test]
+    fn metadata_json_test() -> Result<()> {
+        let mut frame = new_frame((0..=90).map(|deg| deg as f64));
+        frame.data[0].data.distance = Length::new::<meter>(0.0);
+        let json = frame.metadata_json(7, DynamicModel::Vlp16, ReturnMode::StrongestReturn);
+
+        let value: serde_json::Value = serde_json::from_str(&json)?;
+        let object = value.as_object().unwrap();
+        [
+            "frame_id",
+            "timestamp",
+            "point_count",
+            "width",
+            "height",
+            "model",
+            "return_mode",
+            "azimuth_coverage",
+        ]
+        .iter()
+        .for_each(|key| assert!(object.contains_key(*key), "missing key {}", key));
+
+        let metadata: FrameMetadata = serde_json::from_str(&json)?;
+        assert_eq!(metadata.frame_id, 7);
+        assert_eq!(metadata.point_count, 181);
+        assert_eq!((metadata.width, metadata.height), (91, 2));
+        assert_eq!(metadata.model, "Vlp16");
+        assert_eq!(metadata.return_mode, "StrongestReturn");
+        assert!((metadata.azimuth_coverage - 90.0).abs() < 1e-9);
+
+        Ok(())
+    }
+
+    #[test]
+    fn ground_footprint_test() {
+        // a 5x5 grid of points on a square with 2 meters side length
+        let mut frame = new_frame((0..13).map(|deg| deg as f64));
+        frame.data.truncate(25);
+        frame.data.iter_mut().enumerate().for_each(|(idx, point)| {
+            let x = (idx % 5) as f64 * 0.5 - 1.0;
+            let y = (idx / 5) as f64 * 0.5 - 1.0;
+            point.data.position = [
+                Length::new::<meter>(x),
+                Length::new::<meter>(y),
+                Length::new::<meter>(idx as f64),
+            ];
+        });
+        // the invalid point outside the square is ignored
+        frame.data[12].data.distance = Length::new::<meter>(0.0);
+        frame.data[12].data.position[0] = Length::new::<meter>(10.0);
+
+        assert_eq!(
+            frame.ground_footprint(),
+            vec![[-1.0, -1.0], [1.0, -1.0], [1.0, 1.0], [-1.0, 1.0]]
+        );
+        assert!(PcdFrame::<SingleReturnPoint>::new()
+            .ground_footprint()
+            .is_empty());
+    }
+
+    #[test]
+    fn filter_intensity_test() {
+        let mut frame = new_frame((0..5).map(|deg| deg as f64));
+        frame.data.iter_mut().enumerate().for_each(|(idx, point)| {
+            point.data.intensity = if idx % 3 == 0 { 5 } else { 100 };
+            point.data.position = [Length::new::<meter>(1.0); 3];
+        });
+
+        let filtered = frame.filter_intensity(10);
+        assert_eq!(filtered.height, 1);
+        assert_eq!(filtered.width, 6);
+        assert_eq!(filtered.data.len(), 6);
+        assert!(filtered
+            .data
+            .iter()
+            .all(|point| point.data.intensity == 100));
+
+        let masked = frame.mask_intensity(10);
+        assert_eq!((masked.height, masked.width), (frame.height, frame.width));
+        izip!(masked.data.iter(), frame.data.iter()).for_each(|(masked, point)| {
+            if point.data.intensity < 10 {
+                assert_eq!(masked.data.distance, Length::new::<meter>(0.0));
+                assert_eq!(masked.data.position, [Length::new::<meter>(0.0); 3]);
+            } else {
+                assert_eq!(masked.data.distance, point.data.distance);
+                assert_eq!(masked.data.position, point.data.position);
+            }
+        });
+        assert_eq!(masked.per_ring_counts().iter().sum::<usize>(), 6);
+    }
+
+    #[test]
+    fn dedup_spatial_test() {
+        let mut frame = new_frame((0..4).map(|deg| deg as f64));
+        let height = frame.height;
+        let position = |x: f64| [x, 1.0, 0.0].map(Length::new::<meter>);
+        frame.data.iter_mut().for_each(|point| {
+            let col_idx = point.lidar_frame_entry.col_idx;
+            point.data.position = position(col_idx as f64);
+        });
+
+        // the points 2mm apart in ring 0 collapse into one, while ring 1 is intact
+        frame.data[height].data.position = position(0.002);
+        let deduped = frame.dedup_spatial(Length::new::<millimeter>(5.0));
+        assert_eq!((deduped.height, deduped.width), (1, 7));
+        let ring_0: Vec<_> = deduped
+            .data
+            .iter()
+            .filter(|point| point.lidar_frame_entry.row_idx == 0)
+            .map(|point| point.lidar_frame_entry.col_idx)
+            .collect();
+        assert_eq!(ring_0, [0, 2, 3]);
+
+        // a tight tolerance keeps both
+        let deduped = frame.dedup_spatial(Length::new::<millimeter>(1.0));
+        assert_eq!(deduped.width, 8);
+    }
+
+    #[test]
+    fn nearest_only_test() -> Result<()> {
+        let frame = new_frame((0..4).map(|deg| deg as f64));
+        let data = frame
+            .data
+            .iter()
+            .enumerate()
+            .map(|(idx, point)| {
+                let mut strongest = *point;
+                let mut last = *point;
+                strongest.data.distance = Length::new::<meter>(2.0);
+                strongest.data.intensity = 1;
+                last.data.distance = match idx % 4 {
+                    0 => Length::new::<meter>(1.0),
+                    1 => Length::new::<meter>(3.0),
+                    2 => Length::new::<meter>(0.0),
+                    _ => {
+                        strongest.data.distance = Length::new::<meter>(0.0);
+                        Length::new::<meter>(5.0)
+                    }
+                };
+                last.data.intensity = 2;
+                DualReturnPoint::try_from_pair(strongest, last)
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let dual_frame = PcdFrame {
+            height: frame.height,
+            width: frame.width,
+            data,
+        };
+
+        let nearest = dual_frame.nearest_only();
+        assert_eq!((nearest.height, nearest.width), (frame.height, frame.width));
+        nearest.data.iter().enumerate().for_each(|(idx, point)| {
+            let (distance, intensity, return_kind) = match idx % 4 {
+                0 => (1.0, 2, ReturnKind::Last),
+                1 | 2 => (2.0, 1, ReturnKind::Strongest),
+                _ => (5.0, 2, ReturnKind::Last),
+            };
+            assert_eq!(point.data.distance, Length::new::<meter>(distance));
+            assert_eq!(point.data.intensity, intensity);
+            assert_eq!(point.return_kind, return_kind);
+            assert_eq!(point.row_idx(), frame.data[idx].row_idx());
+            assert_eq!(point.col_idx(), frame.data[idx].col_idx());
+        });
+
+        Ok(())
+    }
+
+    #[test]
+    fn to_voxel_coo_test() {
+        let mut frame = new_frame((0..2).map(|deg| deg as f64));
+        let positions = [
+            [0.05, 0.05, 0.05],
+            [0.09, 0.01, 0.02],
+            [-0.05, 0.15, 0.05],
+            [0.0, 0.0, 0.0],
+        ];
+        izip!(
+            frame.data.iter_mut(),
+            positions.iter(),
+            [10u8, 20, 30, 40].iter()
+        )
+        .for_each(|(point, position, &intensity)| {
+            point.data.position = position.map(Length::new::<meter>);
+            point.data.intensity = intensity;
+        });
+        // the point without return is ignored
+        frame.data[3].data.distance = Length::new::<meter>(0.0);
+
+        let entries = frame.to_voxel_coo(Length::new::<meter>(0.1));
+        assert_eq!(entries, vec![(-1, 1, 0, 30.0), (0, 0, 0, 15.0)]);
+    }
+
+    #[test]
+    fn voxel_downsample_test() {
+        let mut frame = new_frame((0..2).map(|deg| deg as f64));
+        let positions = [
+            [0.01, 0.05, 0.05],
+            [0.09, 0.05, 0.05],
+            [-0.05, 0.15, 0.05],
+            [0.0, 0.0, 0.0],
+        ];
+        izip!(
+            frame.data.iter_mut(),
+            positions.iter(),
+            [10u8, 30, 0, 40].iter()
+        )
+        .for_each(|(point, position, &intensity)| {
+            point.data.position = position.map(Length::new::<meter>);
+            point.data.intensity = intensity;
+            point.data.intensity_value = intensity as f64;
+        });
+        frame.data[3].data.distance = Length::new::<meter>(0.0);
+        let voxel_size = Length::new::<meter>(0.1);
+        let x = |point: &SingleReturnPoint| point.data.position[0].get::<meter>();
+
+        // the point without return is ignored
+        let geometric = frame.voxel_downsample(voxel_size, CentroidMode::Geometric);
+        assert_eq!((geometric.height, geometric.width), (1, 2));
+        assert!((x(&geometric.data[1]) - 0.05).abs() < 1e-9);
+        assert_eq!(geometric.data[1].data.intensity, 20);
+        assert_eq!(geometric.data[1].data.intensity_value, 20.0);
+
+        // the brighter point pulls the centroid
+        let weighted = frame.voxel_downsample(voxel_size, CentroidMode::IntensityWeighted);
+        assert!((x(&weighted.data[1]) - 0.07).abs() < 1e-9);
+        let [_, y, z] = weighted.data[1]
+            .data
+            .position
+            .map(|value| value.get::<meter>());
+        assert!((y - 0.05).abs() < 1e-9 && (z - 0.05).abs() < 1e-9);
+        let distance = weighted.data[1].data.distance.get::<meter>();
+        assert!((distance - (0.07f64.powi(2) + 0.005).sqrt()).abs() < 1e-9);
+
+        // the voxel of zero intensity falls back to the geometric centroid
+        assert!((x(&weighted.data[0]) + 0.05).abs() < 1e-9);
+    }
+
+    #[test]
+    fn observed_azimuth_resolution_test() {
+        // uniform 0.2 degree step across the zero azimuth
+        let frame = new_frame((0..100).map(|idx| (350.0 + idx as f64 * 0.2) % 360.0));
+        let resolution = frame.observed_azimuth_resolution();
+        assert!((resolution.get::<degree>() - 0.2).abs() < 1e-9);
+
+        let single_column = new_frame(iter::once(0.0));
+        assert_eq!(
+            single_column.observed_azimuth_resolution(),
+            Angle::new::<radian>(0.0)
+        );
+        let empty = PcdFrame::<SingleReturnPoint>::new();
+        assert_eq!(
+            empty.observed_azimuth_resolution(),
+            Angle::new::<radian>(0.0)
+        );
+    }
+
+    #[test]
+    fn rotate_azimuth_test() {
+        let mut frame = new_frame(iter::once(90.0));
+        frame.data.iter_mut().for_each(|point| {
+            point.data.position = [
+                Length::new::<meter>(1.0),
+                Length::new::<meter>(0.0),
+                Length::new::<meter>(0.5),
+            ];
+        });
+        frame.rotate_azimuth(Angle::new::<degree>(90.0));
+
+        // the point on +X moves to +Y, whose azimuth is zero
+        frame.data.iter().for_each(|point| {
+            let [x, y, z] = point.data.position;
+            assert!(x.get::<meter>().abs() < 1e-9);
+            assert!((y.get::<meter>() - 1.0).abs() < 1e-9);
+            assert!((z.get::<meter>() - 0.5).abs() < 1e-9);
+            assert!(point.original_azimuth_angle.get::<degree>().abs() < 1e-9);
+            assert!(point.corrected_azimuth_angle.get::<degree>().abs() < 1e-9);
+        });
+
+        // wrap around
+        frame.rotate_azimuth(Angle::new::<degree>(90.0));
+        frame.data.iter().for_each(|point| {
+            let [x, _, _] = point.data.position;
+            assert!((x.get::<meter>() + 1.0).abs() < 1e-9);
+            assert!((point.original_azimuth_angle.get::<degree>() - 270.0).abs() < 1e-9);
+        });
+    }
+
+    #[test]
+    fn pcd_frame_merge_test() -> Result<()> {
+        let first_half = new_frame((0..180).map(|deg| deg as f64));
+        let second_half = new_frame((180..360).map(|deg| deg as f64));
+        let merged = first_half.clone().try_merge(second_half.clone())?;
+        assert_eq!(merged.height, 2);
+        assert_eq!(merged.width, 360);
+        assert_eq!(merged.data.len(), 720);
+        assert!(merged
+            .data
+            .chunks(merged.height)
+            .enumerate()
+            .all(|(col_idx, column)| column.iter().all(|point| point.col_idx() == col_idx)));
+
+        // reversed order
+        assert!(second_half.try_merge(first_half.clone()).is_err());
+
+        // non-contiguous
+        let far_half = new_frame((200..360).map(|deg| deg as f64));
+        assert!(first_half.try_merge(far_half).is_err());
+
+        // single-column frames are checked against the widest column spacing
+        let merged = new_frame(iter::once(10.0)).try_merge(new_frame(iter::once(10.5)))?;
+        assert_eq!(merged.width, 2);
+        assert!(new_frame(iter::once(10.0))
+            .try_merge(new_frame(iter::once(90.0)))
+            .is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn skip_first_partial_test() -> Result<()> {
+        // the stream starts at 180 degrees and runs for one and a half rotations
+        let packets = vlp_16_packets(18000, 120);
+
+        let convert = |skip: bool| {
+            let mut converter =
+                Vlp16_Strongest_FrameConverter::from_config(Config::vlp_16_strongest_return());
+            converter.set_skip_first_partial(skip);
+            let mut frames = vec![];
+            for packet in packets.iter() {
+                frames.extend(converter.convert(packet));
+            }
+            frames.extend(converter.finish());
+            frames
+        };
+
+        let frames = convert(false);
+        assert_eq!(frames.len(), 3);
+        assert!(!frames[0].spans_full_rotation());
+        assert!(frames[1].spans_full_rotation());
+
+        // the leading partial frame is dropped, while the trailing one is kept
+        let skipped = convert(true);
+        assert_eq!(skipped.len(), 2);
+        izip!(skipped.iter(), frames[1..].iter()).for_each(|(lhs, rhs)| {
+            assert_eq!(lhs.width, rhs.width);
+            assert_eq!(lhs.data[0].timestamp, rhs.data[0].timestamp);
+        });
+
+        Ok(())
+    }
+
+    #[test]
+    fn azimuth_overlap_test() {
+        let lhs = new_frame((0..=180).map(|deg| deg as f64));
+        let rhs = new_frame((90..=270).map(|deg| deg as f64));
+        // [0, 180] and [90, 270] intersect on 90 degrees out of 270 degrees
+        assert!((lhs.azimuth_overlap(&rhs) - 1.0 / 3.0).abs() < 1e-9);
+        assert!((rhs.azimuth_overlap(&lhs) - 1.0 / 3.0).abs() < 1e-9);
+        assert!((lhs.azimuth_overlap(&lhs) - 1.0).abs() < 1e-9);
+
+        // arcs crossing the zero azimuth
+        let wrapped = new_frame((300..=390).map(|deg| (deg % 360) as f64));
+        assert!((lhs.azimuth_overlap(&wrapped) - 30.0 / 240.0).abs() < 1e-9);
+
+        let disjoint = new_frame((200..=250).map(|deg| deg as f64));
+        assert_eq!(lhs.azimuth_overlap(&disjoint), 0.0);
+        let empty = PcdFrame::<SingleReturnPoint>::new();
+        assert_eq!(lhs.azimuth_overlap(&empty), 0.0);
+    }
+
+    #[test]
+    fn azimuth_gaps_test() {
+        let min_gap = Angle::new::<degree>(5.0);
+        let as_degrees = |gaps: Vec<(Angle, Angle)>| -> Vec<(f64, f64)> {
+            gaps.into_iter()
+                .map(|(start, end)| {
+                    let round = |angle: Angle| (angle.get::<degree>() * 1e6).round() / 1e6;
+                    (round(start), round(end))
+                })
+                .collect()
+        };
+
+        // a full rotation crossing the zero azimuth, missing (99, 120) degrees
+        let frame = new_frame(
+            (300..660)
+                .map(|deg| (deg % 360) as f64)
+                .filter(|deg| !(100.0..120.0).contains(deg)),
+        );
+        assert_eq!(as_degrees(frame.azimuth_gaps(min_gap)), vec![(99.0, 120.0)]);
+        assert!(frame.azimuth_gaps(Angle::new::<degree>(30.0)).is_empty());
+
+        // the gap wraps around the zero azimuth
+        let frame = new_frame((20..=340).map(|deg| deg as f64));
+        assert_eq!(as_degrees(frame.azimuth_gaps(min_gap)), vec![(340.0, 20.0)]);
+
+        assert!(new_frame(iter::once(0.0)).azimuth_gaps(min_gap).is_empty());
+        assert!(PcdFrame::<SingleReturnPoint>::new()
+            .azimuth_gaps(min_gap)
+            .is_empty());
+    }
+
+    #[test]
+    fn ring_as_scan_test() {
+        // a full rotation starting from the zero azimuth
+        let mut converter =
+            Vlp16_Strongest_FrameConverter::from_config(Config::vlp_16_strongest_return());
+        let frame = vlp_16_packets(0, 80)
+            .into_iter()
+            .find_map(|packet| converter.convert(packet))
+            .unwrap();
+        assert_eq!(frame.height, 16);
+
+        let scan = frame.ring_as_scan(0);
+        assert_eq!(scan.len(), frame.width);
+        assert!(scan.windows(2).all(|pair| pair[0].0 <= pair[1].0));
+        assert!(scan
+            .iter()
+            .all(|&(_, range)| (range.get::<meter>() - 1.0).abs() < 1e-9));
+        let expect: Vec<_> = frame
+            .data
+            .iter()
+            .filter(|point| point.row_idx() == 0)
+            .map(|point| point.corrected_azimuth_angle)
+            .collect();
+        assert!(izip!(scan.iter(), expect.iter()).all(|(&(azimuth, _), &expect)| azimuth == expect));
+
+        assert!(frame.ring_as_scan(16).is_empty());
+    }
+
+    #[test]
+    fn min_range_per_sector_test() {
+        // columns at 10, 100, 110 and 350 degrees
+        let mut frame = new_frame([10.0, 100.0, 110.0, 350.0].iter().cloned());
+        let distances = [[3.0, 2.5], [4.0, 0.0], [1.5, 6.0], [7.0, 8.0]];
+        izip!(frame.data.chunks_mut(2), distances.iter()).for_each(|(column, distances)| {
+            izip!(column.iter_mut(), distances.iter()).for_each(|(point, &distance)| {
+                point.data.distance = Length::new::<meter>(distance);
+            });
+        });
+
+        let ranges: Vec<f64> = frame
+            .min_range_per_sector(4)
+            .iter()
+            .map(|range| range.get::<meter>())
+            .collect();
+        // the point without return is ignored, and the third sector is empty
+        assert_eq!(ranges, vec![2.5, 1.5, f64::INFINITY, 7.0]);
+
+        let ranges = frame.min_range_per_sector(1);
+        assert_eq!(ranges, vec![Length::new::<meter>(1.5)]);
+    }
+
+    #[test]
+    fn ring_curvature_test() {
+        // a wall at 2 meters with a step to 5 meters at column 6 on ring 1
+        let mut frame = new_frame((0..12).map(|deg| deg as f64));
+        frame.data.iter_mut().for_each(|point| {
+            let col_idx = point.lidar_frame_entry.col_idx;
+            point.data.distance = Length::new::<meter>(if col_idx < 6 { 2.0 } else { 5.0 });
+        });
+        frame.data[2 * 9 + 1].data.distance = Length::new::<meter>(0.0);
+
+        let curvature = frame.ring_curvature(1, 2);
+        assert_eq!(curvature.len(), frame.width);
+
+        // the borders and the neighbors of the point without return are undefined
+        assert!(curvature[..2].iter().all(|value| value.is_nan()));
+        assert!(curvature[7..].iter().all(|value| value.is_nan()));
+
+        // the planar points have zero curvature, while the edge point is high
+        assert_eq!(curvature[2], 0.0);
+        assert_eq!(curvature[3], 0.0);
+        assert!((curvature[5] - 0.75).abs() < 1e-9);
+        assert!(curvature[5] > curvature[4]);
+        assert!((curvature[4] - 0.375).abs() < 1e-9);
+        assert!((curvature[6] - 0.3).abs() < 1e-9);
+
+        assert!(frame.ring_curvature(2, 2).is_empty());
+        assert!(frame.ring_curvature(1, 0).is_empty());
+    }
+
+    #[test]
+    fn interpolate_gaps_test() {
+        let mut frame = new_frame((0..12).map(|deg| deg as f64 * 10.0));
+        frame.data.iter_mut().for_each(|point| {
+            let col_idx = point.col_idx();
+            let distance = 1.0 + col_idx as f64 * 0.1;
+            let azimuth = point.original_azimuth_angle.get::<radian>();
+            point.data.distance = Length::new::<meter>(distance);
+            point.data.intensity = col_idx as u8 * 10;
+            point.data.intensity_value = col_idx as f64 * 10.0;
+            point.data.position = [
+                Length::new::<meter>(distance * azimuth.cos()),
+                Length::new::<meter>(-distance * azimuth.sin()),
+                Length::new::<meter>(0.0),
+            ];
+        });
+        let expect = frame.clone();
+
+        // a single-column gap at column 3 and a 3-column gap at columns 6 to 8
+        let invalid = PointData {
+            distance: Length::new::<meter>(0.0),
+            intensity: 0,
+            intensity_value: 0.0,
+            position: [Length::new::<meter>(0.0); 3],
+            suspect: false,
+        };
+        frame
+            .data
+            .iter_mut()
+            .filter(|point| [3, 6, 7, 8].contains(&point.col_idx()))
+            .for_each(|point| point.data = invalid);
+        frame.interpolate_gaps(2);
+
+        frame
+            .data
+            .chunks(frame.height)
+            .zip(expect.data.chunks(expect.height))
+            .enumerate()
+            .for_each(|(col_idx, (column, expect_column))| {
+                izip!(column, expect_column).for_each(|(point, expect)| {
+                    if [6, 7, 8].contains(&col_idx) {
+                        assert_eq!(point.data.distance, Length::new::<meter>(0.0));
+                        return;
+                    }
+                    assert!(
+                        (point.data.distance - expect.data.distance)
+                            .get::<meter>()
+                            .abs()
+                            < 1e-9
+                    );
+                    assert_eq!(point.data.intensity, expect.data.intensity);
+                    izip!(point.data.position.iter(), expect.data.position.iter()).for_each(
+                        |(value, expect)| assert!((*value - *expect).get::<meter>().abs() < 1e-3),
+                    );
+                });
+            });
+    }
+
+    #[test]
+    fn bilateral_smooth_test() {
+        // a noisy wall at 5 meters on the left of column 5 and at 10 meters on the right
+        let height = 8;
+        let mut state: u64 = 7;
+        let mut noise = move || {
+            state = state
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            ((state >> 11) as f64 / (1u64 << 53) as f64 - 0.5) * 0.04
+        };
+        let mut frame = new_frame((0..40).map(|deg| deg as f64));
+        frame.height = height;
+        frame.width = 40 * 2 / height;
+        frame.data.iter_mut().enumerate().for_each(|(idx, point)| {
+            let col_idx = idx / height;
+            let azimuth = (col_idx as f64).to_radians();
+            let distance = if col_idx < 5 { 5.0 } else { 10.0 } + noise();
+            point.data.distance = Length::new::<meter>(distance);
+            point.data.position = [
+                Length::new::<meter>(distance * azimuth.sin()),
+                Length::new::<meter>(distance * azimuth.cos()),
+                Length::new::<meter>(0.0),
+            ];
+        });
+        // a point without return is left invalid
+        frame.data[20].data.distance = Length::new::<meter>(0.0);
+
+        let smoothed = frame.bilateral_smooth(1.5, Length::new::<meter>(0.05));
+        assert_eq!(smoothed.data.len(), frame.data.len());
+        assert_eq!(smoothed.data[20].data.distance, Length::new::<meter>(0.0));
+
+        let variance = |frame: &PcdFrame<SingleReturnPoint>, range: Range<usize>| {
+            let distances: Vec<f64> = frame.data[range]
+                .iter()
+                .map(|point| point.data.distance.get::<meter>())
+                .filter(|&distance| distance > 0.0)
+                .collect();
+            let mean = distances.iter().sum::<f64>() / distances.len() as f64;
+            distances
+                .iter()
+                .map(|distance| (distance - mean).powi(2))
+                .sum::<f64>()
+                / distances.len() as f64
+        };
+        let left = 0..5 * height;
+        let right = 5 * height..10 * height;
+        assert!(variance(&smoothed, left.clone()) < variance(&frame, left) / 2.0);
+        assert!(variance(&smoothed, right.clone()) < variance(&frame, right) / 2.0);
+
+        // the edge stays sharp, and the positions follow the distances
+        smoothed.data.iter().enumerate().for_each(|(idx, point)| {
+            let distance = point.data.distance.get::<meter>();
+            if distance == 0.0 {
+                return;
+            }
+            let expect = if idx / height < 5 { 5.0 } else { 10.0 };
+            assert!((distance - expect).abs() < 0.03);
+
+            let [x, y, _] = point.data.position;
+            let norm = x.get::<meter>().hypot(y.get::<meter>());
+            assert!((norm - distance).abs() < 1e-9);
+        });
     }
 }
@@ -0,0 +1,79 @@
+use super::converter::PcdFrame;
+use crate::{
+    common::*,
+    velodyne::point::{PointData, PrimaryData},
+};
+use ndarray::Array2;
+
+impl<P> PcdFrame<P>
+where
+    P: Copy + PrimaryData,
+{
+    /// Counts the valid points over a grid of azimuth and elevation angles,
+    /// which exposes blind spots and oversampled regions.
+    ///
+    /// The grid is indexed by `[azimuth_bin, elevation_bin]`. The azimuth angle, measured
+    /// clockwise from the front of sensor, spans [0°, 360°) with `az_bins` bins, and the
+    /// elevation angle spans [-90°, 90°] with `el_bins` bins. The angles are computed from
+    /// the positions of points. It panics if any of the bin counts is zero.
+    pub fn angular_density(&self, az_bins: usize, el_bins: usize) -> Array2<u32> {
+        assert!(
+            az_bins > 0 && el_bins > 0,
+            "the number of bins must be nonzero"
+        );
+        let mut density = Array2::zeros((az_bins, el_bins));
+
+        self.data
+            .iter()
+            .map(|point| point.primary_data())
+            .filter(|data| data.distance > Length::new::<meter>(0.0))
+            .for_each(|data| {
+                let PointData {
+                    position: [x, y, z],
+                    ..
+                } = *data;
+                let (x, y, z) = (x.get::<meter>(), y.get::<meter>(), z.get::<meter>());
+                let azimuth = x.atan2(y).to_degrees().rem_euclid(360.0);
+                let elevation = z.atan2(x.hypot(y)).to_degrees();
+
+                let az_idx = ((azimuth / 360.0 * az_bins as f64) as usize).min(az_bins - 1);
+                let el_idx =
+                    (((elevation + 90.0) / 180.0 * el_bins as f64) as usize).min(el_bins - 1);
+                density[[az_idx, el_idx]] += 1;
+            });
+
+        density
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::common::*;
+    use crate::velodyne::{test_support::PointBuilder, PcdFrame};
+
+    #[test]
+    fn angular_density_test() {
+        let new_point = |x: f64, y: f64, z: f64| PointBuilder::new().position([x, y, z]).build();
+
+        // 10 points slightly right of the front and above the horizon, and one point behind
+        let mut data: Vec<_> = (0..10)
+            .map(|idx| new_point(0.1 + idx as f64 * 0.01, 5.0, 0.5))
+            .collect();
+        data.push(new_point(0.0, -5.0, 0.0));
+        // the point without return is ignored
+        let mut invalid = new_point(0.1, 5.0, 0.5);
+        invalid.data.distance = Length::new::<meter>(0.0);
+        data.push(invalid);
+        let frame = PcdFrame {
+            height: 1,
+            width: data.len(),
+            data,
+        };
+
+        let density = frame.angular_density(36, 18);
+        assert_eq!(density.dim(), (36, 18));
+        assert_eq!(density[[0, 9]], 10);
+        assert_eq!(density[[18, 9]], 1);
+        assert_eq!(density.sum(), 11);
+    }
+}
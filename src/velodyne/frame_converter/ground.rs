@@ -0,0 +1,225 @@
+use super::converter::PcdFrame;
+use crate::{
+    common::*,
+    velodyne::point::{PointData, PrimaryData},
+};
+
+const RANSAC_ITERATIONS: usize = 200;
+const INLIER_DISTANCE: f64 = 0.05;
+
+/// A 3D vector of unit length.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct UnitVector3([f64; 3]);
+
+impl UnitVector3 {
+    /// Normalizes the vector. It returns `None` if the vector is zero or not finite.
+    pub fn new_normalize(vector: [f64; 3]) -> Option<Self> {
+        let [x, y, z] = vector;
+        let norm = (x * x + y * y + z * z).sqrt();
+        (norm.is_normal()).then(|| Self([x / norm, y / norm, z / norm]))
+    }
+
+    pub fn into_inner(self) -> [f64; 3] {
+        self.0
+    }
+
+    /// Returns the rotation about the X axis that tilts the Z axis to this vector.
+    pub fn roll(&self) -> Angle {
+        let [_, y, z] = self.0;
+        Angle::new::<radian>((-y).atan2(z))
+    }
+
+    /// Returns the rotation about the Y axis that tilts the Z axis to this vector.
+    pub fn pitch(&self) -> Angle {
+        let [x, y, z] = self.0;
+        Angle::new::<radian>(x.atan2((y * y + z * z).sqrt()))
+    }
+}
+
+impl<P> PcdFrame<P>
+where
+    P: Copy + PrimaryData,
+{
+    /// Fits the dominant ground plane, for example, to estimate the mounting tilt of the sensor.
+    ///
+    /// It runs a RANSAC plane fit over the returns below the horizon, and refines the plane
+    /// by least squares over the inliers within 5 centimeters. It returns the upward plane
+    /// normal `n` and the offset `d` such that the points `p` on the plane satisfy `n·p = d`.
+    /// The roll and pitch of the sensor can be read from [UnitVector3::roll] and
+    /// [UnitVector3::pitch] of the normal.
+    ///
+    /// The sampling is deterministic. It returns `None` if there are too few points to fit a plane.
+    pub fn estimate_ground_plane(&self) -> Option<(UnitVector3, Length)> {
+        let points: Vec<[f64; 3]> = self
+            .data
+            .iter()
+            .filter_map(|point| {
+                let PointData {
+                    distance,
+                    position: [x, y, z],
+                    ..
+                } = *point.primary_data();
+                let is_valid = distance > Length::new::<meter>(0.0);
+                let is_below_horizon = z < Length::new::<meter>(0.0);
+                (is_valid && is_below_horizon)
+                    .then(|| [x.get::<meter>(), y.get::<meter>(), z.get::<meter>()])
+            })
+            .collect();
+        if points.len() < 3 {
+            return None;
+        }
+
+        // xorshift generator for reproducible sampling
+        let mut state = 0x2545_f491_4f6c_dd1d_u64;
+        let mut sample = || {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            (state % points.len() as u64) as usize
+        };
+
+        let (normal, offset) = (0..RANSAC_ITERATIONS)
+            .filter_map(|_| {
+                let plane =
+                    plane_from_points(points[sample()], points[sample()], points[sample()])?;
+                let num_inliers = count_inliers(&points, plane);
+                Some((plane, num_inliers))
+            })
+            .max_by_key(|(_, num_inliers)| *num_inliers)
+            .map(|(plane, _)| plane)?;
+
+        let inliers: Vec<_> = points
+            .iter()
+            .filter(|point| (dot(normal, **point) - offset).abs() <= INLIER_DISTANCE)
+            .copied()
+            .collect();
+        let (normal, offset) = refine_plane(&inliers).unwrap_or((normal, offset));
+
+        Some((UnitVector3(normal), Length::new::<meter>(offset)))
+    }
+}
+
+fn dot(lhs: [f64; 3], rhs: [f64; 3]) -> f64 {
+    lhs[0] * rhs[0] + lhs[1] * rhs[1] + lhs[2] * rhs[2]
+}
+
+/// Returns the upward plane through three points, or `None` if they are collinear.
+fn plane_from_points(p0: [f64; 3], p1: [f64; 3], p2: [f64; 3]) -> Option<([f64; 3], f64)> {
+    let u = [p1[0] - p0[0], p1[1] - p0[1], p1[2] - p0[2]];
+    let v = [p2[0] - p0[0], p2[1] - p0[1], p2[2] - p0[2]];
+    let cross = [
+        u[1] * v[2] - u[2] * v[1],
+        u[2] * v[0] - u[0] * v[2],
+        u[0] * v[1] - u[1] * v[0],
+    ];
+    let [x, y, z] = UnitVector3::new_normalize(cross)?.into_inner();
+    let normal = if z < 0.0 { [-x, -y, -z] } else { [x, y, z] };
+    Some((normal, dot(normal, p0)))
+}
+
+fn count_inliers(points: &[[f64; 3]], (normal, offset): ([f64; 3], f64)) -> usize {
+    points
+        .iter()
+        .filter(|point| (dot(normal, **point) - offset).abs() <= INLIER_DISTANCE)
+        .count()
+}
+
+/// Fits `z = a x + b y + c` by least squares.
+fn refine_plane(points: &[[f64; 3]]) -> Option<([f64; 3], f64)> {
+    if points.len() < 3 {
+        return None;
+    }
+
+    // normal equations of the least squares problem
+    let mut ata = [[0.0; 3]; 3];
+    let mut atb = [0.0; 3];
+    for &[x, y, z] in points {
+        let row = [x, y, 1.0];
+        for i in 0..3 {
+            for j in 0..3 {
+                ata[i][j] += row[i] * row[j];
+            }
+            atb[i] += row[i] * z;
+        }
+    }
+
+    let det = |m: [[f64; 3]; 3]| {
+        m[0][0] * (m[1][1] * m[2][2] - m[1][2] * m[2][1])
+            - m[0][1] * (m[1][0] * m[2][2] - m[1][2] * m[2][0])
+            + m[0][2] * (m[1][0] * m[2][1] - m[1][1] * m[2][0])
+    };
+    let denominator = det(ata);
+    if denominator.abs() < f64::EPSILON {
+        return None;
+    }
+
+    // Cramer's rule
+    let mut solution = [0.0; 3];
+    for (col, value) in solution.iter_mut().enumerate() {
+        let mut m = ata;
+        for row in 0..3 {
+            m[row][col] = atb[row];
+        }
+        *value = det(m) / denominator;
+    }
+    let [a, b, c] = solution;
+
+    // a x + b y - z = -c
+    let norm = (a * a + b * b + 1.0).sqrt();
+    let normal = UnitVector3::new_normalize([-a, -b, 1.0])?.into_inner();
+    Some((normal, c / norm))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::velodyne::{point::SingleReturnPoint, test_support::PointBuilder};
+
+    #[test]
+    fn estimate_ground_plane_test() {
+        // the ground is 1.5 meters below the sensor, tilted 5 degrees in pitch and 3 degrees in roll
+        let normal =
+            UnitVector3::new_normalize([5f64.to_radians().sin(), -3f64.to_radians().sin(), 1.0])
+                .unwrap();
+        let [nx, ny, nz] = normal.into_inner();
+        let offset = -1.5;
+
+        let ground = (0..40).flat_map(|i| {
+            (0..40).map(move |j| {
+                let x = i as f64 * 0.5 - 10.0;
+                let y = j as f64 * 0.5 - 10.0;
+                let z = (offset - nx * x - ny * y) / nz;
+                [x, y, z]
+            })
+        });
+        // outliers from objects above the ground
+        let clutter = (0..200).map(|i| [i as f64 * 0.05, 2.0, -0.5 + (i % 7) as f64 * 0.05]);
+
+        let data: Vec<_> = ground
+            .chain(clutter)
+            .enumerate()
+            .map(|(idx, [x, y, z])| {
+                PointBuilder::new()
+                    .position([x, y, z])
+                    .entry(0, idx)
+                    .build()
+            })
+            .collect();
+        let frame = PcdFrame {
+            height: 1,
+            width: data.len(),
+            data,
+        };
+
+        let (estimated_normal, estimated_offset) = frame.estimate_ground_plane().unwrap();
+        let error = dot(estimated_normal.into_inner(), normal.into_inner()).acos();
+        assert!(error < 0.1f64.to_radians());
+        assert!((estimated_offset.get::<meter>() - offset).abs() < 0.01);
+        assert!((estimated_normal.pitch() - normal.pitch()).abs() < Angle::new::<degree>(0.1));
+        assert!((estimated_normal.roll() - normal.roll()).abs() < Angle::new::<degree>(0.1));
+
+        assert!(PcdFrame::<SingleReturnPoint>::new()
+            .estimate_ground_plane()
+            .is_none());
+    }
+}
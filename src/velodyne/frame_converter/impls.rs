@@ -93,7 +93,53 @@ where
     }
 }
 
-fn points_to_frames<Point>(
+/// Discards the frame if it is the first emitted frame and `is_full` returns false.
+///
+/// The `pending` flag is cleared once a frame is emitted.
+pub(crate) fn skip_first_partial<F>(
+    pending: &mut bool,
+    frame: Option<F>,
+    is_full: impl Fn(&F) -> bool,
+) -> Option<F> {
+    let frame = frame?;
+    if mem::take(pending) && !is_full(&frame) {
+        None
+    } else {
+        Some(frame)
+    }
+}
+
+/// Organizes the points of complete columns into a frame, and discards the incomplete column if any.
+pub(crate) fn remaining_to_frame<Point>(mut points: Vec<Point>) -> Option<PcdFrame<Point>>
+where
+    Point: VelodynePoint + LidarFrameMsg + Copy,
+{
+    let beam_num = points.iter().map(|point| point.laser_id()).max()? as usize + 1;
+    let width = points.len() / beam_num;
+    if width == 0 {
+        return None;
+    }
+    points.truncate(width * beam_num);
+
+    // sort channel order by row_idx and renumber columns
+    points
+        .chunks_mut(beam_num)
+        .enumerate()
+        .for_each(|(col_idx, column)| {
+            column.sort_by_key(|point| point.row_idx());
+            column
+                .iter_mut()
+                .for_each(|point| point.set_col_idx(col_idx));
+        });
+
+    Some(PcdFrame {
+        height: beam_num,
+        width,
+        data: points,
+    })
+}
+
+pub(crate) fn points_to_frames<Point>(
     points: impl IntoIterator<Item = Point>,
 ) -> (Option<PcdFrame<Point>>, Vec<Point>)
 where
@@ -133,13 +179,13 @@ where
         }
 
         if prev_laser_id > point.laser_id() {
-            //previous data ID should either 31(for 32 beam laser) or 15(for 16 beam laser)
-            assert!(prev_laser_id == 15 || prev_laser_id == 31);
+            //previous data ID should either 63(for 64 beam laser), 31(for 32 beam laser) or 15(for 16 beam laser)
+            assert!(prev_laser_id == 15 || prev_laser_id == 31 || prev_laser_id == 63);
 
-            // input data length should be either 32 or 16
-            assert!(remaining_channel.len() == 16 || remaining_channel.len() == 32);
+            // input data length should be either 64, 32 or 16
+            assert!([16, 32, 64].contains(&remaining_channel.len()));
 
-            //count whether it is 32 beam or 16 beam
+            //count whether it is 64, 32 or 16 beam
             beam_num = (prev_laser_id + 1) as usize;
 
             //append to remaining_points when a line is collected
@@ -0,0 +1,109 @@
+use super::converter::FrameConverter;
+use crate::{
+    common::*,
+    velodyne::{
+        config::Config,
+        marker::{ModelMarker, ReturnTypeMarker},
+        packet::DataPacket,
+    },
+};
+
+/// Iterates frames over a borrowed slice of packets.
+///
+/// The packets are neither moved nor copied, and the points are accumulated in the buffer
+/// of the frame converter. The yielded frames own their points, so they can be kept
+/// after the slice is dropped. The trailing partial frame is flushed at the end of slice.
+/// The frame converter type has to be specified, for example,
+///
+/// ```no_run
+/// use lidar_utils::velodyne::{frames_over, Config, DataPacket, Vlp16_Strongest_FrameConverter};
+///
+/// # let packets: Vec<DataPacket> = vec![];
+/// let config = Config::vlp_16_strongest_return();
+/// for frame in frames_over::<Vlp16_Strongest_FrameConverter, _, _>(&packets, &config) {
+///     println!("{} points", frame.data.len());
+/// }
+/// ```
+pub fn frames_over<'a, Converter, Model, ReturnType>(
+    packets: &'a [DataPacket],
+    config: &Config<Model, ReturnType>,
+) -> impl Iterator<Item = Converter::Frame> + 'a
+where
+    Converter: FrameConverter<Model, ReturnType> + 'a,
+    Model: ModelMarker + 'a,
+    ReturnType: ReturnTypeMarker + 'a,
+    Config<Model, ReturnType>: Clone,
+{
+    FrameIter::new(Converter::from_config(config.clone()), packets)
+}
+
+/// An iterator that converts packets into frames lazily.
+///
+/// The trailing partial frame is flushed by [FrameConverter::finish] once the packets
+/// are exhausted, so that the last partial rotation of a capture is not dropped.
+///
+/// ```no_run
+/// use lidar_utils::velodyne::{
+///     Config, DataPacket, FrameConverter, FrameIter, Vlp16_Strongest_FrameConverter,
+/// };
+///
+/// # let packets: Vec<DataPacket> = vec![];
+/// let converter = Vlp16_Strongest_FrameConverter::from_config(Config::vlp_16_strongest_return());
+/// for frame in FrameIter::new(converter, packets) {
+///     println!("{} points", frame.data.len());
+/// }
+/// ```
+#[derive(Debug)]
+pub struct FrameIter<Converter, Packets, Model, ReturnType> {
+    converter: Converter,
+    packets: Packets,
+    finished: bool,
+    _phantom: PhantomData<(Model, ReturnType)>,
+}
+
+impl<Converter, Packets, Model, ReturnType> FrameIter<Converter, Packets, Model, ReturnType>
+where
+    Converter: FrameConverter<Model, ReturnType>,
+    Packets: Iterator,
+    Packets::Item: Borrow<DataPacket>,
+    Model: ModelMarker,
+    ReturnType: ReturnTypeMarker,
+{
+    pub fn new<I>(converter: Converter, packets: I) -> Self
+    where
+        I: IntoIterator<IntoIter = Packets>,
+    {
+        Self {
+            converter,
+            packets: packets.into_iter(),
+            finished: false,
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<Converter, Packets, Model, ReturnType> Iterator
+    for FrameIter<Converter, Packets, Model, ReturnType>
+where
+    Converter: FrameConverter<Model, ReturnType>,
+    Packets: Iterator,
+    Packets::Item: Borrow<DataPacket>,
+    Model: ModelMarker,
+    ReturnType: ReturnTypeMarker,
+{
+    type Item = Converter::Frame;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.finished {
+            return None;
+        }
+        for packet in &mut self.packets {
+            if let Some(frame) = self.converter.convert(packet) {
+                return Some(frame);
+            }
+        }
+        // flush the partial frame at end of input
+        self.finished = true;
+        self.converter.finish()
+    }
+}
@@ -0,0 +1,94 @@
+use super::converter::PcdFrame;
+use crate::{common::*, velodyne::point::VelodynePoint};
+use std::{cmp::Reverse, collections::BinaryHeap};
+
+/// Merges the points of frames into a single stream ordered by point timestamps,
+/// for example, for continuous-time SLAM.
+///
+/// The points within each frame must already be sorted by timestamps, and the frames
+/// are k-way merged. Converted frames order the points of a column by rows rather than
+/// by firing time, so such frames should be sorted first. The points with equal
+/// timestamps are ordered by the frame index.
+pub fn merge_frames_by_time<P>(frames: &[PcdFrame<P>]) -> Vec<P>
+where
+    P: Copy + VelodynePoint,
+{
+    let key = |point: &P| R64::new(point.timestamp().get::<second>());
+    let mut cursors: Vec<_> = frames
+        .iter()
+        .map(|frame| frame.data.iter().peekable())
+        .collect();
+    let mut heap: BinaryHeap<_> = cursors
+        .iter_mut()
+        .enumerate()
+        .filter_map(|(frame_idx, cursor)| Some(Reverse((key(cursor.peek()?), frame_idx))))
+        .collect();
+
+    let mut points = Vec::with_capacity(frames.iter().map(|frame| frame.data.len()).sum());
+    while let Some(Reverse((_, frame_idx))) = heap.pop() {
+        let cursor = &mut cursors[frame_idx];
+        points.extend(cursor.next());
+        if let Some(next) = cursor.peek() {
+            heap.push(Reverse((key(next), frame_idx)));
+        }
+    }
+    points
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::velodyne::{point::SingleReturnPoint, test_support::PointBuilder};
+
+    #[test]
+    fn merge_frames_by_time_test() {
+        let new_frame = |timestamps: &[f64]| {
+            let data: Vec<_> = timestamps
+                .iter()
+                .map(|&timestamp| {
+                    PointBuilder::new()
+                        .timestamp(Time::new::<microsecond>(timestamp))
+                        .position([timestamp; 3])
+                        .distance(Length::new::<meter>(1.0))
+                        .build()
+                })
+                .collect();
+            PcdFrame {
+                height: 1,
+                width: data.len(),
+                data,
+            }
+        };
+
+        // the frames overlap in time
+        let frames = vec![
+            new_frame(&[0.0, 2.0, 4.0, 6.0]),
+            new_frame(&[1.0, 3.0, 5.0]),
+            new_frame(&[]),
+            new_frame(&[5.5, 7.0]),
+        ];
+        let points = merge_frames_by_time(&frames);
+        let timestamps: Vec<_> = points
+            .iter()
+            .map(|point| point.timestamp.get::<microsecond>())
+            .collect();
+        assert_eq!(
+            timestamps,
+            vec![0.0, 1.0, 2.0, 3.0, 4.0, 5.0, 5.5, 6.0, 7.0]
+        );
+        assert!(points
+            .iter()
+            .all(|point| point.data.position[0].get::<meter>()
+                == point.timestamp.get::<microsecond>()));
+
+        // the points with equal timestamps are merged as well
+        let frames = vec![new_frame(&[1.0, 2.0]), new_frame(&[1.0, 2.0, 3.0])];
+        let timestamps: Vec<_> = merge_frames_by_time(&frames)
+            .iter()
+            .map(|point| point.timestamp.get::<microsecond>())
+            .collect();
+        assert_eq!(timestamps, vec![1.0, 1.0, 2.0, 2.0, 3.0]);
+
+        assert!(merge_frames_by_time::<SingleReturnPoint>(&[]).is_empty());
+    }
+}
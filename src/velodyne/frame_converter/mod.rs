@@ -1,6 +1,22 @@
 //! Velodyne frame converter that divides a collection of points into frames.
 
+#[cfg(feature = "arrow")]
+mod arrow_export;
 mod converter;
+#[cfg(feature = "ndarray")]
+mod density;
+mod ground;
 mod impls;
+mod iter;
+mod merge;
+#[cfg(feature = "pcd-rs")]
+mod pcd_export;
+mod raw;
+#[cfg(feature = "ndarray")]
+mod spherical;
 
 pub use converter::*;
+pub use ground::*;
+pub use iter::*;
+pub use merge::*;
+pub use raw::*;
@@ -0,0 +1,111 @@
+use super::converter::PcdFrame;
+use crate::{
+    common::*,
+    velodyne::point::{LidarFrameMsg, PointData, PrimaryData, VelodynePoint},
+};
+use pcd_rs::{DynRecord, Field, Schema, ValueKind};
+
+impl<P> PcdFrame<P>
+where
+    P: Copy + PrimaryData + VelodynePoint + LidarFrameMsg,
+{
+    /// Converts the frame into [pcd-rs](pcd_rs) records along with the schema.
+    ///
+    /// The fields are `x`, `y`, `z` in meters as `F32`, `intensity` as `U8`,
+    /// `ring` (the row index) as `U16` and `timestamp` in seconds as `F64`.
+    /// The records are ordered as `data`, and can be written with a
+    /// [DynWriter](pcd_rs::DynWriter) of the frame width and height.
+    /// See [to_pcd_rs](crate::export::to_pcd_rs) for points of other sensors.
+    pub fn to_pcd_rs(&self) -> (Schema, Vec<DynRecord>) {
+        let schema: Schema = vec![
+            ("x", ValueKind::F32, 1),
+            ("y", ValueKind::F32, 1),
+            ("z", ValueKind::F32, 1),
+            ("intensity", ValueKind::U8, 1),
+            ("ring", ValueKind::U16, 1),
+            ("timestamp", ValueKind::F64, 1),
+        ]
+        .into_iter()
+        .collect();
+
+        let records = self
+            .data
+            .iter()
+            .map(|point| {
+                let PointData {
+                    position: [x, y, z],
+                    intensity,
+                    ..
+                } = *point.primary_data();
+                DynRecord(vec![
+                    Field::F32(vec![x.get::<meter>() as f32]),
+                    Field::F32(vec![y.get::<meter>() as f32]),
+                    Field::F32(vec![z.get::<meter>() as f32]),
+                    Field::U8(vec![intensity]),
+                    Field::U16(vec![point.row_idx() as u16]),
+                    Field::F64(vec![point.timestamp().get::<second>()]),
+                ])
+            })
+            .collect();
+
+        (schema, records)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::velodyne::test_support::PointBuilder;
+
+    #[test]
+    fn to_pcd_rs_test() {
+        let data: Vec<_> = (0..2)
+            .map(|row_idx| {
+                PointBuilder::new()
+                    .laser_id(row_idx as u32)
+                    .timestamp(Time::new::<second>(1.5))
+                    .position([1.0, 2.0, 3.0])
+                    .distance(Length::new::<meter>(1.0))
+                    .intensity(7)
+                    .entry(row_idx, 0)
+                    .build()
+            })
+            .collect();
+        let frame = PcdFrame {
+            height: 2,
+            width: 1,
+            data,
+        };
+
+        let (schema, records) = frame.to_pcd_rs();
+        let fields: Vec<_> = schema
+            .fields
+            .iter()
+            .map(|field| (field.name.as_str(), field.kind, field.count))
+            .collect();
+        assert_eq!(
+            fields,
+            vec![
+                ("x", ValueKind::F32, 1),
+                ("y", ValueKind::F32, 1),
+                ("z", ValueKind::F32, 1),
+                ("intensity", ValueKind::U8, 1),
+                ("ring", ValueKind::U16, 1),
+                ("timestamp", ValueKind::F64, 1),
+            ]
+        );
+
+        assert_eq!(records.len(), 2);
+        assert_eq!(
+            records[1].0,
+            vec![
+                Field::F32(vec![1.0]),
+                Field::F32(vec![2.0]),
+                Field::F32(vec![3.0]),
+                Field::U8(vec![7]),
+                Field::U16(vec![1]),
+                Field::F64(vec![1.5]),
+            ]
+        );
+    }
+}
@@ -0,0 +1,228 @@
+use super::{converter::PcdFrame, impls};
+use crate::{
+    common::*,
+    velodyne::{
+        config::Config,
+        consts::BLOCKS_PER_PACKET,
+        marker::{ModelMarker, ReturnTypeMarker},
+        packet::DataPacket,
+        pcd_converter::PointCloudConverter,
+        point::{LidarFrameMsg, VelodynePoint},
+    },
+};
+
+/// A frame that keeps the undecoded packets of one rotation.
+///
+/// It can be re-converted to points later, for example, with an updated calibration.
+#[derive(Debug, Clone)]
+pub struct RawFrame {
+    pub packets: Vec<DataPacket>,
+    /// Whether the leading packets are shared with the previous frame. If it is set, the
+    /// points before the first zero azimuth crossing are dropped in reconversion.
+    pub shares_leading_packets: bool,
+}
+
+impl RawFrame {
+    /// Decodes the packets into a frame of points using the config.
+    ///
+    /// The frame is split on firings at zero azimuth crossings as [FrameConverter](super::FrameConverter)
+    /// does, so that it has the same points as the frame of the same rotation from the frame
+    /// converter. The converter type has to be specified, for example,
+    ///
+    /// ```no_run
+    /// use lidar_utils::velodyne::{Config, RawFrame, Vlp16_Strongest_PcdConverter};
+    ///
+    /// # let raw_frame = RawFrame { packets: vec![], shares_leading_packets: false };
+    /// let frame = raw_frame.reconvert::<Vlp16_Strongest_PcdConverter, _, _, _>(
+    ///     Config::vlp_16_strongest_return(),
+    /// )?;
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    pub fn reconvert<Converter, Model, ReturnType, P>(
+        &self,
+        config: Config<Model, ReturnType>,
+    ) -> Result<PcdFrame<P>>
+    where
+        Converter: PointCloudConverter<Model, ReturnType, Output = Vec<P>>,
+        Model: ModelMarker,
+        ReturnType: ReturnTypeMarker,
+        P: VelodynePoint + LidarFrameMsg + Copy,
+    {
+        let mut converter = Converter::from_config(config);
+        let mut frames = vec![];
+        let mut remaining_points = vec![];
+        for packet in self.packets.iter() {
+            let points = mem::take(&mut remaining_points)
+                .into_iter()
+                .chain(converter.convert(packet)?);
+            let (frame, new_remaining_points) = impls::points_to_frames(points);
+            frames.extend(frame);
+            remaining_points = new_remaining_points;
+        }
+
+        // the points before the first crossing belong to the previous frame
+        let frame = frames
+            .into_iter()
+            .nth(self.shares_leading_packets as usize)
+            .or_else(|| impls::remaining_to_frame(remaining_points))
+            .unwrap_or_else(PcdFrame::new);
+        Ok(frame)
+    }
+}
+
+/// Groups packets into [RawFrame]s by rotation without decoding them.
+///
+/// The packet that crosses zero azimuth closes the current frame. Since the rotation changes
+/// on firings, the packet is shared with the next frame, and so is the previous packet if
+/// the crossing is between the packets, whose last firings are decoded with the next packet.
+#[derive(Debug, Clone, Default)]
+pub struct RawFrameConverter {
+    packets: Vec<DataPacket>,
+    prev_packet: Option<DataPacket>,
+    shares_leading_packets: bool,
+}
+
+impl RawFrameConverter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Pushes a packet, and returns a frame if a rotation is completed.
+    pub fn convert<P>(&mut self, packet: P) -> Option<RawFrame>
+    where
+        P: Borrow<DataPacket>,
+    {
+        let packet = *packet.borrow();
+
+        // the rotation ends between the previous packet and this packet
+        let wrap_before = matches!(
+            self.prev_packet,
+            Some(prev) if packet.blocks[0].azimuth_count < prev.blocks[BLOCKS_PER_PACKET - 1].azimuth_count
+        );
+        // the rotation ends within this packet
+        let wrap_within = packet
+            .blocks
+            .windows(2)
+            .any(|pair| pair[1].azimuth_count < pair[0].azimuth_count);
+        let prev_packet = self.prev_packet.replace(packet);
+
+        self.packets.push(packet);
+        if !(wrap_before || wrap_within) {
+            return None;
+        }
+
+        let frame = self.finish();
+        if wrap_before {
+            self.packets.extend(prev_packet);
+        }
+        self.packets.push(packet);
+        self.shares_leading_packets = true;
+        frame
+    }
+
+    /// Flushes the remaining packets into a partial frame, which is typically called at end of input.
+    pub fn finish(&mut self) -> Option<RawFrame> {
+        let shares_leading_packets = mem::take(&mut self.shares_leading_packets);
+        if self.packets.is_empty() {
+            None
+        } else {
+            Some(RawFrame {
+                packets: mem::take(&mut self.packets),
+                shares_leading_packets,
+            })
+        }
+    }
+}
+
+/// Groups every fixed number of packets into a [RawFrame] regardless of azimuth.
+///
+/// It is an alternative to [RawFrameConverter] when the azimuth is unreliable,
+/// for example, on test benches spinning irregularly.
+#[derive(Debug, Clone)]
+pub struct FixedPacketFrameConverter {
+    packets_per_frame: usize,
+    packets: Vec<DataPacket>,
+}
+
+impl FixedPacketFrameConverter {
+    /// Creates a converter emitting a frame every `packets_per_frame` packets.
+    ///
+    /// # Panics
+    /// It panics if `packets_per_frame` is zero.
+    pub fn new(packets_per_frame: usize) -> Self {
+        assert!(packets_per_frame > 0, "packets_per_frame must be positive");
+        Self {
+            packets_per_frame,
+            packets: Vec::with_capacity(packets_per_frame),
+        }
+    }
+
+    pub fn packets_per_frame(&self) -> usize {
+        self.packets_per_frame
+    }
+
+    /// Pushes a packet, and returns a frame if enough packets are collected.
+    pub fn convert<P>(&mut self, packet: P) -> Option<RawFrame>
+    where
+        P: Borrow<DataPacket>,
+    {
+        self.packets.push(*packet.borrow());
+        if self.packets.len() == self.packets_per_frame {
+            self.finish()
+        } else {
+            None
+        }
+    }
+
+    /// Flushes the remaining packets into a partial frame, which is typically called at end of input.
+    pub fn finish(&mut self) -> Option<RawFrame> {
+        if self.packets.is_empty() {
+            None
+        } else {
+            let packets = mem::replace(
+                &mut self.packets,
+                Vec::with_capacity(self.packets_per_frame),
+            );
+            Some(RawFrame {
+                packets,
+                shares_leading_packets: false,
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::velodyne::test_support::PacketBuilder;
+
+    #[test]
+    fn fixed_packet_frame_converter_test() {
+        let packets: Vec<_> = (0..90)
+            .map(|idx| {
+                // an irregular azimuth that wraps many times
+                PacketBuilder::new()
+                    .timestamp(idx)
+                    .channels(500, 0)
+                    .azimuth_counts(idx as usize * 7919, 0)
+                    .build()
+            })
+            .collect();
+
+        let mut converter = FixedPacketFrameConverter::new(30);
+        let frames: Vec<_> = packets
+            .iter()
+            .filter_map(|packet| converter.convert(packet))
+            .collect();
+        assert_eq!(frames.len(), 3);
+        assert!(frames.iter().all(|frame| frame.packets.len() == 30));
+        assert_eq!({ frames[1].packets[0].timestamp }, 30);
+        assert!(converter.finish().is_none());
+
+        // the trailing packets are flushed at end of input
+        packets[..5].iter().for_each(|packet| {
+            assert!(converter.convert(packet).is_none());
+        });
+        assert_eq!(converter.finish().map(|frame| frame.packets.len()), Some(5));
+    }
+}
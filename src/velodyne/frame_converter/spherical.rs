@@ -0,0 +1,136 @@
+use super::converter::PcdFrame;
+use crate::{
+    common::*,
+    velodyne::point::{LidarFrameMsg, PointData, PrimaryData, VelodynePoint},
+};
+use ndarray::Array3;
+
+/// The number of channels of [PcdFrame::to_spherical_tensor].
+const SPHERICAL_CHANNELS: usize = 5;
+
+impl<P> PcdFrame<P>
+where
+    P: Copy + PrimaryData + LidarFrameMsg + VelodynePoint,
+{
+    /// Projects the points onto a spherical image for projection-based networks.
+    ///
+    /// The tensor is indexed by `[channel, row, col]` with the shape `(5, height, width)`,
+    /// where the channels are the range, intensity, x, y and z in order. The row is the row
+    /// index of the point, and the corrected azimuth angle spanning [0°, 360°) is divided
+    /// into `width` columns. If multiple points fall in a cell, the nearest one is kept.
+    /// Cells without points are filled with zeros. It panics if `width` is zero.
+    pub fn to_spherical_tensor(&self, width: usize) -> Array3<f32> {
+        assert!(width > 0, "the width must be nonzero");
+        let height = self.height;
+        let mut tensor = Array3::zeros((SPHERICAL_CHANNELS, height, width));
+
+        self.data
+            .iter()
+            .filter(|point| point.primary_data().distance > Length::new::<meter>(0.0))
+            .filter(|point| point.row_idx() < height)
+            .for_each(|point| {
+                let PointData {
+                    distance,
+                    intensity,
+                    position: [x, y, z],
+                    ..
+                } = *point.primary_data();
+                let azimuth = point
+                    .corrected_azimuth_angle()
+                    .get::<degree>()
+                    .rem_euclid(360.0);
+                let row_idx = point.row_idx();
+                let col_idx = ((azimuth / 360.0 * width as f64) as usize).min(width - 1);
+
+                let range = distance.get::<meter>() as f32;
+                let prev_range = tensor[[0, row_idx, col_idx]];
+                if prev_range != 0.0 && prev_range <= range {
+                    return;
+                }
+
+                let values = [
+                    range,
+                    intensity as f32,
+                    x.get::<meter>() as f32,
+                    y.get::<meter>() as f32,
+                    z.get::<meter>() as f32,
+                ];
+                values
+                    .iter()
+                    .enumerate()
+                    .for_each(|(channel, &value)| tensor[[channel, row_idx, col_idx]] = value);
+            });
+
+        tensor
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::common::*;
+    use crate::velodyne::{test_support::PointBuilder, PcdFrame};
+
+    #[test]
+    fn to_spherical_tensor_test() {
+        // the range grows with the index, and the intensity and the height are the row index
+        let new_point = |row_idx: usize, col_idx: usize| {
+            let azimuth = col_idx as f64 * 90.0 + 10.0;
+            let distance = (1 + row_idx * 4 + col_idx) as f64;
+            let (sin, cos) = azimuth.to_radians().sin_cos();
+            PointBuilder::new()
+                .laser_id(row_idx as u32)
+                .azimuth(Angle::new::<degree>(azimuth))
+                .position([distance * sin, distance * cos, row_idx as f64])
+                .distance(Length::new::<meter>(distance))
+                .intensity(row_idx as u8)
+                .entry(row_idx, col_idx)
+                .build()
+        };
+
+        // a 16x4 frame in column-major order, where the last point has no return
+        let (height, width) = (16, 4);
+        let mut data: Vec<_> = (0..width)
+            .flat_map(|col_idx| (0..height).map(move |row_idx| new_point(row_idx, col_idx)))
+            .collect();
+        data[height * width - 1].data.distance = Length::new::<meter>(0.0);
+        let frame = PcdFrame {
+            height,
+            width,
+            data,
+        };
+
+        // every point falls in its own cell
+        let tensor = frame.to_spherical_tensor(width);
+        assert_eq!(tensor.dim(), (5, height, width));
+        for row_idx in 0..height {
+            for col_idx in 0..width {
+                if (row_idx, col_idx) == (height - 1, width - 1) {
+                    assert!(tensor
+                        .slice(ndarray::s![.., row_idx, col_idx])
+                        .iter()
+                        .all(|&value| value == 0.0));
+                    continue;
+                }
+
+                let point = frame.point_at(row_idx, col_idx).unwrap();
+                let [x, y, z] = point.data.position;
+                assert_eq!(
+                    tensor[[0, row_idx, col_idx]],
+                    point.data.distance.get::<meter>() as f32
+                );
+                assert_eq!(tensor[[1, row_idx, col_idx]], row_idx as f32);
+                assert_eq!(tensor[[2, row_idx, col_idx]], x.get::<meter>() as f32);
+                assert_eq!(tensor[[3, row_idx, col_idx]], y.get::<meter>() as f32);
+                assert_eq!(tensor[[4, row_idx, col_idx]], z.get::<meter>() as f32);
+            }
+        }
+
+        // the columns at 10° and 100° share a cell, and the nearer point is kept
+        let tensor = frame.to_spherical_tensor(2);
+        assert_eq!(tensor.dim(), (5, height, 2));
+        for row_idx in 0..height {
+            assert_eq!(tensor[[0, row_idx, 0]], (1 + row_idx * 4) as f32);
+            assert_eq!(tensor[[0, row_idx, 1]], (3 + row_idx * 4) as f32);
+        }
+    }
+}
@@ -0,0 +1,141 @@
+//! Fusion of point clouds from multiple sensors.
+
+use super::{
+    frame_converter::PcdFrame,
+    point::{PointData, PrimaryData},
+};
+use crate::common::*;
+
+/// A rigid transform from a sensor frame to a common frame.
+///
+/// A position `p` is transformed to `rotation * p + translation`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Extrinsic {
+    /// The row-major rotation matrix.
+    pub rotation: [[f64; 3]; 3],
+    pub translation: [Length; 3],
+}
+
+impl Extrinsic {
+    pub fn identity() -> Self {
+        Self {
+            rotation: [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]],
+            translation: [Length::new::<meter>(0.0); 3],
+        }
+    }
+
+    pub fn from_translation(translation: [Length; 3]) -> Self {
+        Self {
+            translation,
+            ..Self::identity()
+        }
+    }
+
+    /// Transforms the position to the common frame.
+    pub fn transform(&self, position: [Length; 3]) -> [Length; 3] {
+        let mut output = self.translation;
+        izip!(output.iter_mut(), self.rotation.iter()).for_each(|(output, row)| {
+            *output += izip!(row.iter(), position.iter())
+                .map(|(&scale, &component)| component * scale)
+                .fold(Length::new::<meter>(0.0), |sum, component| sum + component);
+        });
+        output
+    }
+}
+
+impl Default for Extrinsic {
+    fn default() -> Self {
+        Self::identity()
+    }
+}
+
+/// A point in the common frame tagged with the sensor it comes from.
+#[derive(Debug, Clone, Copy)]
+pub struct FusedPoint<P> {
+    /// The index of source sensor, which is 0 for the reference sensor and 1 for the other.
+    pub sensor_id: u8,
+    /// The position in the common frame.
+    pub position: [Length; 3],
+    /// The original point in the sensor frame.
+    pub point: P,
+}
+
+/// Merges the frames of a dual-lidar rig into one point cloud.
+///
+/// The common frame is the frame of the reference sensor, and the extrinsic transforms
+/// positions from the other sensor to the reference sensor.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DualLidarFuser {
+    pub extrinsic: Extrinsic,
+}
+
+impl DualLidarFuser {
+    pub fn new(extrinsic: Extrinsic) -> Self {
+        Self { extrinsic }
+    }
+
+    /// Merges the synchronized frames of the reference sensor and the other sensor.
+    ///
+    /// The points of the reference sensor come first and are tagged with sensor ID 0,
+    /// followed by the transformed points of the other sensor tagged with sensor ID 1.
+    pub fn fuse<P>(&self, reference: &PcdFrame<P>, other: &PcdFrame<P>) -> Vec<FusedPoint<P>>
+    where
+        P: Copy + PrimaryData,
+    {
+        let reference_points = reference.data.iter().map(|point| FusedPoint {
+            sensor_id: 0,
+            position: point.primary_data().position,
+            point: *point,
+        });
+        let other_points = other.data.iter().map(|point| {
+            let PointData { position, .. } = *point.primary_data();
+            FusedPoint {
+                sensor_id: 1,
+                position: self.extrinsic.transform(position),
+                point: *point,
+            }
+        });
+        reference_points.chain(other_points).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::velodyne::{point::SingleReturnPoint, test_support::PointBuilder};
+
+    fn single_point_frame(position: [f64; 3]) -> PcdFrame<SingleReturnPoint> {
+        PcdFrame {
+            height: 1,
+            width: 1,
+            data: vec![PointBuilder::new().position(position).build()],
+        }
+    }
+
+    #[test]
+    fn dual_lidar_fuser_test() {
+        // the other sensor is mounted 2 meters ahead and 0.5 meters above the reference sensor
+        let fuser = DualLidarFuser::new(Extrinsic::from_translation([
+            Length::new::<meter>(0.0),
+            Length::new::<meter>(2.0),
+            Length::new::<meter>(0.5),
+        ]));
+        let reference = single_point_frame([1.0, 0.0, 0.0]);
+        let other = single_point_frame([0.0, 3.0, -1.0]);
+
+        let fused = fuser.fuse(&reference, &other);
+        let positions: Vec<_> = fused
+            .iter()
+            .map(|point| {
+                let [x, y, z] = point.position;
+                [x.get::<meter>(), y.get::<meter>(), z.get::<meter>()]
+            })
+            .collect();
+        assert_eq!(fused.len(), 2);
+        assert_eq!(fused[0].sensor_id, 0);
+        assert_eq!(positions[0], [1.0, 0.0, 0.0]);
+        assert_eq!(fused[1].sensor_id, 1);
+        assert_eq!(positions[1], [0.0, 5.0, -0.5]);
+        assert_eq!(fused[1].point.data.position, other.data[0].data.position);
+    }
+}
@@ -0,0 +1,301 @@
+//! Georeferencing of captures with a GNSS/INS trajectory.
+
+use super::{
+    frame_converter::PcdFrame,
+    fusion::Extrinsic,
+    point::{DualReturnPoint, PointData, PrimaryData, SingleReturnPoint, VelodynePoint},
+};
+use crate::common::*;
+
+/// A timestamped pose of the sensor in the world frame.
+///
+/// A position `p` in the sensor frame is transformed to `rotation * p + translation`
+/// in the world frame.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TrajectoryPose {
+    pub timestamp: Time,
+    pub translation: [Length; 3],
+    /// The rotation as a unit quaternion in `[w, x, y, z]` order.
+    pub rotation: [f64; 4],
+}
+
+impl TrajectoryPose {
+    /// Converts the pose to a rigid transform.
+    pub fn to_extrinsic(&self) -> Extrinsic {
+        let [w, x, y, z] = self.rotation;
+        Extrinsic {
+            rotation: [
+                [
+                    1.0 - 2.0 * (y * y + z * z),
+                    2.0 * (x * y - w * z),
+                    2.0 * (x * z + w * y),
+                ],
+                [
+                    2.0 * (x * y + w * z),
+                    1.0 - 2.0 * (x * x + z * z),
+                    2.0 * (y * z - w * x),
+                ],
+                [
+                    2.0 * (x * z - w * y),
+                    2.0 * (y * z + w * x),
+                    1.0 - 2.0 * (x * x + y * y),
+                ],
+            ],
+            translation: self.translation,
+        }
+    }
+}
+
+/// A sequence of sensor poses ordered by timestamps, such as the output of a GNSS/INS.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Trajectory {
+    poses: Vec<TrajectoryPose>,
+}
+
+impl Trajectory {
+    /// Creates a trajectory from poses.
+    ///
+    /// It fails if there is no pose, the timestamps are not strictly increasing,
+    /// or any rotation is not a unit quaternion.
+    pub fn new(poses: Vec<TrajectoryPose>) -> Result<Self> {
+        ensure!(!poses.is_empty(), "the trajectory has no poses");
+        ensure!(
+            poses
+                .windows(2)
+                .all(|pair| pair[0].timestamp < pair[1].timestamp),
+            "the timestamps of poses are not strictly increasing"
+        );
+        ensure!(
+            poses.iter().all(|pose| {
+                let norm = pose.rotation.iter().map(|value| value * value).sum::<f64>();
+                (norm - 1.0).abs() < 1e-6
+            }),
+            "the rotation of poses must be unit quaternions"
+        );
+        Ok(Self { poses })
+    }
+
+    pub fn poses(&self) -> &[TrajectoryPose] {
+        &self.poses
+    }
+
+    /// Returns the pose at the timestamp, which is interpolated linearly in translation
+    /// and spherically in rotation between the adjacent poses.
+    ///
+    /// It returns `None` if the timestamp is out of the time range of the trajectory.
+    pub fn pose_at(&self, timestamp: Time) -> Option<TrajectoryPose> {
+        let first = self.poses.first()?;
+        let last = self.poses.last()?;
+        if timestamp < first.timestamp || timestamp > last.timestamp {
+            return None;
+        }
+
+        // index of the first pose after the timestamp
+        let index = self
+            .poses
+            .partition_point(|pose| pose.timestamp <= timestamp);
+        if index == self.poses.len() {
+            return Some(*last);
+        }
+        let lhs = &self.poses[index - 1];
+        let rhs = &self.poses[index];
+        let ratio = ((timestamp - lhs.timestamp) / (rhs.timestamp - lhs.timestamp)).value;
+
+        let mut translation = lhs.translation;
+        izip!(translation.iter_mut(), rhs.translation.iter())
+            .for_each(|(lhs, &rhs)| *lhs += (rhs - *lhs) * ratio);
+
+        Some(TrajectoryPose {
+            timestamp,
+            translation,
+            rotation: slerp(lhs.rotation, rhs.rotation, ratio),
+        })
+    }
+}
+
+/// Interpolates the unit quaternions along the shorter arc.
+fn slerp(lhs: [f64; 4], rhs: [f64; 4], ratio: f64) -> [f64; 4] {
+    let mut dot: f64 = izip!(lhs.iter(), rhs.iter())
+        .map(|(lhs, rhs)| lhs * rhs)
+        .sum();
+    let rhs = if dot < 0.0 {
+        dot = -dot;
+        [-rhs[0], -rhs[1], -rhs[2], -rhs[3]]
+    } else {
+        rhs
+    };
+
+    // fall back to linear interpolation for nearly identical rotations
+    let (lhs_weight, rhs_weight) = if dot > 0.9995 {
+        (1.0 - ratio, ratio)
+    } else {
+        let theta = dot.acos();
+        let sin_theta = theta.sin();
+        (
+            ((1.0 - ratio) * theta).sin() / sin_theta,
+            (ratio * theta).sin() / sin_theta,
+        )
+    };
+
+    let mut output = [0.0; 4];
+    izip!(output.iter_mut(), lhs.iter(), rhs.iter())
+        .for_each(|(output, lhs, rhs)| *output = lhs * lhs_weight + rhs * rhs_weight);
+    let norm = output.iter().map(|value| value * value).sum::<f64>().sqrt();
+    output.iter_mut().for_each(|value| *value /= norm);
+    output
+}
+
+/// Points of which the positions can be transformed to another frame.
+pub trait TransformPosition {
+    /// Transforms the positions of all returns of the point.
+    fn transform_position(&mut self, extrinsic: &Extrinsic);
+}
+
+impl TransformPosition for SingleReturnPoint {
+    fn transform_position(&mut self, extrinsic: &Extrinsic) {
+        self.data.position = extrinsic.transform(self.data.position);
+    }
+}
+
+impl TransformPosition for DualReturnPoint {
+    fn transform_position(&mut self, extrinsic: &Extrinsic) {
+        self.strongest_return_data.position =
+            extrinsic.transform(self.strongest_return_data.position);
+        self.last_return_data.position = extrinsic.transform(self.last_return_data.position);
+    }
+}
+
+/// Accumulates the frames of a capture into one world-frame cloud.
+///
+/// Each point is deskewed by the pose interpolated at its own timestamp, so the motion
+/// within frames is compensated. The points without returns and the points outside the
+/// time range of the trajectory are dropped. The output is an unorganized frame with
+/// height 1, where the points are ordered as the input frames.
+pub fn georeference<P>(frames: &[PcdFrame<P>], trajectory: &Trajectory) -> PcdFrame<P>
+where
+    P: Copy + VelodynePoint + PrimaryData + TransformPosition,
+{
+    let data: Vec<P> = frames
+        .iter()
+        .flat_map(|frame| frame.data.iter())
+        .filter(|point| {
+            let PointData { distance, .. } = *point.primary_data();
+            distance > Length::new::<meter>(0.0)
+        })
+        .filter_map(|point| {
+            let pose = trajectory.pose_at(point.timestamp())?;
+            let mut point = *point;
+            point.transform_position(&pose.to_extrinsic());
+            Some(point)
+        })
+        .collect();
+
+    PcdFrame {
+        height: 1,
+        width: data.len(),
+        data,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::velodyne::test_support::PointBuilder;
+
+    fn new_point(timestamp: f64, position: [f64; 3]) -> SingleReturnPoint {
+        PointBuilder::new()
+            .timestamp(Time::new::<second>(timestamp))
+            .position(position)
+            .build()
+    }
+
+    #[test]
+    fn georeference_test() -> Result<()> {
+        // the sensor moves 10 meters along x axis in 1 second, and turns 90 degrees
+        // counter-clockwise in the next second
+        let half_sqrt = std::f64::consts::FRAC_1_SQRT_2;
+        let pose = |timestamp: f64, x: f64, rotation: [f64; 4]| TrajectoryPose {
+            timestamp: Time::new::<second>(timestamp),
+            translation: [
+                Length::new::<meter>(x),
+                Length::new::<meter>(0.0),
+                Length::new::<meter>(0.0),
+            ],
+            rotation,
+        };
+        let trajectory = Trajectory::new(vec![
+            pose(0.0, 0.0, [1.0, 0.0, 0.0, 0.0]),
+            pose(1.0, 10.0, [1.0, 0.0, 0.0, 0.0]),
+            pose(2.0, 10.0, [half_sqrt, 0.0, 0.0, half_sqrt]),
+        ])?;
+
+        let frames = vec![
+            PcdFrame {
+                height: 1,
+                width: 3,
+                data: vec![
+                    new_point(0.0, [1.0, 0.0, 0.0]),
+                    new_point(0.5, [1.0, 0.0, 0.0]),
+                    new_point(0.75, [0.0, 0.0, 0.0]),
+                ],
+            },
+            PcdFrame {
+                height: 1,
+                width: 3,
+                data: vec![
+                    new_point(1.0, [1.0, 0.0, 1.0]),
+                    new_point(2.0, [1.0, 0.0, 0.0]),
+                    new_point(3.0, [1.0, 0.0, 0.0]),
+                ],
+            },
+        ];
+        let cloud = georeference(&frames, &trajectory);
+
+        // the point without return and the point after the trajectory are dropped
+        let positions: Vec<_> = cloud
+            .data
+            .iter()
+            .map(|point| {
+                let [x, y, z] = point.data.position;
+                [x.get::<meter>(), y.get::<meter>(), z.get::<meter>()]
+            })
+            .collect();
+        let expect = [
+            [1.0, 0.0, 0.0],
+            [6.0, 0.0, 0.0],
+            [11.0, 0.0, 1.0],
+            [10.0, 1.0, 0.0],
+        ];
+        assert_eq!(cloud.height, 1);
+        assert_eq!(cloud.width, 4);
+        assert_eq!(positions.len(), expect.len());
+        izip!(positions.iter(), expect.iter()).for_each(|(position, expect)| {
+            izip!(position.iter(), expect.iter())
+                .for_each(|(value, expect)| assert!((value - expect).abs() < 1e-9));
+        });
+
+        // the rotation is interpolated halfway at 1.5 seconds
+        let pose = trajectory.pose_at(Time::new::<second>(1.5)).unwrap();
+        let [x, y, _] = pose
+            .to_extrinsic()
+            .transform(new_point(1.5, [1.0, 0.0, 0.0]).data.position);
+        assert!((x.get::<meter>() - (10.0 + half_sqrt)).abs() < 1e-9);
+        assert!((y.get::<meter>() - half_sqrt).abs() < 1e-9);
+
+        Ok(())
+    }
+
+    #[test]
+    fn trajectory_validation_test() {
+        let pose = |timestamp: f64| TrajectoryPose {
+            timestamp: Time::new::<second>(timestamp),
+            translation: [Length::new::<meter>(0.0); 3],
+            rotation: [1.0, 0.0, 0.0, 0.0],
+        };
+        assert!(Trajectory::new(vec![]).is_err());
+        assert!(Trajectory::new(vec![pose(1.0), pose(0.0)]).is_err());
+        let mut scaled = pose(2.0);
+        scaled.rotation = [2.0, 0.0, 0.0, 0.0];
+        assert!(Trajectory::new(vec![pose(1.0), scaled]).is_err());
+    }
+}
@@ -0,0 +1,195 @@
+//! Consistency statistics of packet streams.
+
+use super::{
+    consts::{AZIMUTH_COUNT_PER_REV, BLOCKS_PER_PACKET, TIMESTAMP_PERIOD},
+    packet::DataPacket,
+};
+use crate::{
+    common::*,
+    diagnostic::{Diagnostic, ValidationLevel},
+};
+
+/// Tallies the anomalies of a data packet stream, which flag corruptions since
+/// Velodyne packets have no CRC.
+///
+/// A packet may have multiple kinds of anomalies, while it is counted once in
+/// `anomalous_packets`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct StreamHealth {
+    /// The number of received packets.
+    pub packets: usize,
+    /// Packets of which the size is not a data packet.
+    pub malformed_packets: usize,
+    /// Packets having invalid block identifiers.
+    pub invalid_block_identifiers: usize,
+    /// Packets having unknown return mode or product ID bytes.
+    pub invalid_factory_bytes: usize,
+    /// Packets of which the timestamp does not increase from the previous packet.
+    /// The wrap-around at the top of hour is not counted.
+    pub non_monotonic_timestamps: usize,
+    /// Packets of which the block azimuths decrease within the packet.
+    /// The wrap-around at the zero azimuth is not counted.
+    pub non_monotonic_azimuths: usize,
+    /// Packets having any of the anomalies above.
+    pub anomalous_packets: usize,
+    last_timestamp: Option<u32>,
+}
+
+impl StreamHealth {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Checks a packet in bytes and updates the counters.
+    pub fn push(&mut self, buffer: &[u8]) {
+        self.packets += 1;
+
+        if buffer.len() != mem::size_of::<DataPacket>() {
+            self.malformed_packets += 1;
+            self.anomalous_packets += 1;
+            return;
+        }
+
+        let mut diagnostics = vec![];
+        let packet =
+            DataPacket::from_slice_validated(buffer, ValidationLevel::Lenient, &mut diagnostics)
+                .unwrap();
+        let mut is_anomalous = false;
+        if diagnostics
+            .iter()
+            .any(|diagnostic| matches!(diagnostic, Diagnostic::InvalidBlockIdentifier { .. }))
+        {
+            self.invalid_block_identifiers += 1;
+            is_anomalous = true;
+        }
+        if diagnostics
+            .iter()
+            .any(|diagnostic| matches!(diagnostic, Diagnostic::InvalidFactoryBytes { .. }))
+        {
+            self.invalid_factory_bytes += 1;
+            is_anomalous = true;
+        }
+
+        if let Some(packet) = packet {
+            if !self.check_timestamp(packet.timestamp) {
+                self.non_monotonic_timestamps += 1;
+                is_anomalous = true;
+            }
+            if !Self::check_azimuths(&packet) {
+                self.non_monotonic_azimuths += 1;
+                is_anomalous = true;
+            }
+        }
+
+        if is_anomalous {
+            self.anomalous_packets += 1;
+        }
+    }
+
+    /// Returns the ratio of anomalous packets to all packets, or zero if no packet is received.
+    pub fn anomaly_rate(&self) -> f64 {
+        self.rate(self.anomalous_packets)
+    }
+
+    /// Returns the ratio of packets with invalid factory bytes.
+    pub fn invalid_factory_bytes_rate(&self) -> f64 {
+        self.rate(self.invalid_factory_bytes)
+    }
+
+    /// Returns the ratio of packets with non-monotonic timestamps.
+    pub fn non_monotonic_timestamp_rate(&self) -> f64 {
+        self.rate(self.non_monotonic_timestamps)
+    }
+
+    /// Returns the ratio of packets with non-monotonic azimuths.
+    pub fn non_monotonic_azimuth_rate(&self) -> f64 {
+        self.rate(self.non_monotonic_azimuths)
+    }
+
+    fn rate(&self, count: usize) -> f64 {
+        if self.packets == 0 {
+            0.0
+        } else {
+            count as f64 / self.packets as f64
+        }
+    }
+
+    fn check_timestamp(&mut self, timestamp: u32) -> bool {
+        let prev_timestamp = match self.last_timestamp.replace(timestamp) {
+            Some(prev_timestamp) => prev_timestamp,
+            None => return true,
+        };
+
+        // a large backward step is the wrap-around at the top of hour
+        let diff = timestamp as i64 - prev_timestamp as i64;
+        !(-TIMESTAMP_PERIOD / 2..=0).contains(&diff)
+    }
+
+    fn check_azimuths(packet: &DataPacket) -> bool {
+        let num_counts = AZIMUTH_COUNT_PER_REV as i64;
+        (1..BLOCKS_PER_PACKET).all(|block_idx| {
+            let prev = packet.blocks[block_idx - 1].azimuth_count as i64;
+            let curr = packet.blocks[block_idx].azimuth_count as i64;
+
+            // wrap the difference into [-half revolution, half revolution)
+            let diff = (curr - prev + num_counts / 2).rem_euclid(num_counts) - num_counts / 2;
+            diff >= 0
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::velodyne::test_support::PacketBuilder;
+
+    fn new_packet(timestamp: u32, start_azimuth_count: u16) -> DataPacket {
+        PacketBuilder::new()
+            .timestamp(timestamp)
+            .channels(500, 0)
+            .azimuth_counts(start_azimuth_count as usize, 40)
+            .build()
+    }
+
+    #[test]
+    fn stream_health_test() {
+        let mut health = StreamHealth::new();
+        assert_eq!(health.anomaly_rate(), 0.0);
+
+        // healthy packets crossing the zero azimuth and the top of hour
+        health.push(&new_packet(3_599_999_000, 35800).to_bytes());
+        health.push(&new_packet(100, 200).to_bytes());
+        assert_eq!(health.packets, 2);
+        assert_eq!(health.anomalous_packets, 0);
+
+        // a timestamp going backward
+        health.push(&new_packet(50, 700).to_bytes());
+        assert_eq!(health.non_monotonic_timestamps, 1);
+
+        // the azimuth decreases within the packet
+        let mut packet = new_packet(1000, 1200);
+        packet.blocks[5].azimuth_count = 1000;
+        health.push(&packet.to_bytes());
+        assert_eq!(health.non_monotonic_azimuths, 1);
+
+        // corrupted factory bytes and block identifier
+        let mut bytes = new_packet(2000, 1700).to_bytes();
+        let len = bytes.len();
+        bytes[len - 1] = 0x77;
+        bytes[0] = 0x00;
+        health.push(&bytes);
+        assert_eq!(health.invalid_factory_bytes, 1);
+        assert_eq!(health.invalid_block_identifiers, 1);
+
+        // a truncated packet
+        health.push(&bytes[..100]);
+        assert_eq!(health.malformed_packets, 1);
+
+        assert_eq!(health.packets, 6);
+        assert_eq!(health.anomalous_packets, 4);
+        assert!((health.anomaly_rate() - 4.0 / 6.0).abs() < 1e-9);
+        assert!((health.non_monotonic_timestamp_rate() - 1.0 / 6.0).abs() < 1e-9);
+        assert!((health.non_monotonic_azimuth_rate() - 1.0 / 6.0).abs() < 1e-9);
+        assert!((health.invalid_factory_bytes_rate() - 1.0 / 6.0).abs() < 1e-9);
+    }
+}
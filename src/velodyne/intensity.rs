@@ -0,0 +1,183 @@
+//! Per-ring intensity calibration and intensity reporting modes.
+
+use super::{
+    config::ParamsConfig,
+    point::{DualReturnPoint, DynamicReturnPoints, PointData, SingleReturnPoint},
+};
+use crate::common::*;
+
+/// The range at which the focal distance is normalized in the intensity compensation.
+const FOCAL_DISTANCE_SCALE_METERS: f64 = 131.0;
+/// The range covered by 16-bit distance counts at 2 millimeter resolution.
+const DISTANCE_SCALE_METERS: f64 = 131.07;
+
+/// Linear intensity response curves of lasers.
+///
+/// The corrected intensity is `gain * raw + offset`, which is rounded and
+/// clamped to the range of `u8`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct IntensityCalibration {
+    /// The `(gain, offset)` pairs indexed by laser ID.
+    pub per_ring: Vec<(f64, f64)>,
+}
+
+impl IntensityCalibration {
+    /// Returns the corrected intensity of the laser.
+    pub fn correct(&self, laser_id: u32, intensity: u8) -> u8 {
+        let (gain, offset) = self.per_ring[laser_id as usize];
+        (gain * intensity as f64 + offset)
+            .round()
+            .clamp(0.0, u8::MAX as f64) as u8
+    }
+
+    pub(crate) fn validate(&self, num_lasers: usize) -> Result<()> {
+        ensure!(
+            self.per_ring.len() == num_lasers,
+            "expect {} intensity calibration entries, but get {}",
+            num_lasers,
+            self.per_ring.len()
+        );
+        Ok(())
+    }
+
+    fn correct_data(&self, laser_id: u32, data: &mut PointData) {
+        data.intensity = self.correct(laser_id, data.intensity);
+    }
+}
+
+/// Focal calibration of a laser, which is found in HDL-64E calibration files.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FocalParameter {
+    pub focal_distance: Length,
+    pub focal_slope: f64,
+}
+
+/// Determines the `intensity_value` of points.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub enum IntensityMode {
+    /// Passes the reflectivity byte as is.
+    #[default]
+    Raw,
+    /// Scales the reflectivity byte to the range from 0.0 to 1.0.
+    Normalized,
+    /// Compensates the intensity falloff around the focal distance of each laser,
+    /// where the parameters are indexed by laser ID.
+    ///
+    /// The compensated intensity is
+    /// `raw + focal_slope * |256 * (1 - focal_distance / 131 m)² - 256 * (1 - distance / 131.07 m)²|`,
+    /// which is clamped to the range of `u8`.
+    Calibrated(Vec<FocalParameter>),
+}
+
+impl IntensityMode {
+    /// Creates the calibrated mode from the focal parameters in calibration file.
+    pub fn calibrated(params: &ParamsConfig) -> Self {
+        let focal_params = params
+            .lasers()
+            .iter()
+            .map(|laser| FocalParameter {
+                focal_distance: Length::new::<meter>(laser.focal_distance),
+                focal_slope: laser.focal_slope,
+            })
+            .collect();
+        Self::Calibrated(focal_params)
+    }
+
+    /// Returns the intensity value of a return from the laser.
+    pub fn apply(&self, laser_id: u32, intensity: u8, distance: Length) -> f64 {
+        match self {
+            Self::Raw => intensity as f64,
+            Self::Normalized => intensity as f64 / u8::MAX as f64,
+            Self::Calibrated(focal_params) => {
+                // zero distance indicates no return
+                if distance == Length::new::<meter>(0.0) {
+                    return intensity as f64;
+                }
+                let FocalParameter {
+                    focal_distance,
+                    focal_slope,
+                } = focal_params[laser_id as usize];
+                let focal_offset = 256.0
+                    * (1.0 - focal_distance.get::<meter>() / FOCAL_DISTANCE_SCALE_METERS).powi(2);
+                let range_offset =
+                    256.0 * (1.0 - distance.get::<meter>() / DISTANCE_SCALE_METERS).powi(2);
+                (intensity as f64 + focal_slope * (focal_offset - range_offset).abs())
+                    .clamp(0.0, u8::MAX as f64)
+            }
+        }
+    }
+
+    pub(crate) fn validate(&self, num_lasers: usize) -> Result<()> {
+        if let Self::Calibrated(focal_params) = self {
+            ensure!(
+                focal_params.len() == num_lasers,
+                "expect {} focal parameters, but get {}",
+                num_lasers,
+                focal_params.len()
+            );
+        }
+        Ok(())
+    }
+
+    fn apply_data(&self, laser_id: u32, data: &mut PointData) {
+        data.intensity_value = self.apply(laser_id, data.intensity, data.distance);
+    }
+}
+
+pub(crate) trait CalibrateIntensity {
+    fn calibrate_intensity(&mut self, calibration: &IntensityCalibration);
+}
+
+impl CalibrateIntensity for Vec<SingleReturnPoint> {
+    fn calibrate_intensity(&mut self, calibration: &IntensityCalibration) {
+        self.iter_mut()
+            .for_each(|point| calibration.correct_data(point.laser_id, &mut point.data));
+    }
+}
+
+impl CalibrateIntensity for Vec<DualReturnPoint> {
+    fn calibrate_intensity(&mut self, calibration: &IntensityCalibration) {
+        self.iter_mut().for_each(|point| {
+            calibration.correct_data(point.laser_id, &mut point.strongest_return_data);
+            calibration.correct_data(point.laser_id, &mut point.last_return_data);
+        });
+    }
+}
+
+impl CalibrateIntensity for DynamicReturnPoints {
+    fn calibrate_intensity(&mut self, calibration: &IntensityCalibration) {
+        match self {
+            Self::Single(points) => points.calibrate_intensity(calibration),
+            Self::Dual(points) => points.calibrate_intensity(calibration),
+        }
+    }
+}
+
+pub(crate) trait ApplyIntensityMode {
+    fn apply_intensity_mode(&mut self, mode: &IntensityMode);
+}
+
+impl ApplyIntensityMode for Vec<SingleReturnPoint> {
+    fn apply_intensity_mode(&mut self, mode: &IntensityMode) {
+        self.iter_mut()
+            .for_each(|point| mode.apply_data(point.laser_id, &mut point.data));
+    }
+}
+
+impl ApplyIntensityMode for Vec<DualReturnPoint> {
+    fn apply_intensity_mode(&mut self, mode: &IntensityMode) {
+        self.iter_mut().for_each(|point| {
+            mode.apply_data(point.laser_id, &mut point.strongest_return_data);
+            mode.apply_data(point.laser_id, &mut point.last_return_data);
+        });
+    }
+}
+
+impl ApplyIntensityMode for DynamicReturnPoints {
+    fn apply_intensity_mode(&mut self, mode: &IntensityMode) {
+        match self {
+            Self::Single(points) => points.apply_intensity_mode(mode),
+            Self::Dual(points) => points.apply_intensity_mode(mode),
+        }
+    }
+}
@@ -1,6 +1,10 @@
 //! Marker traits and types that are mainly used by config types.
 
-use super::{config::LaserParameter, packet::ReturnMode};
+use super::{
+    config::LaserParameter,
+    consts::{FIRING_PERIOD, HDL_64E_FIRING_PERIOD},
+    packet::ReturnMode,
+};
 use crate::common::*;
 
 pub use model::*;
@@ -71,10 +75,18 @@ mod model {
     pub use super::*;
 
     pub trait ModelMarker {
-        type ParamArray;
+        type ParamArray: AsRef<[LaserParameter]>;
 
         fn into_dynamic(self) -> DynamicModel;
         fn to_dynamic_params(params: Self::ParamArray) -> Vec<LaserParameter>;
+
+        /// Returns the model with the given number of lasers, or `None` if they mismatch.
+        fn from_num_lasers(num_lasers: usize) -> Option<Self>
+        where
+            Self: Sized;
+
+        /// Returns the time period between consecutive firings.
+        fn firing_period(&self) -> Time;
     }
 
     #[derive(Debug, Clone, Copy)]
@@ -90,6 +102,14 @@ mod model {
         fn to_dynamic_params(params: Self::ParamArray) -> Vec<LaserParameter> {
             params.into()
         }
+
+        fn from_num_lasers(num_lasers: usize) -> Option<Self> {
+            (num_lasers == 16).then_some(Vlp16)
+        }
+
+        fn firing_period(&self) -> Time {
+            Time::new::<microsecond>(FIRING_PERIOD)
+        }
     }
 
     #[derive(Debug, Clone, Copy)]
@@ -105,12 +125,70 @@ mod model {
         fn to_dynamic_params(params: Self::ParamArray) -> Vec<LaserParameter> {
             params.into()
         }
+
+        fn from_num_lasers(num_lasers: usize) -> Option<Self> {
+            (num_lasers == 32).then_some(Vlp32)
+        }
+
+        fn firing_period(&self) -> Time {
+            Time::new::<microsecond>(FIRING_PERIOD)
+        }
+    }
+
+    #[derive(Debug, Clone, Copy)]
+    pub struct Hdl64;
+
+    impl ModelMarker for Hdl64 {
+        type ParamArray = [LaserParameter; 64];
+
+        fn into_dynamic(self) -> DynamicModel {
+            DynamicModel::Hdl64
+        }
+
+        fn to_dynamic_params(params: Self::ParamArray) -> Vec<LaserParameter> {
+            params.into()
+        }
+
+        fn from_num_lasers(num_lasers: usize) -> Option<Self> {
+            (num_lasers == 64).then_some(Hdl64)
+        }
+
+        fn firing_period(&self) -> Time {
+            Time::new::<microsecond>(HDL_64E_FIRING_PERIOD)
+        }
+    }
+
+    /// Model with N lasers and user-defined laser parameters.
+    #[derive(Debug, Clone, Copy)]
+    pub struct CustomModel<const N: usize>;
+
+    impl<const N: usize> ModelMarker for CustomModel<N> {
+        type ParamArray = [LaserParameter; N];
+
+        fn into_dynamic(self) -> DynamicModel {
+            DynamicModel::Custom(N)
+        }
+
+        fn to_dynamic_params(params: Self::ParamArray) -> Vec<LaserParameter> {
+            params.into()
+        }
+
+        fn from_num_lasers(num_lasers: usize) -> Option<Self> {
+            (num_lasers == N).then_some(CustomModel)
+        }
+
+        fn firing_period(&self) -> Time {
+            Time::new::<microsecond>(FIRING_PERIOD)
+        }
     }
 
     #[derive(Debug, Clone, Copy)]
     pub enum DynamicModel {
         Vlp16,
         Vlp32,
+        Hdl64,
+        /// Custom model with the number of lasers.
+        Custom(usize),
     }
 
     impl ModelMarker for DynamicModel {
@@ -123,5 +201,26 @@ mod model {
         fn to_dynamic_params(params: Self::ParamArray) -> Vec<LaserParameter> {
             params
         }
+
+        /// Maps 16, 32 and 64 lasers to VLP-16, VLP-32C and HDL-64E respectively,
+        /// and the other numbers to custom models.
+        fn from_num_lasers(num_lasers: usize) -> Option<Self> {
+            let model = match num_lasers {
+                16 => Self::Vlp16,
+                32 => Self::Vlp32,
+                64 => Self::Hdl64,
+                _ => Self::Custom(num_lasers),
+            };
+            Some(model)
+        }
+
+        fn firing_period(&self) -> Time {
+            match self {
+                Self::Vlp16 => Vlp16.firing_period(),
+                Self::Vlp32 => Vlp32.firing_period(),
+                Self::Hdl64 => Hdl64.firing_period(),
+                Self::Custom(_) => Time::new::<microsecond>(FIRING_PERIOD),
+            }
+        }
     }
 }
@@ -0,0 +1,100 @@
+//! Masks of spurious returns from the sensor mounting structure.
+
+use super::point::{DualReturnPoint, DynamicReturnPoints, PointData, SingleReturnPoint};
+use crate::common::*;
+
+/// A window in sensor coordinates, bounded by azimuth, elevation and distance ranges.
+///
+/// The window is in the sensor frame, that is, converters apply the mask before
+/// [Config::azimuth_zero_offset](super::config::Config::azimuth_zero_offset) and
+/// [Config::flip_elevation_sign](super::config::Config::flip_elevation_sign).
+#[derive(Debug, Clone)]
+pub struct MaskWindow {
+    /// Range of corrected azimuth angle in [0, 2π). It wraps around zero if start is greater than end.
+    pub azimuth_range: Range<Angle>,
+    /// Range of elevation angle.
+    pub elevation_range: Range<Angle>,
+    /// Range of distance.
+    pub distance_range: Range<Length>,
+}
+
+impl MaskWindow {
+    /// Checks if a measurement falls in the window.
+    pub fn contains(&self, azimuth: Angle, elevation: Angle, distance: Length) -> bool {
+        let Range { start, end } = self.azimuth_range;
+        let azimuth_matched = if start <= end {
+            start <= azimuth && azimuth < end
+        } else {
+            start <= azimuth || azimuth < end
+        };
+
+        azimuth_matched
+            && self.elevation_range.contains(&elevation)
+            && self.distance_range.contains(&distance)
+    }
+}
+
+/// Drops the points hitting the mounting structure, which appear at fixed angles and short range.
+#[derive(Debug, Clone, Default)]
+pub struct SelfReturnMask {
+    pub windows: Vec<MaskWindow>,
+}
+
+impl SelfReturnMask {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a window to the mask.
+    pub fn with_window(mut self, window: MaskWindow) -> Self {
+        self.windows.push(window);
+        self
+    }
+
+    /// Checks if a measurement falls in any of the windows.
+    pub fn contains(&self, azimuth: Angle, elevation: Angle, distance: Length) -> bool {
+        self.windows
+            .iter()
+            .any(|window| window.contains(azimuth, elevation, distance))
+    }
+
+    fn contains_data(&self, azimuth: Angle, data: &PointData) -> bool {
+        let [x, y, z] = data.position;
+        let elevation = {
+            let horizontal = (x * x + y * y).sqrt();
+            Angle::new::<radian>(z.get::<meter>().atan2(horizontal.get::<meter>()))
+        };
+        self.contains(azimuth, elevation, data.distance)
+    }
+}
+
+/// Removes masked points from converted points.
+pub(crate) trait MaskPoints {
+    fn mask(&mut self, mask: &SelfReturnMask);
+}
+
+impl MaskPoints for Vec<SingleReturnPoint> {
+    fn mask(&mut self, mask: &SelfReturnMask) {
+        self.retain(|point| !mask.contains_data(point.corrected_azimuth_angle, &point.data));
+    }
+}
+
+impl MaskPoints for Vec<DualReturnPoint> {
+    /// Drops a point only if both returns are masked.
+    fn mask(&mut self, mask: &SelfReturnMask) {
+        self.retain(|point| {
+            let azimuth = point.corrected_azimuth_angle;
+            !(mask.contains_data(azimuth, &point.strongest_return_data)
+                && mask.contains_data(azimuth, &point.last_return_data))
+        });
+    }
+}
+
+impl MaskPoints for DynamicReturnPoints {
+    fn mask(&mut self, mask: &SelfReturnMask) {
+        match self {
+            Self::Single(points) => points.mask(mask),
+            Self::Dual(points) => points.mask(mask),
+        }
+    }
+}
@@ -1,20 +1,48 @@
 //! Velodyne packet format types, configs and converters.
 
+#[cfg(feature = "pcap")]
+pub mod capture;
 pub mod config;
 pub mod consts;
 pub mod frame_converter;
+pub mod fusion;
+pub mod georeference;
+pub mod health;
+pub mod intensity;
 pub mod marker;
+pub mod mask;
+pub mod multi_echo;
+pub mod near_field;
 pub mod packet;
 pub mod pcd_converter;
+pub mod pipeline;
 pub mod point;
+pub mod quantile;
+pub mod reorder;
+pub mod temperature;
+#[cfg(test)]
+pub(crate) mod test_support;
 
 pub mod prelude {
     pub use super::{pcd_converter::PointCloudConverter, point::VelodynePoint};
 }
 
+#[cfg(feature = "pcap")]
+pub use capture::*;
 pub use config::*;
 pub use frame_converter::*;
+pub use fusion::*;
+pub use georeference::*;
+pub use health::*;
+pub use intensity::*;
 pub use marker::*;
+pub use mask::*;
+pub use multi_echo::*;
+pub use near_field::*;
 pub use packet::*;
 pub use pcd_converter::*;
+pub use pipeline::*;
 pub use point::*;
+pub use quantile::*;
+pub use reorder::*;
+pub use temperature::*;
@@ -0,0 +1,241 @@
+//! Frames keeping all echoes of each beam.
+
+use super::{
+    frame_converter::PcdFrame,
+    point::{DualReturnPoint, PointData, ReturnKind, SingleReturnPoint},
+};
+use crate::common::*;
+use smallvec::SmallVec;
+
+/// A return of a laser beam.
+#[derive(Debug, Clone, Copy)]
+pub struct Echo {
+    pub distance: Length,
+    pub intensity: u8,
+    pub position: [Length; 3],
+    /// Indicates whether it is the strongest or the last return.
+    pub return_kind: ReturnKind,
+}
+
+impl Echo {
+    fn new(data: &PointData, return_kind: ReturnKind) -> Self {
+        let PointData {
+            distance,
+            intensity,
+            position,
+            ..
+        } = *data;
+        Self {
+            distance,
+            intensity,
+            position,
+            return_kind,
+        }
+    }
+}
+
+/// The echoes of a cell, which are stored inline for up to two echoes.
+pub type EchoList = SmallVec<[Echo; 2]>;
+
+/// Points that can be split into echoes.
+pub trait EchoSource {
+    /// Returns the row index of the beam in frames.
+    fn row_idx(&self) -> usize;
+
+    /// Returns the echoes, excluding those without returns.
+    fn echoes(&self) -> EchoList;
+}
+
+impl EchoSource for SingleReturnPoint {
+    fn row_idx(&self) -> usize {
+        self.lidar_frame_entry.row_idx
+    }
+
+    fn echoes(&self) -> EchoList {
+        let mut echoes = EchoList::new();
+        if self.data.distance > Length::new::<meter>(0.0) {
+            echoes.push(Echo::new(&self.data, self.return_kind));
+        }
+        echoes
+    }
+}
+
+impl EchoSource for DualReturnPoint {
+    fn row_idx(&self) -> usize {
+        self.lidar_frame_entry.row_idx
+    }
+
+    /// The strongest echo comes first. The last echo is omitted if it has the same
+    /// distance as the strongest one, since the sensor reports the single return twice.
+    fn echoes(&self) -> EchoList {
+        let zero = Length::new::<meter>(0.0);
+        let mut echoes = EchoList::new();
+        if self.strongest_return_data.distance > zero {
+            echoes.push(Echo::new(
+                &self.strongest_return_data,
+                ReturnKind::Strongest,
+            ));
+        }
+        if self.last_return_data.distance > zero
+            && self.last_return_data.distance != self.strongest_return_data.distance
+        {
+            echoes.push(Echo::new(&self.last_return_data, ReturnKind::Last));
+        }
+        echoes
+    }
+}
+
+/// A frame where each beam and azimuth cell holds a variable number of echoes.
+///
+/// Unlike [PcdFrame], it does not collapse the returns of dual return mode, and cells
+/// without returns are empty. The cells are stored in column-major order as [PcdFrame].
+#[derive(Debug, Clone)]
+pub struct MultiEchoFrame {
+    pub height: usize,
+    pub width: usize,
+    pub cells: Vec<EchoList>,
+}
+
+impl MultiEchoFrame {
+    /// Builds the frame from points in firing order, where every `height` points form
+    /// a column, for example, the output of a point cloud converter.
+    ///
+    /// It fails if the number of points is not a multiple of `height`, or a point has
+    /// a row index out of the height.
+    pub fn from_points<P>(points: &[P], height: usize) -> Result<Self>
+    where
+        P: EchoSource,
+    {
+        ensure!(height > 0, "the height must be positive");
+        let width = points.len() / height;
+        ensure!(
+            width * height == points.len(),
+            "the number of points {} is not a multiple of height {}",
+            points.len(),
+            height
+        );
+        let mut cells = vec![EchoList::new(); points.len()];
+
+        for (idx, point) in points.iter().enumerate() {
+            let row_idx = point.row_idx();
+            ensure!(
+                row_idx < height,
+                "the row index {} is out of height {}",
+                row_idx,
+                height
+            );
+            let col_idx = idx / height;
+            cells[col_idx * height + row_idx] = point.echoes();
+        }
+
+        Ok(Self {
+            height,
+            width,
+            cells,
+        })
+    }
+
+    /// Builds the frame from a frame of points.
+    pub fn from_frame<P>(frame: &PcdFrame<P>) -> Result<Self>
+    where
+        P: Copy + EchoSource,
+    {
+        Self::from_points(&frame.data, frame.height)
+    }
+
+    /// Returns the echoes at the cell.
+    pub fn cell_at(&self, row_idx: usize, col_idx: usize) -> &[Echo] {
+        &self.cells[col_idx * self.height + row_idx]
+    }
+
+    /// Returns the total number of echoes.
+    pub fn num_echoes(&self) -> usize {
+        self.cells.iter().map(|echoes| echoes.len()).sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::velodyne::{
+        config::Config,
+        packet::ReturnMode,
+        pcd_converter::{PointCloudConverter, Vlp16_Dual_PcdConverter},
+        test_support::PacketBuilder,
+    };
+
+    #[test]
+    fn multi_echo_frame_test() -> Result<()> {
+        // the strongest and last blocks alternate in dual return mode
+        let mut packet = PacketBuilder::new()
+            .return_mode(ReturnMode::DualReturn)
+            .channels(1000, 10)
+            .build();
+        packet
+            .blocks
+            .iter_mut()
+            .enumerate()
+            .for_each(|(block_idx, block)| {
+                block.azimuth_count = (block_idx / 2 * 40) as u16;
+                if block_idx % 2 == 1 {
+                    // channels of even laser IDs see a second echo
+                    block
+                        .channels
+                        .iter_mut()
+                        .enumerate()
+                        .filter(|(channel_idx, _)| channel_idx % 2 == 0)
+                        .for_each(|(_, channel)| {
+                            channel.distance = 3000;
+                            channel.intensity = 5;
+                        });
+                }
+            });
+        // a beam without returns
+        packet.blocks[4].channels[1].distance = 0;
+        packet.blocks[5].channels[1].distance = 0;
+
+        let mut converter = Vlp16_Dual_PcdConverter::from_config(Config::vlp_16_dual_return());
+        let points = converter.convert(packet)?;
+        assert!(!points.is_empty());
+
+        let frame = MultiEchoFrame::from_points(&points, 16)?;
+        assert_eq!(frame.width * frame.height, points.len());
+
+        let mut num_empty = 0;
+        points.iter().enumerate().for_each(|(idx, point)| {
+            let echoes = frame.cell_at(point.lidar_frame_entry.row_idx, idx / 16);
+            if point.strongest_return_data.distance == Length::new::<meter>(0.0) {
+                assert!(echoes.is_empty());
+                num_empty += 1;
+            } else if point.laser_id % 2 == 0 {
+                assert_eq!(echoes.len(), 2);
+                assert_eq!(echoes[0].return_kind, ReturnKind::Strongest);
+                assert_eq!(echoes[1].return_kind, ReturnKind::Last);
+                assert!((echoes[0].distance.get::<meter>() - 2.0).abs() < 1e-9);
+                assert!((echoes[1].distance.get::<meter>() - 6.0).abs() < 1e-9);
+            } else {
+                assert_eq!(echoes.len(), 1);
+                assert_eq!(echoes[0].return_kind, ReturnKind::Strongest);
+                assert_eq!(echoes[0].intensity, 10);
+            }
+        });
+        assert_eq!(num_empty, 1);
+
+        // the frame of single return points has at most one echo per cell
+        let frame = PcdFrame {
+            height: 16,
+            width: frame.width,
+            data: points.clone(),
+        };
+        assert_eq!(
+            MultiEchoFrame::from_frame(&frame)?.num_echoes(),
+            points.len() * 2 - points.len() / 2 - num_empty
+        );
+        let frame = MultiEchoFrame::from_frame(&frame.nearest_only())?;
+        assert_eq!(frame.num_echoes(), points.len() - num_empty);
+
+        assert!(MultiEchoFrame::from_points(&points[1..], 16).is_err());
+
+        Ok(())
+    }
+}
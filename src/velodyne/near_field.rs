@@ -0,0 +1,107 @@
+//! Flags of near-field crosstalk returns.
+
+use super::point::{DualReturnPoint, PointData, SingleReturnPoint};
+use crate::common::*;
+
+/// Flags the returns that are likely the crosstalk at short range.
+///
+/// Unlike [SelfReturnMask](super::mask::SelfReturnMask) or a minimum range filter,
+/// the points are kept and only the [suspect](PointData::suspect) flag is set, so that
+/// consumers can decide to drop or inspect them. A return is suspect if its distance
+/// falls in `range` and its reflectivity is not above `max_intensity`, since the
+/// crosstalk is weak compared to real objects close to the sensor.
+#[derive(Debug, Clone)]
+pub struct NearFieldClassifier {
+    /// The range of distance where crosstalk appears.
+    pub range: Range<Length>,
+    /// The maximum reflectivity byte of a suspect return.
+    pub max_intensity: u8,
+}
+
+impl NearFieldClassifier {
+    pub fn new(range: Range<Length>, max_intensity: u8) -> Self {
+        Self {
+            range,
+            max_intensity,
+        }
+    }
+
+    /// Checks if a return is likely crosstalk. The points without returns are never suspect.
+    pub fn is_suspect(&self, data: &PointData) -> bool {
+        data.distance > Length::new::<meter>(0.0)
+            && self.range.contains(&data.distance)
+            && data.intensity <= self.max_intensity
+    }
+
+    /// Sets the suspect flag of each point. The flags that are already set are kept.
+    pub fn classify<P>(&self, points: &mut [P])
+    where
+        P: ClassifyNearField,
+    {
+        points
+            .iter_mut()
+            .for_each(|point| point.classify_near_field(self));
+    }
+}
+
+impl Default for NearFieldClassifier {
+    /// Flags returns within 1 meter with reflectivity up to 10.
+    fn default() -> Self {
+        Self::new(Length::new::<meter>(0.0)..Length::new::<meter>(1.0), 10)
+    }
+}
+
+/// Points of which the returns can be flagged by [NearFieldClassifier].
+pub trait ClassifyNearField {
+    fn classify_near_field(&mut self, classifier: &NearFieldClassifier);
+}
+
+impl ClassifyNearField for SingleReturnPoint {
+    fn classify_near_field(&mut self, classifier: &NearFieldClassifier) {
+        self.data.suspect |= classifier.is_suspect(&self.data);
+    }
+}
+
+/// Each return is flagged individually.
+impl ClassifyNearField for DualReturnPoint {
+    fn classify_near_field(&mut self, classifier: &NearFieldClassifier) {
+        self.strongest_return_data.suspect |= classifier.is_suspect(&self.strongest_return_data);
+        self.last_return_data.suspect |= classifier.is_suspect(&self.last_return_data);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::velodyne::test_support::PointBuilder;
+
+    fn new_point(distance: f64, intensity: u8) -> SingleReturnPoint {
+        PointBuilder::new()
+            .position([0.0, distance, 0.0])
+            .intensity(intensity)
+            .build()
+    }
+
+    #[test]
+    fn near_field_classifier_test() {
+        let classifier = NearFieldClassifier::default();
+        let mut points = vec![
+            // close and weak
+            new_point(0.4, 3),
+            // close but bright
+            new_point(0.4, 200),
+            // far and weak
+            new_point(5.0, 3),
+            // no return
+            new_point(0.0, 0),
+        ];
+        classifier.classify(&mut points);
+
+        let flags: Vec<_> = points.iter().map(|point| point.data.suspect).collect();
+        assert_eq!(flags, [true, false, false, false]);
+
+        // the points are kept
+        assert_eq!(points.len(), 4);
+        assert_eq!(points[0].data.distance, Length::new::<meter>(0.4));
+    }
+}
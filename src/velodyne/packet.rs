@@ -1,8 +1,18 @@
 //! Provides `C-packed` structs for Velodyne data packets.
 
-use super::consts::{AZIMUTH_COUNT_PER_REV, BLOCKS_PER_PACKET, CHANNELS_PER_BLOCK};
-
-use crate::common::*;
+use super::{
+    config::Config,
+    consts::{
+        AZIMUTH_COUNT_PER_REV, BLOCKS_PER_PACKET, CHANNELS_PER_BLOCK, CHANNEL_PERIOD,
+        FIRING_PERIOD, HDL_32E_CHANNEL_PERIOD, HDL_32E_FIRING_PERIOD,
+    },
+    marker::{DynamicModel, ModelMarker, ReturnTypeMarker},
+};
+
+use crate::{
+    common::*,
+    diagnostic::{Diagnostic, ValidationLevel},
+};
 
 pub use data_packet::*;
 pub use position_packet::*;
@@ -18,6 +28,20 @@ mod data_packet {
         Block32To63 = 0xddff,
     }
 
+    impl BlockIdentifier {
+        /// Returns the laser ID of the first channel in the block.
+        ///
+        /// HDL-64E packets mark upper laser blocks with 0xeeff and lower
+        /// laser blocks with 0xddff. The channels of a lower block map to
+        /// lasers 32 to 63.
+        pub fn laser_id_offset(&self) -> u32 {
+            match self {
+                Self::Block0To31 => 0,
+                Self::Block32To63 => CHANNELS_PER_BLOCK as u32,
+            }
+        }
+    }
+
     /// Represents the way the sensor measures the laser signal.
     #[repr(u8)]
     #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
@@ -27,19 +51,95 @@ mod data_packet {
         DualReturn = 0x39,
     }
 
-    /// Represents the hardware model.
+    /// Represents the hardware model by the product ID byte at the end of data packets,
+    /// which follows the factory byte tables of the Velodyne user manuals.
     #[repr(u8)]
     #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
     pub enum ProductID {
+        /// 0x21, reported by HDL-32E and early VLP-16 firmwares.
         HDL32E = 0x21,
+        /// 0x22, reported by VLP-16.
         VLP16 = 0x22,
+        /// 0x23, reported by Puck LITE.
         PuckLite = 0x23,
+        /// 0x24, reported by Puck Hi-Res.
         PuckHiRes = 0x24,
+        /// 0x28, reported by VLP-32C.
         VLP32C = 0x28,
+        /// 0x31, reported by Velarray.
         Velarray = 0x31,
+        /// 0xA1, reported by VLS-128.
         VLS128 = 0xa1,
     }
 
+    impl ProductID {
+        /// The product ID bytes of 16-laser sensors, which are all aliased to
+        /// [Vlp16](crate::velodyne::marker::Vlp16):
+        /// 0x22 for VLP-16, 0x23 for Puck LITE and 0x24 for Puck Hi-Res.
+        ///
+        /// Early VLP-16 firmwares report 0x21, which is the byte of HDL-32E, for example,
+        /// the capture `test_files/velodyne_vlp16.pcap`. It cannot be told apart by the byte
+        /// alone, see [DataPacket::model].
+        pub const VLP_16_IDS: [u8; 3] = [0x22, 0x23, 0x24];
+
+        /// Parses the product ID byte. It returns `None` if the byte is unknown.
+        pub fn from_u8(byte: u8) -> Option<Self> {
+            [
+                Self::HDL32E,
+                Self::VLP16,
+                Self::PuckLite,
+                Self::PuckHiRes,
+                Self::VLP32C,
+                Self::Velarray,
+                Self::VLS128,
+            ]
+            .iter()
+            .copied()
+            .find(|&id| id as u8 == byte)
+        }
+
+        /// Checks if the product is a 16-laser sensor.
+        pub fn is_vlp16(&self) -> bool {
+            Self::VLP_16_IDS.contains(&(*self as u8))
+        }
+
+        /// Returns the model of the product.
+        ///
+        /// It returns `None` for HDL-32E, whose byte is shared with early VLP-16 firmwares,
+        /// and for unsupported products. Use [DataPacket::model] to resolve the shared byte.
+        pub fn model(&self) -> Option<DynamicModel> {
+            let model = match self {
+                _ if self.is_vlp16() => DynamicModel::Vlp16,
+                Self::VLP32C => DynamicModel::Vlp32,
+                Self::VLS128 => DynamicModel::Custom(128),
+                _ => return None,
+            };
+            Some(model)
+        }
+    }
+
+    /// Represents the unit of raw distances in a packet.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    pub enum DistanceMode {
+        /// Raw distance is in 2 millimeters.
+        TwoMillimeter,
+        /// Raw distance is in 4 millimeters.
+        FourMillimeter,
+        /// Raw distance is in centimeters, which extends the maximum range.
+        Centimeter,
+    }
+
+    impl DistanceMode {
+        /// Returns the length of one raw distance unit.
+        pub fn distance_resolution(&self) -> Length {
+            match self {
+                Self::TwoMillimeter => Length::new::<millimeter>(2.0),
+                Self::FourMillimeter => Length::new::<millimeter>(4.0),
+                Self::Centimeter => Length::new::<millimeter>(10.0),
+            }
+        }
+    }
+
     /// Represents a point of measurement.
     #[repr(C, packed)]
     #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
@@ -75,6 +175,12 @@ mod data_packet {
         pub fn azimuth_angle(&self) -> Angle {
             Angle::new::<radian>(self.azimuth_angle_radian())
         }
+
+        /// Returns the laser ID of the first channel, decoded from the block identifier.
+        pub fn laser_id_offset(&self) -> u32 {
+            let block_identifier = self.block_identifier;
+            block_identifier.laser_id_offset()
+        }
     }
 
     /// Represents the data packet from Velodyne sensor.
@@ -108,11 +214,69 @@ mod data_packet {
             Ok(Self::from_buffer(*buffer))
         }
 
+        /// Construct packet from a frame read by [PcapFileReader](crate::pcap_file_reader::PcapFileReader).
+        ///
+        /// The `offset` is the size of protocol headers preceding the packet.
+        #[cfg(feature = "pcap-file")]
+        pub fn from_pcap_file_packet(data: &[u8], offset: usize) -> Result<Self> {
+            ensure!(
+                data.len() >= offset,
+                "The offset {} exceeds the frame size {}",
+                offset,
+                data.len(),
+            );
+            ensure!(
+                data.len() - offset == mem::size_of::<Self>(),
+                "Input frame is not a valid Velodyne Lidar packet",
+            );
+            Ok(*Self::from_slice(&data[offset..])?)
+        }
+
         /// Construct packet from binary buffer.
         pub fn from_buffer(buffer: [u8; mem::size_of::<Self>()]) -> Self {
             unsafe { mem::transmute::<_, Self>(buffer) }
         }
 
+        /// Serializes the packet into the little-endian wire layout, which is
+        /// the inverse of [from_buffer](DataPacket::from_buffer).
+        pub fn to_bytes(&self) -> [u8; mem::size_of::<Self>()] {
+            let mut bytes = [0u8; mem::size_of::<Self>()];
+            let mut offset = 0;
+            let mut write = |src: &[u8]| {
+                bytes[offset..(offset + src.len())].copy_from_slice(src);
+                offset += src.len();
+            };
+
+            let blocks = self.blocks;
+            for block in blocks.iter() {
+                write(&(block.block_identifier as u16).to_le_bytes());
+                write(&{ block.azimuth_count }.to_le_bytes());
+                for channel in block.channels.iter() {
+                    write(&{ channel.distance }.to_le_bytes());
+                    write(&[channel.intensity]);
+                }
+            }
+            write(&{ self.timestamp }.to_le_bytes());
+            write(&[self.return_mode as u8, self.product_id as u8]);
+
+            bytes
+        }
+
+        /// Returns the `(distance, intensity)` pairs of channels indexed by block and channel,
+        /// which are the raw values on the wire without geometric interpretation.
+        pub fn raw_array(&self) -> [[(u16, u8); CHANNELS_PER_BLOCK]; BLOCKS_PER_PACKET] {
+            let mut array = [[(0, 0); CHANNELS_PER_BLOCK]; BLOCKS_PER_PACKET];
+            // copy the packed fields out instead of taking references
+            let blocks = self.blocks;
+            izip!(array.iter_mut(), blocks.iter()).for_each(|(row, block)| {
+                let channels = block.channels;
+                izip!(row.iter_mut(), channels.iter()).for_each(|(entry, channel)| {
+                    *entry = ({ channel.distance }, channel.intensity);
+                });
+            });
+            array
+        }
+
         /// Construct packet from slice of bytes. Fail if the slice size is not correct.
         pub fn from_slice(buffer: &[u8]) -> Result<&Self> {
             ensure!(
@@ -125,6 +289,100 @@ mod data_packet {
             Ok(packet)
         }
 
+        /// Construct packet from a [Bytes](bytes::Bytes) buffer without copying. Fail if the buffer size is not correct.
+        ///
+        /// The packet has byte alignment, so it is always borrowed from the buffer.
+        #[cfg(feature = "bytes")]
+        pub fn from_bytes(buffer: &bytes::Bytes) -> Result<&Self> {
+            Self::from_slice(buffer.as_ref())
+        }
+
+        /// Construct packet from slice of bytes, and validates the factory bytes and block identifiers.
+        ///
+        /// In strict mode, it fails on any unrecognized byte. In lenient mode, it reports
+        /// the anomalies to `diagnostics` and returns `None` for the packet that cannot be decoded.
+        pub fn from_slice_validated(
+            buffer: &[u8],
+            level: ValidationLevel,
+            diagnostics: &mut Vec<Diagnostic>,
+        ) -> Result<Option<Self>> {
+            ensure!(
+                buffer.len() == mem::size_of::<Self>(),
+                "Requre the slice length to be {}, but get {}",
+                mem::size_of::<Self>(),
+                buffer.len(),
+            );
+
+            let mut is_valid = true;
+            let block_size = mem::size_of::<Block>();
+            for block_idx in 0..BLOCKS_PER_PACKET {
+                let offset = block_idx * block_size;
+                let value = u16::from_le_bytes([buffer[offset], buffer[offset + 1]]);
+                if value != BlockIdentifier::Block0To31 as u16
+                    && value != BlockIdentifier::Block32To63 as u16
+                {
+                    level.check(
+                        Some(diagnostics),
+                        Diagnostic::InvalidBlockIdentifier { block_idx, value },
+                    )?;
+                    is_valid = false;
+                }
+            }
+
+            let return_mode = buffer[buffer.len() - 2];
+            let product_id = buffer[buffer.len() - 1];
+            let return_mode_valid = [
+                ReturnMode::StrongestReturn,
+                ReturnMode::LastReturn,
+                ReturnMode::DualReturn,
+            ]
+            .iter()
+            .any(|&mode| mode as u8 == return_mode);
+            let product_id_valid = ProductID::from_u8(product_id).is_some();
+            if !return_mode_valid || !product_id_valid {
+                level.check(
+                    Some(diagnostics),
+                    Diagnostic::InvalidFactoryBytes {
+                        return_mode,
+                        product_id,
+                    },
+                )?;
+                is_valid = false;
+            }
+
+            if !is_valid {
+                return Ok(None);
+            }
+            Ok(Some(*Self::from_slice(buffer)?))
+        }
+
+        /// Construct packet from slice of bytes, ignoring the return mode byte in the packet.
+        ///
+        /// It is an escape hatch to recover captures with corrupted return mode bytes,
+        /// when the actual mode is known. The other bytes are validated as
+        /// [from_slice_validated](DataPacket::from_slice_validated) in strict mode.
+        pub fn from_slice_with_return_mode(buffer: &[u8], mode: ReturnMode) -> Result<Self> {
+            ensure!(
+                buffer.len() == mem::size_of::<Self>(),
+                "Requre the slice length to be {}, but get {}",
+                mem::size_of::<Self>(),
+                buffer.len(),
+            );
+            let mut bytes = [0u8; mem::size_of::<Self>()];
+            bytes.copy_from_slice(buffer);
+            bytes[bytes.len() - 2] = mode as u8;
+
+            let mut diagnostics = vec![];
+            Self::from_slice_validated(&bytes, ValidationLevel::Strict, &mut diagnostics)?
+                .ok_or_else(|| format_err!("the packet cannot be decoded"))
+        }
+
+        /// Overrides the return mode of the packet, bypassing the mode reported by the sensor.
+        pub fn with_forced_return_mode(mut self, mode: ReturnMode) -> Self {
+            self.return_mode = mode;
+            self
+        }
+
         /// Construct [NaiveDateTime](chrono::NaiveDateTime) from packet timestamp.
         pub fn datetime(&self) -> NaiveDateTime {
             let secs = self.timestamp / 1_000_000;
@@ -135,6 +393,179 @@ mod data_packet {
         pub fn time(&self) -> Time {
             Time::new::<microsecond>(self.timestamp as f64)
         }
+
+        /// Returns the factory default distance mode of the product, which is not detected
+        /// from the packet contents.
+        ///
+        /// The packet does not flag the centimeter mode, so it is never returned here. Sensors
+        /// configured so should be decoded with [Config::with_distance_mode](crate::velodyne::config::Config::with_distance_mode).
+        pub fn default_distance_mode(&self) -> DistanceMode {
+            match self.product_id {
+                ProductID::VLP32C | ProductID::VLS128 => DistanceMode::FourMillimeter,
+                _ => DistanceMode::TwoMillimeter,
+            }
+        }
+
+        /// Returns the model of the sensor, which extends [ProductID::model] to the byte 0x21.
+        ///
+        /// The byte 0x21 is shared by HDL-32E and early VLP-16 firmwares, and it is told apart
+        /// by the firing layout. A 16-laser block holds two consecutive firings of the same
+        /// lasers, so the distances of the channels i and i + 16 mostly agree, while the 32
+        /// channels of a HDL-32E block are distinct lasers. It returns `None` for HDL-32E and
+        /// unsupported products.
+        pub fn model(&self) -> Option<DynamicModel> {
+            match self.product_id {
+                ProductID::HDL32E if self.has_vlp_16_firing_layout() => Some(DynamicModel::Vlp16),
+                product_id => product_id.model(),
+            }
+        }
+
+        fn has_vlp_16_firing_layout(&self) -> bool {
+            let (num_pairs, num_matches) = self
+                .blocks
+                .iter()
+                .flat_map(|block| {
+                    let (lhs, rhs) = block.channels.split_at(CHANNELS_PER_BLOCK / 2);
+                    izip!(lhs, rhs)
+                })
+                .filter(|(lhs, rhs)| lhs.distance != 0 && rhs.distance != 0)
+                .fold((0, 0), |(num_pairs, num_matches), (lhs, rhs)| {
+                    // the distances of the same laser are within 5% of each other
+                    let diff = (lhs.distance as i32 - rhs.distance as i32).abs();
+                    let is_match = diff * 20 <= lhs.distance.max(rhs.distance) as i32;
+                    (num_pairs + 1, num_matches + is_match as usize)
+                });
+            num_matches * 2 > num_pairs
+        }
+
+        /// Returns the firing time of a block relative to the packet timestamp.
+        ///
+        /// The 16-laser sensors fire two sequences per block, while VLP-32C and HDL-32E fire
+        /// one sequence per block. In dual return mode, the paired blocks share the same firing
+        /// time. It returns `None` for products without a known timing table, that is, HDL-64E,
+        /// VLS-128 and Velarray. The byte 0x21 is resolved by [model](DataPacket::model).
+        pub fn block_time_offset(&self, block_idx: usize) -> Option<Time> {
+            assert!(block_idx < BLOCKS_PER_PACKET, "block index out of bound");
+
+            let firing_idx = match self.return_mode {
+                ReturnMode::DualReturn => block_idx / 2,
+                _ => block_idx,
+            };
+            let block_period = match self.product_id {
+                _ if matches!(self.model(), Some(DynamicModel::Vlp16)) => FIRING_PERIOD * 2.0,
+                ProductID::VLP32C => FIRING_PERIOD,
+                ProductID::HDL32E => HDL_32E_FIRING_PERIOD,
+                _ => return None,
+            };
+            Some(Time::new::<microsecond>(block_period * firing_idx as f64))
+        }
+
+        /// Returns the firing time of a channel relative to the packet timestamp.
+        ///
+        /// For the 16-laser sensors, the channels 16 to 31 belong to the second sequence of
+        /// the block. VLP-32C fires the channels in pairs, and HDL-32E fires them one by one.
+        /// It returns `None` if [block_time_offset](DataPacket::block_time_offset) does.
+        pub fn channel_time_offset(&self, block_idx: usize, channel_idx: usize) -> Option<Time> {
+            assert!(
+                channel_idx < CHANNELS_PER_BLOCK,
+                "channel index out of bound"
+            );
+
+            let offset = match self.product_id {
+                _ if matches!(self.model(), Some(DynamicModel::Vlp16)) => {
+                    FIRING_PERIOD * (channel_idx / 16) as f64
+                        + CHANNEL_PERIOD * (channel_idx % 16) as f64
+                }
+                ProductID::VLP32C => CHANNEL_PERIOD * (channel_idx / 2) as f64,
+                ProductID::HDL32E => HDL_32E_CHANNEL_PERIOD * channel_idx as f64,
+                _ => return None,
+            };
+            Some(self.block_time_offset(block_idx)? + Time::new::<microsecond>(offset))
+        }
+
+        /// Lists the returns of each laser firing.
+        ///
+        /// In dual return mode, the paired blocks are merged into one [Echoes] per firing.
+        /// Empty returns with zero distance are skipped, and identical returns are reported once.
+        pub fn echoes<Model, ReturnType>(
+            &self,
+            config: &Config<Model, ReturnType>,
+        ) -> impl Iterator<Item = Echoes> + '_
+        where
+            Model: ModelMarker,
+            ReturnType: ReturnTypeMarker,
+        {
+            let num_lasers = config.lasers.as_ref().len();
+            let distance_resolution = config.effective_distance_resolution();
+            let blocks_per_firing = match self.return_mode {
+                ReturnMode::DualReturn => 2,
+                ReturnMode::StrongestReturn | ReturnMode::LastReturn => 1,
+            };
+
+            self.blocks
+                .chunks(blocks_per_firing)
+                .flat_map(move |blocks| {
+                    let first_block = &blocks[0];
+                    let second_block = blocks.get(1);
+                    let laser_id_offset = if num_lasers > CHANNELS_PER_BLOCK {
+                        first_block.laser_id_offset()
+                    } else {
+                        0
+                    };
+
+                    (0..CHANNELS_PER_BLOCK).map(move |channel_idx| {
+                        let to_return = |channel: &Channel| {
+                            let Channel {
+                                distance,
+                                intensity,
+                            } = *channel;
+                            (distance != 0)
+                                .then(|| (distance_resolution * distance as f64, intensity))
+                        };
+                        let first_return = to_return(&first_block.channels[channel_idx]);
+                        let second_return = second_block
+                            .and_then(|block| to_return(&block.channels[channel_idx]))
+                            .filter(|echo| Some(*echo) != first_return);
+                        let returns = match first_return {
+                            Some(_) => [first_return, second_return],
+                            None => [second_return, None],
+                        };
+
+                        Echoes {
+                            laser_id: (laser_id_offset + channel_idx as u32) % num_lasers as u32,
+                            azimuth_count: first_block.azimuth_count,
+                            returns,
+                        }
+                    })
+                })
+        }
+    }
+
+    /// The returns of a laser firing, which has at most two returns in dual return mode.
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub struct Echoes {
+        pub laser_id: u32,
+        /// Encoder count of the block.
+        pub azimuth_count: u16,
+        /// The `(distance, intensity)` returns, where empty returns are placed last.
+        pub returns: [Option<(Length, u8)>; 2],
+    }
+
+    impl Echoes {
+        /// Returns the number of non-empty returns.
+        pub fn len(&self) -> usize {
+            self.returns.iter().flatten().count()
+        }
+
+        /// Checks if there is no return.
+        pub fn is_empty(&self) -> bool {
+            self.len() == 0
+        }
+
+        /// Iterates over non-empty returns.
+        pub fn iter(&self) -> impl Iterator<Item = (Length, u8)> + '_ {
+            self.returns.iter().flatten().copied()
+        }
     }
 }
 
@@ -178,6 +609,24 @@ mod position_packet {
             Ok(Self::from_buffer(*buffer))
         }
 
+        /// Construct packet from a frame read by [PcapFileReader](crate::pcap_file_reader::PcapFileReader).
+        ///
+        /// The `offset` is the size of protocol headers preceding the packet.
+        #[cfg(feature = "pcap-file")]
+        pub fn from_pcap_file_packet(data: &[u8], offset: usize) -> Result<Self> {
+            ensure!(
+                data.len() >= offset,
+                "The offset {} exceeds the frame size {}",
+                offset,
+                data.len(),
+            );
+            ensure!(
+                data.len() - offset == mem::size_of::<Self>(),
+                "Input frame is not a valid Velodyne Lidar packet",
+            );
+            Ok(*Self::from_slice(&data[offset..])?)
+        }
+
         /// Construct packet from binary buffer.
         pub fn from_buffer(buffer: [u8; mem::size_of::<Self>()]) -> Self {
             unsafe { mem::transmute::<_, Self>(buffer) }
@@ -195,6 +644,16 @@ mod position_packet {
             Ok(packet)
         }
 
+        /// Returns the temperature of top board.
+        pub fn top_board_temperature(&self) -> ThermodynamicTemperature {
+            ThermodynamicTemperature::new::<degree_celsius>(self.top_board_temperature as f64)
+        }
+
+        /// Returns the temperature of bottom board.
+        pub fn bottom_board_temperature(&self) -> ThermodynamicTemperature {
+            ThermodynamicTemperature::new::<degree_celsius>(self.bottom_board_temperature as f64)
+        }
+
         pub fn calibration_in_progress(&self) -> bool {
             self.adc_calibration_bitmask & 0b0001 != 0
         }
@@ -238,10 +697,239 @@ mod position_packet {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::velodyne::{
+        pcd_converter::{PointCloudConverter, Vlp16_Dual_PcdConverter},
+        test_support::PacketBuilder,
+    };
+
+    #[test]
+    fn time_offset_test() {
+        let new_packet = |product_id: ProductID, return_mode: ReturnMode| {
+            PacketBuilder::new()
+                .product_id(product_id)
+                .return_mode(return_mode)
+                .build()
+        };
+        let assert_offset = |offset: Time, expect: f64| {
+            assert!(
+                (offset.get::<microsecond>() - expect).abs() < 1e-6,
+                "expect {} but get {}",
+                expect,
+                offset.get::<microsecond>()
+            );
+        };
+
+        // VLP-16 single return
+        let packet = new_packet(ProductID::VLP16, ReturnMode::StrongestReturn);
+        assert_offset(packet.channel_time_offset(0, 0).unwrap(), 0.0);
+        assert_offset(packet.channel_time_offset(0, 15).unwrap(), 34.56);
+        assert_offset(packet.channel_time_offset(0, 16).unwrap(), 55.296);
+        assert_offset(packet.block_time_offset(1).unwrap(), 110.592);
+        assert_offset(packet.channel_time_offset(11, 31).unwrap(), 1306.368);
+
+        // VLP-16 dual return
+        let packet = new_packet(ProductID::VLP16, ReturnMode::DualReturn);
+        assert_offset(packet.channel_time_offset(0, 0).unwrap(), 0.0);
+        assert_offset(packet.block_time_offset(1).unwrap(), 0.0);
+        assert_offset(
+            packet.channel_time_offset(3, 17).unwrap(),
+            110.592 + 55.296 + 2.304,
+        );
+
+        // VLP-32C single and dual return
+        let packet = new_packet(ProductID::VLP32C, ReturnMode::LastReturn);
+        assert_offset(packet.channel_time_offset(0, 0).unwrap(), 0.0);
+        assert_offset(packet.channel_time_offset(0, 1).unwrap(), 0.0);
+        assert_offset(packet.channel_time_offset(0, 31).unwrap(), 34.56);
+        assert_offset(packet.block_time_offset(11).unwrap(), 608.256);
+        let packet = new_packet(ProductID::VLP32C, ReturnMode::DualReturn);
+        assert_offset(packet.channel_time_offset(11, 2).unwrap(), 276.48 + 2.304);
+
+        // HDL-32E fires a sequence every 46.08 microseconds
+        let packet = new_packet(ProductID::HDL32E, ReturnMode::StrongestReturn);
+        assert_offset(packet.channel_time_offset(0, 1).unwrap(), 1.152);
+        assert_offset(packet.block_time_offset(11).unwrap(), 506.88);
+        assert_offset(packet.channel_time_offset(1, 31).unwrap(), 46.08 + 35.712);
+
+        // the timing of other products is unknown
+        let packet = new_packet(ProductID::VLS128, ReturnMode::StrongestReturn);
+        assert!(packet.block_time_offset(0).is_none());
+        assert!(packet.channel_time_offset(0, 0).is_none());
+    }
 
     #[test]
     fn velodyne_packet_size_test() {
         assert_eq!(mem::size_of::<DataPacket>(), 1206);
         assert_eq!(mem::size_of::<PositionPacket>(), 512);
     }
+
+    #[test]
+    fn to_bytes_round_trip_test() -> Result<()> {
+        let mut packet = PacketBuilder::new()
+            .product_id(ProductID::VLP32C)
+            .return_mode(ReturnMode::DualReturn)
+            .timestamp(0x1234_5678)
+            .build();
+        packet
+            .blocks
+            .iter_mut()
+            .enumerate()
+            .for_each(|(idx, block)| {
+                block.block_identifier = if idx % 2 == 0 {
+                    BlockIdentifier::Block0To31
+                } else {
+                    BlockIdentifier::Block32To63
+                };
+                block.azimuth_count = 35990 + idx as u16;
+                block
+                    .channels
+                    .iter_mut()
+                    .enumerate()
+                    .for_each(|(channel_idx, channel)| {
+                        channel.distance = (idx * 1000 + channel_idx) as u16;
+                        channel.intensity = channel_idx as u8;
+                    });
+            });
+
+        let bytes = packet.to_bytes();
+        assert_eq!(&bytes[0..4], &[0xff, 0xee, 0x96, 0x8c]);
+        assert_eq!(*DataPacket::from_slice(&bytes)?, packet);
+
+        Ok(())
+    }
+
+    #[test]
+    fn block_identifier_test() {
+        let mut buffer = [0u8; mem::size_of::<Block>()];
+        buffer[0..2].copy_from_slice(&[0xff, 0xee]);
+        let upper = unsafe { mem::transmute::<[u8; mem::size_of::<Block>()], Block>(buffer) };
+        buffer[0..2].copy_from_slice(&[0xff, 0xdd]);
+        let lower = unsafe { mem::transmute::<[u8; mem::size_of::<Block>()], Block>(buffer) };
+
+        assert_eq!({ upper.block_identifier }, BlockIdentifier::Block0To31);
+        assert_eq!({ lower.block_identifier }, BlockIdentifier::Block32To63);
+        assert_eq!(upper.laser_id_offset(), 0);
+        assert_eq!(lower.laser_id_offset(), 32);
+    }
+
+    #[test]
+    fn dual_return_echoes_test() {
+        let config = Config::vlp_16_dual_return();
+        let mut packet = PacketBuilder::new()
+            .return_mode(ReturnMode::DualReturn)
+            .build();
+
+        // laser 0 has a single echo, and laser 1 has two echoes
+        packet.blocks[0].channels[0] = Channel {
+            distance: 500,
+            intensity: 10,
+        };
+        packet.blocks[1].channels[0] = Channel {
+            distance: 500,
+            intensity: 10,
+        };
+        packet.blocks[0].channels[1] = Channel {
+            distance: 1000,
+            intensity: 20,
+        };
+        packet.blocks[1].channels[1] = Channel {
+            distance: 750,
+            intensity: 40,
+        };
+
+        let echoes: Vec<_> = packet.echoes(&config).collect();
+        assert_eq!(echoes.len(), BLOCKS_PER_PACKET / 2 * CHANNELS_PER_BLOCK);
+
+        let length = |distance: f64| Length::new::<meter>(distance);
+        assert_eq!(echoes[0].laser_id, 0);
+        assert_eq!(echoes[0].len(), 1);
+        assert_eq!(echoes[0].returns, [Some((length(1.0), 10)), None]);
+        assert_eq!(echoes[1].laser_id, 1);
+        assert_eq!(echoes[1].len(), 2);
+        assert_eq!(
+            echoes[1].iter().collect::<Vec<_>>(),
+            vec![(length(2.0), 20), (length(1.5), 40)]
+        );
+        assert!(echoes[2..].iter().all(|echoes| echoes.is_empty()));
+        assert_eq!(echoes[16].laser_id, 0);
+    }
+
+    #[test]
+    #[cfg(feature = "bytes")]
+    fn from_bytes_test() -> Result<()> {
+        let packet = PacketBuilder::new()
+            .timestamp(42)
+            .channels(500, 7)
+            .azimuth_counts(12345, 0)
+            .build();
+        assert_eq!(mem::align_of::<DataPacket>(), 1);
+
+        // place the packet at an odd offset of the buffer
+        let mut buffer = vec![0u8];
+        buffer.extend_from_slice(&packet.to_bytes());
+        let bytes = bytes::Bytes::from(buffer).slice(1..);
+        let parsed = DataPacket::from_bytes(&bytes)?;
+        assert_eq!(*parsed, packet);
+        assert_eq!(parsed as *const DataPacket as *const u8, bytes.as_ptr());
+
+        assert!(DataPacket::from_bytes(&bytes.slice(1..)).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn forced_return_mode_test() -> Result<()> {
+        let packet = PacketBuilder::new()
+            .return_mode(ReturnMode::DualReturn)
+            .channels(500, 0)
+            .build();
+        let mut buffer = packet.to_bytes();
+        let len = buffer.len();
+        buffer[len - 2] = 0xff;
+
+        let mut diagnostics = vec![];
+        assert!(DataPacket::from_slice_validated(
+            &buffer,
+            ValidationLevel::Strict,
+            &mut diagnostics
+        )
+        .is_err());
+
+        let forced = DataPacket::from_slice_with_return_mode(&buffer, ReturnMode::DualReturn)?;
+        assert_eq!(forced, packet);
+        let mut converter = Vlp16_Dual_PcdConverter::from_config(Config::vlp_16_dual_return());
+        assert!(!converter.convert(forced)?.is_empty());
+
+        let forced = packet.with_forced_return_mode(ReturnMode::LastReturn);
+        assert_eq!(forced.return_mode, ReturnMode::LastReturn);
+
+        // the other bytes are still validated
+        buffer[len - 1] = 0xff;
+        assert!(DataPacket::from_slice_with_return_mode(&buffer, ReturnMode::DualReturn).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn raw_array_test() {
+        let mut packet = PacketBuilder::new().build();
+        packet.blocks[3].channels[17] = Channel {
+            distance: 0xabcd,
+            intensity: 200,
+        };
+
+        let array = packet.raw_array();
+        assert_eq!(array[3][17], (0xabcd, 200));
+        assert_eq!(array[3][16], (0, 0));
+        assert_eq!(array[2][17], (0, 0));
+
+        // agrees with the wire layout
+        let bytes = packet.to_bytes();
+        let offset = 3 * mem::size_of::<Block>() + 4 + 17 * mem::size_of::<Channel>();
+        assert_eq!(
+            u16::from_le_bytes([bytes[offset], bytes[offset + 1]]),
+            array[3][17].0
+        );
+        assert_eq!(bytes[offset + 2], array[3][17].1);
+    }
 }
@@ -1,18 +1,26 @@
-use super::impls;
+use super::{
+    cache::DirectionCache,
+    impls,
+    options::{post_process, ConverterOptions},
+};
 use crate::{
     common::*,
+    diagnostic::{Diagnostic, ValidationLevel},
     velodyne::{
         config::{
-            Config, Dynamic_Config, LaserParameter, Vlp16_Dual_Config, Vlp16_Dynamic_Config,
-            Vlp16_Last_Config, Vlp16_Strongest_Config, Vlp32_Dual_Config, Vlp32_Dynamic_Config,
-            Vlp32_Last_Config, Vlp32_Strongest_Config,
+            Config, Dynamic_Config, Hdl64_Last_Config, Hdl64_Strongest_Config, LaserParameter,
+            Vlp16_Dual_Config, Vlp16_Dynamic_Config, Vlp16_Last_Config, Vlp16_Strongest_Config,
+            Vlp32_Dual_Config, Vlp32_Dynamic_Config, Vlp32_Last_Config, Vlp32_Strongest_Config,
         },
+        intensity::IntensityCalibration,
         marker::{
-            DualReturn, DynamicModel, DynamicReturn, LastReturn, ModelMarker, ReturnTypeMarker,
-            StrongestReturn, Vlp16, Vlp32,
+            DualReturn, DynamicModel, DynamicReturn, Hdl64, LastReturn, ModelMarker,
+            ReturnTypeMarker, StrongestReturn, Vlp16, Vlp32,
         },
+        mask::SelfReturnMask,
         packet::{Block, DataPacket, ReturnMode},
-        point::{DualReturnPoint, DynamicReturnPoints, SingleReturnPoint},
+        point::{CylindricalPoint, DualReturnPoint, DynamicReturnPoints, SingleReturnPoint},
+        temperature::TemperatureCorrection,
     },
 };
 
@@ -37,6 +45,105 @@ mod definition {
         fn convert<P>(&mut self, packet: P) -> Result<Self::Output>
         where
             P: Borrow<DataPacket>;
+
+        /// Converts the blocks within the range of a packet, which serves low-latency previews
+        /// that need only the first few blocks.
+        ///
+        /// Unlike [convert](PointCloudConverter::convert), it neither depends on nor updates the
+        /// state of previous packets, and the azimuth of the last firing is extrapolated at constant
+        /// rotation speed. The corrections and the mask are applied as `convert`. In dual return
+        /// mode, the strongest and last return blocks of the same firing are decoded together.
+        ///
+        /// It fails if the range is empty or exceeds [BLOCKS_PER_PACKET](crate::velodyne::consts::BLOCKS_PER_PACKET).
+        fn convert_blocks<P>(&mut self, packet: P, range: Range<usize>) -> Result<Self::Output>
+        where
+            P: Borrow<DataPacket>;
+
+        /// Returns the laser parameters indexed by laser ID.
+        fn lasers(&self) -> &[LaserParameter];
+
+        /// Returns the options of the converter.
+        fn options(&self) -> &ConverterOptions;
+
+        /// Returns the options of the converter for modification.
+        fn options_mut(&mut self) -> &mut ConverterOptions;
+
+        /// Starts collecting [Diagnostic]s found in subsequent conversions.
+        fn enable_diagnostics(&mut self) {
+            self.options_mut().diagnostics.get_or_insert_with(Vec::new);
+        }
+
+        /// Takes out collected [Diagnostic]s. It returns an empty list if diagnostics is not enabled.
+        fn take_diagnostics(&mut self) -> Vec<Diagnostic> {
+            self.options_mut()
+                .diagnostics
+                .as_mut()
+                .map(mem::take)
+                .unwrap_or_default()
+        }
+
+        /// Drops the points inside the mask in subsequent conversions.
+        fn set_self_return_mask(&mut self, mask: SelfReturnMask) {
+            self.options_mut().self_return_mask = Some(mask);
+        }
+
+        /// Applies the temperature-based range correction in subsequent conversions.
+        fn set_temperature_correction(&mut self, correction: TemperatureCorrection) {
+            self.options_mut().temperature_correction = Some(correction);
+        }
+
+        /// Updates the sensor temperature, which is typically decoded from
+        /// [PositionPacket](crate::velodyne::packet::PositionPacket). It has no effect
+        /// if temperature correction is not set.
+        fn update_temperature(&mut self, temperature: ThermodynamicTemperature) {
+            if let Some(correction) = &mut self.options_mut().temperature_correction {
+                correction.temperature = temperature;
+            }
+        }
+
+        /// Normalizes the intensities by per-laser response curves in subsequent conversions.
+        ///
+        /// It fails if the number of entries does not match the number of lasers.
+        fn set_intensity_calibration(&mut self, calibration: IntensityCalibration) -> Result<()> {
+            calibration.validate(self.lasers().len())?;
+            self.options_mut().intensity_calibration = Some(calibration);
+            Ok(())
+        }
+
+        /// Caches the direction vectors of lasers over azimuth bins, so that subsequent
+        /// conversions skip computing trigonometric functions.
+        ///
+        /// See [DirectionCache] for the memory cost and precision.
+        fn enable_direction_cache(&mut self) {
+            let cache = DirectionCache::new(self.lasers());
+            self.options_mut().direction_cache = Some(cache);
+        }
+
+        /// Sets how packet anomalies are handled in subsequent conversions.
+        ///
+        /// In [ValidationLevel::Strict] mode, an azimuth count beyond a revolution is an error.
+        /// In [ValidationLevel::Lenient] mode, which is the default, it is reported as a
+        /// [Diagnostic] if diagnostics is enabled.
+        fn set_validation_level(&mut self, level: ValidationLevel) {
+            self.options_mut().validation_level = level;
+        }
+
+        /// Converts a packet into points in (range, azimuth bin, ring) cylindrical coordinates.
+        ///
+        /// It skips the Cartesian transform, and does not apply the mask nor the corrections.
+        /// Use [AzimuthTable](crate::velodyne::AzimuthTable) to convert points that are already Cartesian.
+        fn convert_cylindrical<P>(&self, packet: P) -> Vec<CylindricalPoint>
+        where
+            P: Borrow<DataPacket>,
+        {
+            let options = self.options();
+            impls::convert_cylindrical(
+                self.lasers(),
+                options.distance_resolution,
+                options.elevation_index.as_deref(),
+                packet.borrow(),
+            )
+        }
     }
 
     #[derive(Debug)]
@@ -74,32 +181,33 @@ mod definition {
         pub(crate) model: DynamicModel,
         pub(crate) return_type: DynamicReturn,
         pub(crate) lasers: Vec<LaserParameter>,
-        pub(crate) distance_resolution: Length,
         pub(crate) last_block: LastBlock,
+        pub(crate) last_timestamp: Option<Time>,
+        pub(crate) options: ConverterOptions,
     }
 
     #[derive(Debug)]
     #[allow(non_camel_case_types)]
     pub struct Vlp16_Strongest_PcdConverter {
         pub(crate) lasers: [LaserParameter; 16],
-        pub(crate) distance_resolution: Length,
         pub(crate) last_block: Option<(Time, Block)>,
+        pub(crate) options: ConverterOptions,
     }
 
     #[derive(Debug)]
     #[allow(non_camel_case_types)]
     pub struct Vlp16_Last_PcdConverter {
         pub(crate) lasers: [LaserParameter; 16],
-        pub(crate) distance_resolution: Length,
         pub(crate) last_block: Option<(Time, Block)>,
+        pub(crate) options: ConverterOptions,
     }
 
     #[derive(Debug)]
     #[allow(non_camel_case_types)]
     pub struct Vlp16_Dual_PcdConverter {
         pub(crate) lasers: [LaserParameter; 16],
-        pub(crate) distance_resolution: Length,
         pub(crate) last_block: Option<(Time, Block, Block)>,
+        pub(crate) options: ConverterOptions,
     }
 
     #[derive(Debug)]
@@ -107,32 +215,32 @@ mod definition {
     pub struct Vlp16_Dynamic_PcdConverter {
         pub(crate) return_type: DynamicReturn,
         pub(crate) lasers: [LaserParameter; 16],
-        pub(crate) distance_resolution: Length,
         pub(crate) last_block: LastBlock,
+        pub(crate) options: ConverterOptions,
     }
 
     #[derive(Debug)]
     #[allow(non_camel_case_types)]
     pub struct Vlp32_Strongest_PcdConverter {
         pub(crate) lasers: [LaserParameter; 32],
-        pub(crate) distance_resolution: Length,
         pub(crate) last_block: Option<(Time, Block)>,
+        pub(crate) options: ConverterOptions,
     }
 
     #[derive(Debug)]
     #[allow(non_camel_case_types)]
     pub struct Vlp32_Last_PcdConverter {
         pub(crate) lasers: [LaserParameter; 32],
-        pub(crate) distance_resolution: Length,
         pub(crate) last_block: Option<(Time, Block)>,
+        pub(crate) options: ConverterOptions,
     }
 
     #[derive(Debug)]
     #[allow(non_camel_case_types)]
     pub struct Vlp32_Dual_PcdConverter {
         pub(crate) lasers: [LaserParameter; 32],
-        pub(crate) distance_resolution: Length,
         pub(crate) last_block: Option<(Time, Block, Block)>,
+        pub(crate) options: ConverterOptions,
     }
 
     #[derive(Debug)]
@@ -140,8 +248,24 @@ mod definition {
     pub struct Vlp32_Dynamic_PcdConverter {
         pub(crate) return_type: DynamicReturn,
         pub(crate) lasers: [LaserParameter; 32],
-        pub(crate) distance_resolution: Length,
         pub(crate) last_block: LastBlock,
+        pub(crate) options: ConverterOptions,
+    }
+
+    #[derive(Debug)]
+    #[allow(non_camel_case_types)]
+    pub struct Hdl64_Strongest_PcdConverter {
+        pub(crate) lasers: [LaserParameter; 64],
+        pub(crate) last_timestamp: Option<Time>,
+        pub(crate) options: ConverterOptions,
+    }
+
+    #[derive(Debug)]
+    #[allow(non_camel_case_types)]
+    pub struct Hdl64_Last_PcdConverter {
+        pub(crate) lasers: [LaserParameter; 64],
+        pub(crate) last_timestamp: Option<Time>,
+        pub(crate) options: ConverterOptions,
     }
 }
 
@@ -152,16 +276,12 @@ mod converter_impls {
         type Output = Vec<SingleReturnPoint>;
 
         fn from_config(config: Vlp16_Strongest_Config) -> Self {
-            let Config {
-                lasers,
-                distance_resolution,
-                ..
-            } = config;
+            let options = ConverterOptions::from_config(&config);
 
             Self {
-                lasers,
-                distance_resolution,
+                lasers: config.lasers,
                 last_block: None,
+                options,
             }
         }
 
@@ -171,21 +291,56 @@ mod converter_impls {
         {
             let Self {
                 ref lasers,
-                distance_resolution,
                 ref mut last_block,
+                ref mut options,
             } = *self;
 
             let packet = packet.borrow();
+            impls::validate_encoder(packet, options)?;
             ensure!(
                 packet.return_mode == ReturnMode::StrongestReturn,
                 "return mode does not match"
             );
-            Ok(impls::convert_single_return_16_channel(
-                lasers,
-                distance_resolution,
-                last_block,
-                packet,
-            ))
+            let mut points =
+                impls::convert_single_return_16_channel(lasers, options, last_block, packet);
+            post_process(options, &mut points);
+
+            Ok(points)
+        }
+
+        fn convert_blocks<P>(&mut self, packet: P, range: Range<usize>) -> Result<Self::Output>
+        where
+            P: Borrow<DataPacket>,
+        {
+            let Self {
+                ref lasers,
+                ref mut options,
+                ..
+            } = *self;
+
+            let packet = packet.borrow();
+            impls::validate_encoder(packet, options)?;
+            ensure!(
+                packet.return_mode == ReturnMode::StrongestReturn,
+                "return mode does not match"
+            );
+            let mut points =
+                impls::convert_blocks_single_return_16_channel(lasers, options, packet, range)?;
+            post_process(options, &mut points);
+
+            Ok(points)
+        }
+
+        fn lasers(&self) -> &[LaserParameter] {
+            &self.lasers
+        }
+
+        fn options(&self) -> &ConverterOptions {
+            &self.options
+        }
+
+        fn options_mut(&mut self) -> &mut ConverterOptions {
+            &mut self.options
         }
     }
 
@@ -193,16 +348,12 @@ mod converter_impls {
         type Output = Vec<SingleReturnPoint>;
 
         fn from_config(config: Vlp16_Last_Config) -> Self {
-            let Config {
-                lasers,
-                distance_resolution,
-                ..
-            } = config;
+            let options = ConverterOptions::from_config(&config);
 
             Self {
-                lasers,
-                distance_resolution,
+                lasers: config.lasers,
                 last_block: None,
+                options,
             }
         }
 
@@ -212,21 +363,56 @@ mod converter_impls {
         {
             let Self {
                 ref lasers,
-                distance_resolution,
                 ref mut last_block,
+                ref mut options,
             } = *self;
 
             let packet = packet.borrow();
+            impls::validate_encoder(packet, options)?;
             ensure!(
                 packet.return_mode == ReturnMode::LastReturn,
                 "return mode does not match"
             );
-            Ok(impls::convert_single_return_16_channel(
-                lasers,
-                distance_resolution,
-                last_block,
-                packet,
-            ))
+            let mut points =
+                impls::convert_single_return_16_channel(lasers, options, last_block, packet);
+            post_process(options, &mut points);
+
+            Ok(points)
+        }
+
+        fn convert_blocks<P>(&mut self, packet: P, range: Range<usize>) -> Result<Self::Output>
+        where
+            P: Borrow<DataPacket>,
+        {
+            let Self {
+                ref lasers,
+                ref mut options,
+                ..
+            } = *self;
+
+            let packet = packet.borrow();
+            impls::validate_encoder(packet, options)?;
+            ensure!(
+                packet.return_mode == ReturnMode::LastReturn,
+                "return mode does not match"
+            );
+            let mut points =
+                impls::convert_blocks_single_return_16_channel(lasers, options, packet, range)?;
+            post_process(options, &mut points);
+
+            Ok(points)
+        }
+
+        fn lasers(&self) -> &[LaserParameter] {
+            &self.lasers
+        }
+
+        fn options(&self) -> &ConverterOptions {
+            &self.options
+        }
+
+        fn options_mut(&mut self) -> &mut ConverterOptions {
+            &mut self.options
         }
     }
 
@@ -234,16 +420,12 @@ mod converter_impls {
         type Output = Vec<DualReturnPoint>;
 
         fn from_config(config: Vlp16_Dual_Config) -> Self {
-            let Config {
-                lasers,
-                distance_resolution,
-                ..
-            } = config;
+            let options = ConverterOptions::from_config(&config);
 
             Self {
-                lasers,
-                distance_resolution,
+                lasers: config.lasers,
                 last_block: None,
+                options,
             }
         }
 
@@ -253,21 +435,56 @@ mod converter_impls {
         {
             let Self {
                 ref lasers,
-                distance_resolution,
                 ref mut last_block,
+                ref mut options,
             } = *self;
 
             let packet = packet.borrow();
+            impls::validate_encoder(packet, options)?;
             ensure!(
                 packet.return_mode == ReturnMode::DualReturn,
                 "return mode does not match"
             );
-            Ok(impls::convert_dual_return_16_channel(
-                lasers,
-                distance_resolution,
-                last_block,
-                packet,
-            ))
+            let mut points =
+                impls::convert_dual_return_16_channel(lasers, options, last_block, packet);
+            post_process(options, &mut points);
+
+            Ok(points)
+        }
+
+        fn convert_blocks<P>(&mut self, packet: P, range: Range<usize>) -> Result<Self::Output>
+        where
+            P: Borrow<DataPacket>,
+        {
+            let Self {
+                ref lasers,
+                ref mut options,
+                ..
+            } = *self;
+
+            let packet = packet.borrow();
+            impls::validate_encoder(packet, options)?;
+            ensure!(
+                packet.return_mode == ReturnMode::DualReturn,
+                "return mode does not match"
+            );
+            let mut points =
+                impls::convert_blocks_dual_return_16_channel(lasers, options, packet, range)?;
+            post_process(options, &mut points);
+
+            Ok(points)
+        }
+
+        fn lasers(&self) -> &[LaserParameter] {
+            &self.lasers
+        }
+
+        fn options(&self) -> &ConverterOptions {
+            &self.options
+        }
+
+        fn options_mut(&mut self) -> &mut ConverterOptions {
+            &mut self.options
         }
     }
 
@@ -275,18 +492,13 @@ mod converter_impls {
         type Output = DynamicReturnPoints;
 
         fn from_config(config: Vlp16_Dynamic_Config) -> Self {
-            let Config {
-                lasers,
-                return_type,
-                distance_resolution,
-                ..
-            } = config;
+            let options = ConverterOptions::from_config(&config);
 
             Self {
-                lasers,
-                return_type,
-                distance_resolution,
-                last_block: LastBlock::new(return_type),
+                lasers: config.lasers,
+                return_type: config.return_type,
+                last_block: LastBlock::new(config.return_type),
+                options,
             }
         }
 
@@ -297,17 +509,17 @@ mod converter_impls {
             let Self {
                 return_type,
                 ref lasers,
-                distance_resolution,
                 ref mut last_block,
+                ref mut options,
             } = *self;
 
             let packet = packet.borrow();
-
-            let points: DynamicReturnPoints = match return_type {
+            impls::validate_encoder(packet, options)?;
+            let mut points: DynamicReturnPoints = match return_type {
                 DynamicReturn::LastReturn | DynamicReturn::StrongestReturn => {
                     impls::convert_single_return_16_channel(
                         lasers,
-                        distance_resolution,
+                        options,
                         last_block.single(),
                         packet,
                     )
@@ -315,31 +527,68 @@ mod converter_impls {
                 }
                 DynamicReturn::DualReturn => impls::convert_dual_return_16_channel(
                     lasers,
-                    distance_resolution,
+                    options,
                     last_block.dual(),
                     packet,
                 )
                 .into(),
             };
+            post_process(options, &mut points);
+
+            Ok(points)
+        }
+
+        fn convert_blocks<P>(&mut self, packet: P, range: Range<usize>) -> Result<Self::Output>
+        where
+            P: Borrow<DataPacket>,
+        {
+            let Self {
+                return_type,
+                ref lasers,
+                ref mut options,
+                ..
+            } = *self;
+
+            let packet = packet.borrow();
+            impls::validate_encoder(packet, options)?;
+            let mut points: DynamicReturnPoints = match return_type {
+                DynamicReturn::LastReturn | DynamicReturn::StrongestReturn => {
+                    impls::convert_blocks_single_return_16_channel(lasers, options, packet, range)?
+                        .into()
+                }
+                DynamicReturn::DualReturn => {
+                    impls::convert_blocks_dual_return_16_channel(lasers, options, packet, range)?
+                        .into()
+                }
+            };
+            post_process(options, &mut points);
 
             Ok(points)
         }
+
+        fn lasers(&self) -> &[LaserParameter] {
+            &self.lasers
+        }
+
+        fn options(&self) -> &ConverterOptions {
+            &self.options
+        }
+
+        fn options_mut(&mut self) -> &mut ConverterOptions {
+            &mut self.options
+        }
     }
 
     impl PointCloudConverter<Vlp32, StrongestReturn> for Vlp32_Strongest_PcdConverter {
         type Output = Vec<SingleReturnPoint>;
 
         fn from_config(config: Vlp32_Strongest_Config) -> Self {
-            let Config {
-                lasers,
-                distance_resolution,
-                ..
-            } = config;
+            let options = ConverterOptions::from_config(&config);
 
             Self {
-                lasers,
-                distance_resolution,
+                lasers: config.lasers,
                 last_block: None,
+                options,
             }
         }
 
@@ -349,21 +598,56 @@ mod converter_impls {
         {
             let Self {
                 ref lasers,
-                distance_resolution,
                 ref mut last_block,
+                ref mut options,
             } = *self;
 
             let packet = packet.borrow();
+            impls::validate_encoder(packet, options)?;
             ensure!(
                 packet.return_mode == ReturnMode::StrongestReturn,
                 "return mode does not match"
             );
-            Ok(impls::convert_single_return_32_channel(
-                lasers,
-                distance_resolution,
-                last_block,
-                packet,
-            ))
+            let mut points =
+                impls::convert_single_return_32_channel(lasers, options, last_block, packet);
+            post_process(options, &mut points);
+
+            Ok(points)
+        }
+
+        fn convert_blocks<P>(&mut self, packet: P, range: Range<usize>) -> Result<Self::Output>
+        where
+            P: Borrow<DataPacket>,
+        {
+            let Self {
+                ref lasers,
+                ref mut options,
+                ..
+            } = *self;
+
+            let packet = packet.borrow();
+            impls::validate_encoder(packet, options)?;
+            ensure!(
+                packet.return_mode == ReturnMode::StrongestReturn,
+                "return mode does not match"
+            );
+            let mut points =
+                impls::convert_blocks_single_return_32_channel(lasers, options, packet, range)?;
+            post_process(options, &mut points);
+
+            Ok(points)
+        }
+
+        fn lasers(&self) -> &[LaserParameter] {
+            &self.lasers
+        }
+
+        fn options(&self) -> &ConverterOptions {
+            &self.options
+        }
+
+        fn options_mut(&mut self) -> &mut ConverterOptions {
+            &mut self.options
         }
     }
 
@@ -371,16 +655,12 @@ mod converter_impls {
         type Output = Vec<SingleReturnPoint>;
 
         fn from_config(config: Vlp32_Last_Config) -> Self {
-            let Config {
-                lasers,
-                distance_resolution,
-                ..
-            } = config;
+            let options = ConverterOptions::from_config(&config);
 
             Self {
-                lasers,
-                distance_resolution,
+                lasers: config.lasers,
                 last_block: None,
+                options,
             }
         }
 
@@ -390,21 +670,56 @@ mod converter_impls {
         {
             let Self {
                 ref lasers,
-                distance_resolution,
                 ref mut last_block,
+                ref mut options,
             } = *self;
 
             let packet = packet.borrow();
+            impls::validate_encoder(packet, options)?;
             ensure!(
                 packet.return_mode == ReturnMode::LastReturn,
                 "return mode does not match"
             );
-            Ok(impls::convert_single_return_32_channel(
-                lasers,
-                distance_resolution,
-                last_block,
-                packet,
-            ))
+            let mut points =
+                impls::convert_single_return_32_channel(lasers, options, last_block, packet);
+            post_process(options, &mut points);
+
+            Ok(points)
+        }
+
+        fn convert_blocks<P>(&mut self, packet: P, range: Range<usize>) -> Result<Self::Output>
+        where
+            P: Borrow<DataPacket>,
+        {
+            let Self {
+                ref lasers,
+                ref mut options,
+                ..
+            } = *self;
+
+            let packet = packet.borrow();
+            impls::validate_encoder(packet, options)?;
+            ensure!(
+                packet.return_mode == ReturnMode::LastReturn,
+                "return mode does not match"
+            );
+            let mut points =
+                impls::convert_blocks_single_return_32_channel(lasers, options, packet, range)?;
+            post_process(options, &mut points);
+
+            Ok(points)
+        }
+
+        fn lasers(&self) -> &[LaserParameter] {
+            &self.lasers
+        }
+
+        fn options(&self) -> &ConverterOptions {
+            &self.options
+        }
+
+        fn options_mut(&mut self) -> &mut ConverterOptions {
+            &mut self.options
         }
     }
 
@@ -412,16 +727,12 @@ mod converter_impls {
         type Output = Vec<DualReturnPoint>;
 
         fn from_config(config: Vlp32_Dual_Config) -> Self {
-            let Config {
-                lasers,
-                distance_resolution,
-                ..
-            } = config;
+            let options = ConverterOptions::from_config(&config);
 
             Self {
-                lasers,
-                distance_resolution,
+                lasers: config.lasers,
                 last_block: None,
+                options,
             }
         }
 
@@ -431,21 +742,56 @@ mod converter_impls {
         {
             let Self {
                 ref lasers,
-                distance_resolution,
                 ref mut last_block,
+                ref mut options,
             } = *self;
 
             let packet = packet.borrow();
+            impls::validate_encoder(packet, options)?;
             ensure!(
                 packet.return_mode == ReturnMode::DualReturn,
                 "return mode does not match"
             );
-            Ok(impls::convert_dual_return_32_channel(
-                lasers,
-                distance_resolution,
-                last_block,
-                packet,
-            ))
+            let mut points =
+                impls::convert_dual_return_32_channel(lasers, options, last_block, packet);
+            post_process(options, &mut points);
+
+            Ok(points)
+        }
+
+        fn convert_blocks<P>(&mut self, packet: P, range: Range<usize>) -> Result<Self::Output>
+        where
+            P: Borrow<DataPacket>,
+        {
+            let Self {
+                ref lasers,
+                ref mut options,
+                ..
+            } = *self;
+
+            let packet = packet.borrow();
+            impls::validate_encoder(packet, options)?;
+            ensure!(
+                packet.return_mode == ReturnMode::DualReturn,
+                "return mode does not match"
+            );
+            let mut points =
+                impls::convert_blocks_dual_return_32_channel(lasers, options, packet, range)?;
+            post_process(options, &mut points);
+
+            Ok(points)
+        }
+
+        fn lasers(&self) -> &[LaserParameter] {
+            &self.lasers
+        }
+
+        fn options(&self) -> &ConverterOptions {
+            &self.options
+        }
+
+        fn options_mut(&mut self) -> &mut ConverterOptions {
+            &mut self.options
         }
     }
 
@@ -453,18 +799,13 @@ mod converter_impls {
         type Output = DynamicReturnPoints;
 
         fn from_config(config: Vlp32_Dynamic_Config) -> Self {
-            let Config {
-                lasers,
-                return_type,
-                distance_resolution,
-                ..
-            } = config;
+            let options = ConverterOptions::from_config(&config);
 
             Self {
-                lasers,
-                return_type,
-                distance_resolution,
-                last_block: LastBlock::new(return_type),
+                lasers: config.lasers,
+                return_type: config.return_type,
+                last_block: LastBlock::new(config.return_type),
+                options,
             }
         }
 
@@ -475,17 +816,17 @@ mod converter_impls {
             let Self {
                 return_type,
                 ref lasers,
-                distance_resolution,
                 ref mut last_block,
+                ref mut options,
             } = *self;
 
             let packet = packet.borrow();
-
-            let points: DynamicReturnPoints = match return_type {
+            impls::validate_encoder(packet, options)?;
+            let mut points: DynamicReturnPoints = match return_type {
                 DynamicReturn::LastReturn | DynamicReturn::StrongestReturn => {
                     impls::convert_single_return_32_channel(
                         lasers,
-                        distance_resolution,
+                        options,
                         last_block.single(),
                         packet,
                     )
@@ -493,35 +834,221 @@ mod converter_impls {
                 }
                 DynamicReturn::DualReturn => impls::convert_dual_return_32_channel(
                     lasers,
-                    distance_resolution,
+                    options,
                     last_block.dual(),
                     packet,
                 )
                 .into(),
             };
+            post_process(options, &mut points);
+
+            Ok(points)
+        }
+
+        fn convert_blocks<P>(&mut self, packet: P, range: Range<usize>) -> Result<Self::Output>
+        where
+            P: Borrow<DataPacket>,
+        {
+            let Self {
+                return_type,
+                ref lasers,
+                ref mut options,
+                ..
+            } = *self;
+
+            let packet = packet.borrow();
+            impls::validate_encoder(packet, options)?;
+            let mut points: DynamicReturnPoints = match return_type {
+                DynamicReturn::LastReturn | DynamicReturn::StrongestReturn => {
+                    impls::convert_blocks_single_return_32_channel(lasers, options, packet, range)?
+                        .into()
+                }
+                DynamicReturn::DualReturn => {
+                    impls::convert_blocks_dual_return_32_channel(lasers, options, packet, range)?
+                        .into()
+                }
+            };
+            post_process(options, &mut points);
+
+            Ok(points)
+        }
+
+        fn lasers(&self) -> &[LaserParameter] {
+            &self.lasers
+        }
+
+        fn options(&self) -> &ConverterOptions {
+            &self.options
+        }
+
+        fn options_mut(&mut self) -> &mut ConverterOptions {
+            &mut self.options
+        }
+    }
+
+    impl PointCloudConverter<Hdl64, StrongestReturn> for Hdl64_Strongest_PcdConverter {
+        type Output = Vec<SingleReturnPoint>;
+
+        fn from_config(config: Hdl64_Strongest_Config) -> Self {
+            let options = ConverterOptions::from_config(&config);
+
+            Self {
+                lasers: config.lasers,
+                last_timestamp: None,
+                options,
+            }
+        }
+
+        fn convert<P>(&mut self, packet: P) -> Result<Self::Output>
+        where
+            P: Borrow<DataPacket>,
+        {
+            let Self {
+                ref lasers,
+                ref mut last_timestamp,
+                ref mut options,
+            } = *self;
+
+            let packet = packet.borrow();
+            impls::validate_encoder(packet, options)?;
+            ensure!(
+                packet.return_mode == ReturnMode::StrongestReturn,
+                "return mode does not match"
+            );
+            let mut points =
+                impls::convert_single_return_64_channel(lasers, options, last_timestamp, packet);
+            post_process(options, &mut points);
 
             Ok(points)
         }
+
+        fn convert_blocks<P>(&mut self, packet: P, range: Range<usize>) -> Result<Self::Output>
+        where
+            P: Borrow<DataPacket>,
+        {
+            let Self {
+                ref lasers,
+                ref mut options,
+                ..
+            } = *self;
+
+            let packet = packet.borrow();
+            impls::validate_encoder(packet, options)?;
+            ensure!(
+                packet.return_mode == ReturnMode::StrongestReturn,
+                "return mode does not match"
+            );
+            let mut points =
+                impls::convert_blocks_single_return_64_channel(lasers, options, packet, range)?;
+            post_process(options, &mut points);
+
+            Ok(points)
+        }
+
+        fn lasers(&self) -> &[LaserParameter] {
+            &self.lasers
+        }
+
+        fn options(&self) -> &ConverterOptions {
+            &self.options
+        }
+
+        fn options_mut(&mut self) -> &mut ConverterOptions {
+            &mut self.options
+        }
+    }
+
+    impl PointCloudConverter<Hdl64, LastReturn> for Hdl64_Last_PcdConverter {
+        type Output = Vec<SingleReturnPoint>;
+
+        fn from_config(config: Hdl64_Last_Config) -> Self {
+            let options = ConverterOptions::from_config(&config);
+
+            Self {
+                lasers: config.lasers,
+                last_timestamp: None,
+                options,
+            }
+        }
+
+        fn convert<P>(&mut self, packet: P) -> Result<Self::Output>
+        where
+            P: Borrow<DataPacket>,
+        {
+            let Self {
+                ref lasers,
+                ref mut last_timestamp,
+                ref mut options,
+            } = *self;
+
+            let packet = packet.borrow();
+            impls::validate_encoder(packet, options)?;
+            ensure!(
+                packet.return_mode == ReturnMode::LastReturn,
+                "return mode does not match"
+            );
+            let mut points =
+                impls::convert_single_return_64_channel(lasers, options, last_timestamp, packet);
+            post_process(options, &mut points);
+
+            Ok(points)
+        }
+
+        fn convert_blocks<P>(&mut self, packet: P, range: Range<usize>) -> Result<Self::Output>
+        where
+            P: Borrow<DataPacket>,
+        {
+            let Self {
+                ref lasers,
+                ref mut options,
+                ..
+            } = *self;
+
+            let packet = packet.borrow();
+            impls::validate_encoder(packet, options)?;
+            ensure!(
+                packet.return_mode == ReturnMode::LastReturn,
+                "return mode does not match"
+            );
+            let mut points =
+                impls::convert_blocks_single_return_64_channel(lasers, options, packet, range)?;
+            post_process(options, &mut points);
+
+            Ok(points)
+        }
+
+        fn lasers(&self) -> &[LaserParameter] {
+            &self.lasers
+        }
+
+        fn options(&self) -> &ConverterOptions {
+            &self.options
+        }
+
+        fn options_mut(&mut self) -> &mut ConverterOptions {
+            &mut self.options
+        }
     }
 
     impl PointCloudConverter<DynamicModel, DynamicReturn> for Dynamic_PcdConverter {
         type Output = DynamicReturnPoints;
 
         fn from_config(config: Dynamic_Config) -> Self {
+            let options = ConverterOptions::from_config(&config);
             let Config {
                 model,
                 lasers,
                 return_type,
-                distance_resolution,
                 ..
             } = config;
 
             Self {
                 model,
-                lasers,
                 return_type,
-                distance_resolution,
+                lasers,
                 last_block: LastBlock::new(return_type),
+                last_timestamp: None,
+                options,
             }
         }
 
@@ -533,19 +1060,21 @@ mod converter_impls {
                 model,
                 return_type,
                 ref lasers,
-                distance_resolution,
                 ref mut last_block,
+                ref mut last_timestamp,
+                ref mut options,
             } = *self;
 
             let packet = packet.borrow();
+            impls::validate_encoder(packet, options)?;
 
-            let points: DynamicReturnPoints = match (model, return_type) {
+            let mut points: DynamicReturnPoints = match (model, return_type) {
                 (DynamicModel::Vlp16, DynamicReturn::LastReturn)
                 | (DynamicModel::Vlp16, DynamicReturn::StrongestReturn) => {
                     let lasers: &[_; 16] = lasers.as_slice().try_into().unwrap();
                     impls::convert_single_return_16_channel(
                         lasers,
-                        distance_resolution,
+                        options,
                         last_block.single(),
                         packet,
                     )
@@ -555,7 +1084,7 @@ mod converter_impls {
                     let lasers: &[_; 16] = lasers.as_slice().try_into().unwrap();
                     impls::convert_dual_return_16_channel(
                         lasers,
-                        distance_resolution,
+                        options,
                         last_block.dual(),
                         packet,
                     )
@@ -566,7 +1095,7 @@ mod converter_impls {
                     let lasers: &[_; 32] = lasers.as_slice().try_into().unwrap();
                     impls::convert_single_return_32_channel(
                         lasers,
-                        distance_resolution,
+                        options,
                         last_block.single(),
                         packet,
                     )
@@ -576,15 +1105,987 @@ mod converter_impls {
                     let lasers: &[_; 32] = lasers.as_slice().try_into().unwrap();
                     impls::convert_dual_return_32_channel(
                         lasers,
-                        distance_resolution,
+                        options,
                         last_block.dual(),
                         packet,
                     )
                     .into()
                 }
+                (DynamicModel::Hdl64, DynamicReturn::LastReturn)
+                | (DynamicModel::Hdl64, DynamicReturn::StrongestReturn) => {
+                    let lasers: &[_; 64] = lasers.as_slice().try_into().unwrap();
+                    impls::convert_single_return_64_channel(lasers, options, last_timestamp, packet)
+                        .into()
+                }
+                (DynamicModel::Hdl64, DynamicReturn::DualReturn) => {
+                    bail!("dual return mode is not supported for HDL-64E")
+                }
+                (DynamicModel::Custom(_), _) => {
+                    bail!("custom models are not supported by the converter")
+                }
+            };
+            post_process(options, &mut points);
+
+            Ok(points)
+        }
+
+        fn convert_blocks<P>(&mut self, packet: P, range: Range<usize>) -> Result<Self::Output>
+        where
+            P: Borrow<DataPacket>,
+        {
+            let Self {
+                model,
+                return_type,
+                ref lasers,
+                ref mut options,
+                ..
+            } = *self;
+
+            let packet = packet.borrow();
+            impls::validate_encoder(packet, options)?;
+
+            let mut points: DynamicReturnPoints = match (model, return_type) {
+                (DynamicModel::Vlp16, DynamicReturn::LastReturn)
+                | (DynamicModel::Vlp16, DynamicReturn::StrongestReturn) => {
+                    let lasers: &[_; 16] = lasers.as_slice().try_into().unwrap();
+                    impls::convert_blocks_single_return_16_channel(lasers, options, packet, range)?
+                        .into()
+                }
+                (DynamicModel::Vlp16, DynamicReturn::DualReturn) => {
+                    let lasers: &[_; 16] = lasers.as_slice().try_into().unwrap();
+                    impls::convert_blocks_dual_return_16_channel(lasers, options, packet, range)?
+                        .into()
+                }
+                (DynamicModel::Vlp32, DynamicReturn::LastReturn)
+                | (DynamicModel::Vlp32, DynamicReturn::StrongestReturn) => {
+                    let lasers: &[_; 32] = lasers.as_slice().try_into().unwrap();
+                    impls::convert_blocks_single_return_32_channel(lasers, options, packet, range)?
+                        .into()
+                }
+                (DynamicModel::Vlp32, DynamicReturn::DualReturn) => {
+                    let lasers: &[_; 32] = lasers.as_slice().try_into().unwrap();
+                    impls::convert_blocks_dual_return_32_channel(lasers, options, packet, range)?
+                        .into()
+                }
+                (DynamicModel::Hdl64, DynamicReturn::LastReturn)
+                | (DynamicModel::Hdl64, DynamicReturn::StrongestReturn) => {
+                    let lasers: &[_; 64] = lasers.as_slice().try_into().unwrap();
+                    impls::convert_blocks_single_return_64_channel(lasers, options, packet, range)?
+                        .into()
+                }
+                (DynamicModel::Hdl64, DynamicReturn::DualReturn) => {
+                    bail!("dual return mode is not supported for HDL-64E")
+                }
+                (DynamicModel::Custom(_), _) => {
+                    bail!("custom models are not supported by the converter")
+                }
             };
+            post_process(options, &mut points);
 
             Ok(points)
         }
+
+        fn lasers(&self) -> &[LaserParameter] {
+            &self.lasers
+        }
+
+        fn options(&self) -> &ConverterOptions {
+            &self.options
+        }
+
+        fn options_mut(&mut self) -> &mut ConverterOptions {
+            &mut self.options
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::velodyne::{
+        config::ParamsConfig,
+        consts::{BLOCKS_PER_PACKET, CHANNELS_PER_BLOCK, CHANNEL_PERIOD, FIRING_PERIOD},
+        intensity::{FocalParameter, IntensityMode},
+        mask::MaskWindow,
+        packet::{BlockIdentifier, DistanceMode, PositionPacket, ProductID},
+        point::{ReturnKind, VelodynePoint},
+        test_support::PacketBuilder,
+    };
+
+    #[test]
+    fn hdl_64e_upper_lower_block_test() -> Result<()> {
+        let params = ParamsConfig::from_str(include_str!("../params/64e_utexas.yaml"))?;
+        let config = Config::hdl_64e_strongest_return(&params)?;
+        let lasers = config.lasers.clone();
+        let mut converter = Hdl64_Strongest_PcdConverter::from_config(config);
+
+        // upper and lower blocks alternate, and mark intensities by block type
+        let mut packet = PacketBuilder::new()
+            .product_id(ProductID::HDL32E)
+            .channels(500, 0)
+            .alternate_blocks()
+            .build();
+        packet
+            .blocks
+            .iter_mut()
+            .enumerate()
+            .for_each(|(idx, block)| {
+                let intensity = if idx % 2 == 0 { 1 } else { 2 };
+                block.azimuth_count = (idx / 2 * 20) as u16;
+                block.channels.iter_mut().for_each(|channel| {
+                    channel.intensity = intensity;
+                });
+            });
+
+        let points = converter.convert(packet)?;
+        assert_eq!(points.len(), BLOCKS_PER_PACKET * CHANNELS_PER_BLOCK);
+
+        for (idx, point) in points.iter().enumerate() {
+            let is_lower_block = idx / CHANNELS_PER_BLOCK % 2 == 1;
+            assert_eq!(point.laser_id >= 32, is_lower_block);
+            assert_eq!(point.data.intensity == 2, is_lower_block);
+
+            let LaserParameter {
+                elevation_angle,
+                vertical_offset,
+                ..
+            } = lasers[point.laser_id as usize];
+            let expect_z = point.data.distance * elevation_angle.sin()
+                + vertical_offset * elevation_angle.cos();
+            assert!((point.data.position[2] - expect_z).abs() < Length::new::<millimeter>(1e-6));
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn dropped_firing_diagnostic_test() -> Result<()> {
+        let config = Config::vlp_16_strongest_return();
+        let mut converter = Vlp16_Strongest_PcdConverter::from_config(config);
+        converter.enable_diagnostics();
+
+        let packet_period = FIRING_PERIOD * 2.0 * BLOCKS_PER_PACKET as f64;
+        let new_packet = |timestamp: f64| {
+            PacketBuilder::new()
+                .timestamp(timestamp.round() as u32)
+                .channels(500, 0)
+                .build()
+        };
+
+        // consecutive packets
+        converter.convert(new_packet(1000.0))?;
+        converter.convert(new_packet(1000.0 + packet_period))?;
+        assert!(converter.take_diagnostics().is_empty());
+
+        // drop one packet
+        converter.convert(new_packet(1000.0 + packet_period * 3.0))?;
+        let diagnostics = converter.take_diagnostics();
+        assert_eq!(diagnostics.len(), 1);
+        match diagnostics[0] {
+            Diagnostic::DroppedFiring { num_dropped, .. } => {
+                assert_eq!(num_dropped, BLOCKS_PER_PACKET * 2);
+            }
+            other => panic!("unexpected diagnostic {:?}", other),
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn centimeter_distance_mode_test() -> Result<()> {
+        let packet = PacketBuilder::new().channels(1234, 0).build();
+        assert_eq!(packet.default_distance_mode(), DistanceMode::TwoMillimeter);
+        let vlp_32c_packet = PacketBuilder::new().product_id(ProductID::VLP32C).build();
+        assert_eq!(
+            vlp_32c_packet.default_distance_mode(),
+            DistanceMode::FourMillimeter
+        );
+
+        let config = Config::vlp_16_strongest_return().with_distance_mode(DistanceMode::Centimeter);
+        assert_eq!(config.distance_mode, Some(DistanceMode::Centimeter));
+        assert_eq!(
+            config.distance_resolution,
+            Config::vlp_16_strongest_return().distance_resolution
+        );
+        let mut converter = Vlp16_Strongest_PcdConverter::from_config(config);
+        let points = converter.convert(packet)?;
+        assert!(!points.is_empty());
+        points.iter().for_each(|point| {
+            let distance = point.data.distance.get::<meter>();
+            assert!((distance - 12.34).abs() < 1e-9);
+        });
+
+        // the positions match the same range in the default mode
+        let mut reference_packet = packet;
+        reference_packet.blocks.iter_mut().for_each(|block| {
+            block
+                .channels
+                .iter_mut()
+                .for_each(|channel| channel.distance = 6170)
+        });
+        let mut converter =
+            Vlp16_Strongest_PcdConverter::from_config(Config::vlp_16_strongest_return());
+        let reference_points = converter.convert(reference_packet)?;
+        assert_eq!(points.len(), reference_points.len());
+        points
+            .iter()
+            .zip(reference_points.iter())
+            .for_each(|(point, reference)| {
+                izip!(point.data.position.iter(), reference.data.position.iter()).for_each(
+                    |(lhs, rhs)| assert!((lhs.get::<meter>() - rhs.get::<meter>()).abs() < 1e-9),
+                );
+            });
+
+        Ok(())
+    }
+
+    #[test]
+    fn temperature_correction_test() -> Result<()> {
+        let new_packet = |distance: u16| PacketBuilder::new().channels(distance, 0).build();
+        let mut position_packet: PositionPacket = unsafe { mem::zeroed() };
+        position_packet.top_board_temperature = 35;
+
+        // 10 degrees above reference shifts ranges by 20 mm, that is, 10 raw counts
+        let mut converter =
+            Vlp16_Strongest_PcdConverter::from_config(Config::vlp_16_strongest_return());
+        let expect_points = converter.convert(new_packet(510))?;
+
+        let mut converter =
+            Vlp16_Strongest_PcdConverter::from_config(Config::vlp_16_strongest_return());
+        converter.set_temperature_correction(TemperatureCorrection::new(
+            ThermodynamicTemperature::new::<degree_celsius>(25.0),
+            Length::new::<millimeter>(2.0),
+        ));
+        converter.update_temperature(position_packet.top_board_temperature());
+        let points = converter.convert(new_packet(500))?;
+
+        // the positions are computed from the corrected ranges
+        assert_eq!(points.len(), expect_points.len());
+        izip!(points.iter(), expect_points.iter()).for_each(|(point, expect)| {
+            let diff = point.data.distance - expect.data.distance;
+            assert!(diff.get::<millimeter>().abs() < 1e-9);
+            izip!(point.data.position.iter(), expect.data.position.iter()).for_each(
+                |(lhs, rhs)| {
+                    assert!((lhs.get::<meter>() - rhs.get::<meter>()).abs() < 1e-9);
+                },
+            );
+        });
+
+        Ok(())
+    }
+
+    #[test]
+    fn intensity_calibration_test() -> Result<()> {
+        let packet = PacketBuilder::new().channels(500, 100).build();
+
+        let mut converter =
+            Vlp16_Strongest_PcdConverter::from_config(Config::vlp_16_strongest_return());
+        assert!(converter
+            .set_intensity_calibration(IntensityCalibration {
+                per_ring: vec![(1.0, 0.0); 15],
+            })
+            .is_err());
+
+        // laser i has gain 1 + 0.1 i, and laser 15 saturates
+        let mut per_ring: Vec<_> = (0..16).map(|idx| (1.0 + 0.1 * idx as f64, -5.0)).collect();
+        per_ring[15] = (3.0, 0.0);
+        converter.set_intensity_calibration(IntensityCalibration { per_ring })?;
+        let points = converter.convert(packet)?;
+        assert!(!points.is_empty());
+        points.iter().for_each(|point| {
+            let expect = match point.laser_id {
+                15 => 255,
+                laser_id => 95 + 10 * laser_id as u8,
+            };
+            assert_eq!(point.data.intensity, expect);
+        });
+
+        Ok(())
+    }
+
+    #[test]
+    fn intensity_mode_test() -> Result<()> {
+        let packet = PacketBuilder::new().channels(500, 100).build();
+        let convert = |mode: IntensityMode| -> Result<Vec<SingleReturnPoint>> {
+            let config = Config::vlp_16_strongest_return().with_intensity_mode(mode)?;
+            Vlp16_Strongest_PcdConverter::from_config(config).convert(packet)
+        };
+
+        // raw mode is the default
+        let points = Vlp16_Strongest_PcdConverter::from_config(Config::vlp_16_strongest_return())
+            .convert(packet)?;
+        assert!(!points.is_empty());
+        points.iter().for_each(|point| {
+            assert_eq!(point.data.intensity, 100);
+            assert_eq!(point.data.intensity_value, 100.0);
+        });
+
+        let points = convert(IntensityMode::Raw)?;
+        points
+            .iter()
+            .for_each(|point| assert_eq!(point.data.intensity_value, 100.0));
+
+        let points = convert(IntensityMode::Normalized)?;
+        points.iter().for_each(|point| {
+            assert_eq!(point.data.intensity, 100);
+            assert!((point.data.intensity_value - 100.0 / 255.0).abs() < 1e-9);
+        });
+
+        // 1 meter range with focal distance at 13.1 meters
+        let focal_params = vec![
+            FocalParameter {
+                focal_distance: Length::new::<meter>(13.1),
+                focal_slope: 0.5,
+            };
+            16
+        ];
+        let points = convert(IntensityMode::Calibrated(focal_params))?;
+        let expect =
+            100.0 + 0.5 * (256.0 * 0.9f64.powi(2) - 256.0 * (1.0 - 1.0 / 131.07f64).powi(2)).abs();
+        points.iter().for_each(|point| {
+            assert_eq!(point.data.intensity, 100);
+            assert!((point.data.intensity_value - expect).abs() < 1e-9);
+        });
+
+        // calibrated mode requires parameters for all lasers
+        assert!(Config::vlp_16_strongest_return()
+            .with_intensity_mode(IntensityMode::Calibrated(vec![]))
+            .is_err());
+
+        let params = ParamsConfig::from_str(include_str!("../params/64e_utexas.yaml"))?;
+        assert!(matches!(
+            IntensityMode::calibrated(&params),
+            IntensityMode::Calibrated(focal_params) if focal_params.len() == 64
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn azimuth_zero_offset_test() -> Result<()> {
+        let mut packet = PacketBuilder::new().channels(500, 0).build();
+        packet
+            .blocks
+            .iter_mut()
+            .enumerate()
+            .for_each(|(idx, block)| block.azimuth_count = 35800 + 40 * idx as u16);
+
+        let config = Config::vlp_16_strongest_return();
+        let expect_points =
+            Vlp16_Strongest_PcdConverter::from_config(config.clone()).convert(packet)?;
+        let config = config.with_azimuth_zero_offset(Angle::new::<degree>(5.0));
+        let points = Vlp16_Strongest_PcdConverter::from_config(config).convert(packet)?;
+
+        // the cloud is rotated clockwise by 5 degrees viewed from above
+        let wrapped_diff = |lhs: Angle, rhs: Angle| (lhs - rhs).get::<degree>().rem_euclid(360.0);
+        assert!(!points.is_empty());
+        assert_eq!(points.len(), expect_points.len());
+        izip!(points.iter(), expect_points.iter()).for_each(|(point, expect)| {
+            let diff = wrapped_diff(
+                point.corrected_azimuth_angle,
+                expect.corrected_azimuth_angle,
+            );
+            assert!((diff - 5.0).abs() < 1e-9);
+            let diff = wrapped_diff(point.original_azimuth_angle, expect.original_azimuth_angle);
+            assert!((diff - 5.0).abs() < 1e-9);
+
+            let azimuth = |position: &[Length; 3]| {
+                let [x, y, _] = *position;
+                Angle::new::<radian>(x.get::<meter>().atan2(y.get::<meter>()))
+            };
+            let diff = wrapped_diff(
+                azimuth(&point.data.position),
+                azimuth(&expect.data.position),
+            );
+            assert!((diff - 5.0).abs() < 1e-6);
+            assert!(
+                (point.data.position[2] - expect.data.position[2])
+                    .get::<meter>()
+                    .abs()
+                    < 1e-9
+            );
+        });
+
+        Ok(())
+    }
+
+    #[test]
+    fn flip_elevation_sign_test() -> Result<()> {
+        let packet = PacketBuilder::new().channels(500, 0).build();
+
+        // remove the offsets so that the elevation of points equals that of lasers
+        let mut config = Config::vlp_16_strongest_return();
+        config.lasers.iter_mut().for_each(|laser| {
+            laser.vertical_offset = Length::new::<meter>(0.0);
+            laser.horizontal_offset = Length::new::<meter>(0.0);
+        });
+        let expect_points =
+            Vlp16_Strongest_PcdConverter::from_config(config.clone()).convert(packet)?;
+        let config = config.with_flip_elevation_sign(true);
+        let points = Vlp16_Strongest_PcdConverter::from_config(config.clone()).convert(packet)?;
+
+        let elevation = |position: &[Length; 3]| {
+            let [x, y, z] = *position;
+            let planar = x.get::<meter>().hypot(y.get::<meter>());
+            z.get::<meter>().atan2(planar).to_degrees()
+        };
+        assert!(!points.is_empty());
+        assert_eq!(points.len(), expect_points.len());
+        izip!(points.iter(), expect_points.iter()).for_each(|(point, expect)| {
+            assert_eq!(
+                point.lidar_frame_entry.row_idx,
+                expect.lidar_frame_entry.row_idx
+            );
+            let laser_elevation = config.lasers[point.laser_id as usize]
+                .elevation_angle
+                .get::<degree>();
+            assert!((elevation(&expect.data.position) - laser_elevation).abs() < 1e-6);
+            assert!((elevation(&point.data.position) + laser_elevation).abs() < 1e-6);
+        });
+
+        // the +15 degree beam is pointing downward
+        let top = points
+            .iter()
+            .find(|point| {
+                (config.lasers[point.laser_id as usize]
+                    .elevation_angle
+                    .get::<degree>()
+                    - 15.0)
+                    .abs()
+                    < 1e-9
+            })
+            .unwrap();
+        assert!((elevation(&top.data.position) + 15.0).abs() < 1e-6);
+
+        Ok(())
+    }
+
+    #[test]
+    fn range_quantum_test() -> Result<()> {
+        let new_packet = |distance: u16| PacketBuilder::new().channels(distance, 0).build();
+
+        // 5.04 m is snapped to 5.0 m
+        let config = Config::vlp_16_strongest_return();
+        let expect_points =
+            Vlp16_Strongest_PcdConverter::from_config(config.clone()).convert(new_packet(2500))?;
+        let config = config.with_range_quantum(Length::new::<meter>(0.1))?;
+        let points = Vlp16_Strongest_PcdConverter::from_config(config).convert(new_packet(2520))?;
+
+        assert!(!points.is_empty());
+        assert_eq!(points.len(), expect_points.len());
+        izip!(points.iter(), expect_points.iter()).for_each(|(point, expect)| {
+            assert!((point.data.distance.get::<meter>() - 5.0).abs() < 1e-9);
+            izip!(point.data.position.iter(), expect.data.position.iter()).for_each(
+                |(lhs, rhs)| {
+                    assert!((lhs.get::<meter>() - rhs.get::<meter>()).abs() < 1e-9);
+                },
+            );
+        });
+
+        // the ranges are quantized after the temperature correction
+        let quantum = Length::new::<meter>(0.1);
+        let config = Config::vlp_16_strongest_return().with_range_quantum(quantum)?;
+        let mut converter = Vlp16_Strongest_PcdConverter::from_config(config);
+        converter.set_temperature_correction(TemperatureCorrection::new(
+            ThermodynamicTemperature::new::<degree_celsius>(25.0),
+            Length::new::<millimeter>(2.0),
+        ));
+        converter.update_temperature(ThermodynamicTemperature::new::<degree_celsius>(50.0));
+        let points = converter.convert(new_packet(2495))?;
+        assert!(!points.is_empty());
+        points.iter().for_each(|point| {
+            let multiple = (point.data.distance / quantum).value;
+            assert!((multiple - multiple.round()).abs() < 1e-9);
+            assert!((point.data.distance.get::<meter>() - 5.0).abs() < 1e-9);
+        });
+
+        // the quantum must be positive
+        assert!(Config::vlp_16_strongest_return()
+            .with_range_quantum(Length::new::<meter>(0.0))
+            .is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn convert_cylindrical_test() -> Result<()> {
+        let mut packet = PacketBuilder::new().channels(500, 7).build();
+        packet.blocks[0].azimuth_count = 12345;
+
+        let mut converter =
+            Vlp16_Strongest_PcdConverter::from_config(Config::vlp_16_strongest_return());
+        let points = converter.convert_cylindrical(packet);
+        assert_eq!(points.len(), BLOCKS_PER_PACKET * CHANNELS_PER_BLOCK);
+
+        // the encoder count of 123.45 degrees is the azimuth bin of the first block
+        let first_block = &points[..CHANNELS_PER_BLOCK];
+        assert!(first_block.iter().all(|point| point.azimuth_bin == 12345));
+        assert!(points[CHANNELS_PER_BLOCK..]
+            .iter()
+            .all(|point| point.azimuth_bin == 0));
+
+        assert!(points.iter().all(|point| {
+            (point.range.get::<meter>() - 1.0).abs() < 1e-9 && point.intensity == 7
+        }));
+
+        // the rings agree with the rows of converted points
+        let expect_points = converter.convert_blocks(packet, 0..BLOCKS_PER_PACKET)?;
+        assert_eq!(points.len(), expect_points.len());
+        izip!(points.iter(), expect_points.iter()).for_each(|(point, expect_point)| {
+            assert_eq!(point.ring as usize, expect_point.lidar_frame_entry.row_idx);
+        });
+
+        Ok(())
+    }
+
+    #[test]
+    fn convert_blocks_test() -> Result<()> {
+        let new_packet = |packet_idx: usize, return_mode: ReturnMode| {
+            let mut packet = PacketBuilder::new()
+                .return_mode(return_mode)
+                .timestamp(packet_idx as u32 * 1327)
+                .channels(500, 7)
+                .build();
+            packet
+                .blocks
+                .iter_mut()
+                .enumerate()
+                .for_each(|(block_idx, block)| {
+                    let firing_idx = match return_mode {
+                        ReturnMode::DualReturn => block_idx / 2,
+                        _ => block_idx,
+                    };
+                    let count = 35800 + (packet_idx * BLOCKS_PER_PACKET + firing_idx) * 40;
+                    block.azimuth_count = (count % 36000) as u16;
+                });
+            packet
+        };
+        let packet = new_packet(0, ReturnMode::StrongestReturn);
+        let next_packet = new_packet(1, ReturnMode::StrongestReturn);
+
+        let mut converter =
+            Vlp16_Strongest_PcdConverter::from_config(Config::vlp_16_strongest_return());
+        let points = converter.convert_blocks(packet, 0..3)?;
+        assert_eq!(points.len(), 3 * CHANNELS_PER_BLOCK);
+
+        // the state is untouched, and the points match the streaming conversion
+        let expect_points: Vec<_> = converter
+            .convert(packet)?
+            .into_iter()
+            .chain(converter.convert(next_packet)?)
+            .collect();
+        assert_eq!(
+            expect_points.len(),
+            (2 * BLOCKS_PER_PACKET - 1) * CHANNELS_PER_BLOCK
+        );
+        let assert_same = |points: &[SingleReturnPoint], expect_points: &[SingleReturnPoint]| {
+            assert_eq!(points.len(), expect_points.len());
+            izip!(points, expect_points).for_each(|(point, expect)| {
+                assert_eq!(point.timestamp, expect.timestamp);
+                assert_eq!(
+                    point.lidar_frame_entry.row_idx,
+                    expect.lidar_frame_entry.row_idx
+                );
+                assert!(
+                    (point.corrected_azimuth_angle - expect.corrected_azimuth_angle)
+                        .get::<degree>()
+                        .abs()
+                        < 1e-9
+                );
+            });
+        };
+        assert_same(&points, &expect_points[..3 * CHANNELS_PER_BLOCK]);
+
+        // the last block is interpolated toward the extrapolated packet
+        let points = converter.convert_blocks(packet, 11..12)?;
+        assert_same(
+            &points,
+            &expect_points[11 * CHANNELS_PER_BLOCK..12 * CHANNELS_PER_BLOCK],
+        );
+
+        // the blocks of the same firing are decoded together in dual return mode
+        let mut converter = Vlp16_Dual_PcdConverter::from_config(Config::vlp_16_dual_return());
+        let points = converter.convert_blocks(new_packet(0, ReturnMode::DualReturn), 0..3)?;
+        assert_eq!(points.len(), 2 * CHANNELS_PER_BLOCK);
+
+        assert!(converter
+            .convert_blocks(new_packet(0, ReturnMode::DualReturn), 3..3)
+            .is_err());
+        assert!(converter
+            .convert_blocks(new_packet(0, ReturnMode::DualReturn), 0..13)
+            .is_err());
+
+        // the encoder is validated as usual
+        let mut packet = new_packet(0, ReturnMode::DualReturn);
+        packet.blocks[4].azimuth_count = 40000;
+        converter.enable_diagnostics();
+        converter.convert_blocks(packet, 0..2)?;
+        assert_eq!(
+            converter.take_diagnostics(),
+            vec![Diagnostic::EncoderOverflow {
+                encoder_count: 40000
+            }]
+        );
+
+        // the upper and lower blocks of HDL-64E are decoded together
+        let params = ParamsConfig::from_str(include_str!("../params/64e_utexas.yaml"))?;
+        let mut converter =
+            Hdl64_Strongest_PcdConverter::from_config(Config::hdl_64e_strongest_return(&params)?);
+        let mut packet = new_packet(0, ReturnMode::StrongestReturn);
+        packet
+            .blocks
+            .iter_mut()
+            .skip(1)
+            .step_by(2)
+            .for_each(|block| block.block_identifier = BlockIdentifier::Block32To63);
+        let points = converter.convert_blocks(packet, 3..4)?;
+        let expect_points = converter.convert(packet)?;
+        assert_same(
+            &points,
+            &expect_points[2 * CHANNELS_PER_BLOCK..4 * CHANNELS_PER_BLOCK],
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn direction_cache_test() -> Result<()> {
+        let new_packet = |packet_idx: usize| {
+            PacketBuilder::new()
+                .timestamp(packet_idx as u32 * 1327)
+                .channels(5000, 0)
+                .azimuth_counts(packet_idx * BLOCKS_PER_PACKET * 37, 37)
+                .alternate_blocks()
+                .build()
+        };
+        let assert_close = |lhs: &[SingleReturnPoint], rhs: &[SingleReturnPoint]| {
+            assert_eq!(lhs.len(), rhs.len());
+            izip!(lhs, rhs).for_each(|(lhs, rhs)| {
+                izip!(lhs.data.position.iter(), rhs.data.position.iter()).for_each(|(l, r)| {
+                    // rounding to 0.01 degrees displaces points at 10 meters by at most 1mm
+                    assert!((*l - *r).abs() < Length::new::<millimeter>(1.0));
+                });
+            });
+        };
+
+        let config = Config::vlp_16_strongest_return();
+        let mut converter = Vlp16_Strongest_PcdConverter::from_config(config.clone());
+        let mut cached_converter = Vlp16_Strongest_PcdConverter::from_config(config);
+        cached_converter.enable_direction_cache();
+        for packet_idx in 0..3 {
+            let packet = new_packet(packet_idx);
+            assert_close(
+                &converter.convert(packet)?,
+                &cached_converter.convert(packet)?,
+            );
+        }
+
+        // lasers with offsets
+        let params = ParamsConfig::from_str(include_str!("../params/64e_utexas.yaml"))?;
+        let config = Config::hdl_64e_strongest_return(&params)?;
+        let mut converter = Hdl64_Strongest_PcdConverter::from_config(config.clone());
+        let mut cached_converter = Hdl64_Strongest_PcdConverter::from_config(config);
+        cached_converter.enable_direction_cache();
+        let packet = new_packet(0);
+        assert_close(
+            &converter.convert(packet)?,
+            &cached_converter.convert(packet)?,
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn self_return_mask_test() -> Result<()> {
+        let new_packet = |timestamp: u32, azimuth_count: usize| {
+            PacketBuilder::new()
+                .timestamp(timestamp)
+                .channels(250, 0)
+                .azimuth_counts(azimuth_count, 0)
+                .build()
+        };
+
+        // mask 10 degrees of azimuth within 1 meter
+        let mask = SelfReturnMask::new().with_window(MaskWindow {
+            azimuth_range: Angle::new::<degree>(85.0)..Angle::new::<degree>(95.0),
+            elevation_range: Angle::new::<degree>(-90.0)..Angle::new::<degree>(90.0),
+            distance_range: Length::new::<meter>(0.0)..Length::new::<meter>(1.0),
+        });
+        let mut converter =
+            Vlp16_Strongest_PcdConverter::from_config(Config::vlp_16_strongest_return());
+        converter.set_self_return_mask(mask.clone());
+
+        // points facing the mask are removed
+        converter.convert(new_packet(0, 9000))?;
+        let points = converter.convert(new_packet(1327, 9000))?;
+        assert!(points.is_empty());
+
+        // points in other directions survive
+        let points = converter.convert(new_packet(2654, 18000))?;
+        assert!(!points.is_empty());
+        assert!(points.iter().all(|point| {
+            let azimuth = point.corrected_azimuth_angle.get::<degree>();
+            !(85.0..95.0).contains(&azimuth)
+        }));
+
+        // points beyond the range survive
+        let mut packet = new_packet(3981, 9000);
+        packet.blocks.iter_mut().for_each(|block| {
+            block.channels.iter_mut().for_each(|channel| {
+                channel.distance = 5000;
+            });
+        });
+        let points = converter.convert(packet)?;
+        assert!(!points.is_empty());
+
+        // the mask is in the sensor frame, before the azimuth offset and the elevation flip
+        let config = Config::vlp_16_strongest_return()
+            .with_azimuth_zero_offset(Angle::new::<degree>(90.0))
+            .with_flip_elevation_sign(true);
+        let lasers = config.lasers.clone();
+        let mut converter = Vlp16_Strongest_PcdConverter::from_config(config);
+        converter.set_self_return_mask(SelfReturnMask::new().with_window(MaskWindow {
+            azimuth_range: Angle::new::<degree>(85.0)..Angle::new::<degree>(95.0),
+            elevation_range: Angle::new::<degree>(0.0)..Angle::new::<degree>(90.0),
+            distance_range: Length::new::<meter>(0.0)..Length::new::<meter>(1.0),
+        }));
+        converter.convert(new_packet(0, 9000))?;
+        let points = converter.convert(new_packet(1327, 9000))?;
+        assert_eq!(points.len(), BLOCKS_PER_PACKET * CHANNELS_PER_BLOCK / 2);
+        assert!(points.iter().all(|point| {
+            let azimuth = point.corrected_azimuth_angle.get::<degree>();
+            lasers[point.laser_id as usize].elevation_angle < Angle::new::<degree>(0.0)
+                && (azimuth - 180.0).abs() < 1.0
+                && point.data.position[2] > Length::new::<meter>(0.0)
+        }));
+
+        Ok(())
+    }
+
+    #[test]
+    fn elevation_index_override_test() -> Result<()> {
+        assert!(Config::vlp_16_strongest_return()
+            .with_elevation_index(vec![0; 16])
+            .is_err());
+        assert!(Config::vlp_16_strongest_return()
+            .with_elevation_index((0..15).collect())
+            .is_err());
+
+        let elevation_index: Vec<usize> = (0..16).rev().collect();
+        let config =
+            Config::vlp_16_strongest_return().with_elevation_index(elevation_index.clone())?;
+        assert_eq!(config.elevation_index(), Some(elevation_index.as_slice()));
+        let mut converter = Vlp16_Strongest_PcdConverter::from_config(config);
+
+        let packet = PacketBuilder::new().channels(500, 0).build();
+        let points = converter.convert(packet)?;
+        assert!(!points.is_empty());
+        assert!(points
+            .iter()
+            .all(|point| point.lidar_frame_entry.row_idx == 15 - point.laser_id as usize));
+
+        Ok(())
+    }
+
+    #[test]
+    fn return_kind_test() -> Result<()> {
+        let new_packet = |return_mode: ReturnMode, timestamp: u32| {
+            PacketBuilder::new()
+                .return_mode(return_mode)
+                .timestamp(timestamp)
+                .channels(500, 0)
+                .build()
+        };
+
+        // the first packet is fully converted after the next packet arrives
+        let mut converter = Vlp16_Dual_PcdConverter::from_config(Config::vlp_16_dual_return());
+        converter.convert(new_packet(ReturnMode::DualReturn, 0))?;
+        let points = converter.convert(new_packet(ReturnMode::DualReturn, 1327))?;
+        assert!(!points.is_empty());
+        assert!(points
+            .iter()
+            .all(|point| point.return_kind == ReturnKind::Both));
+
+        let mut converter = Vlp16_Dynamic_PcdConverter::from_config(Config::vlp_16_dynamic_return(
+            ReturnMode::LastReturn,
+        ));
+        match converter.convert(new_packet(ReturnMode::LastReturn, 0))? {
+            DynamicReturnPoints::Single(points) => {
+                assert!(!points.is_empty());
+                assert!(points
+                    .iter()
+                    .all(|point| point.return_kind == ReturnKind::Last));
+            }
+            DynamicReturnPoints::Dual(_) => unreachable!(),
+        }
+
+        let mut converter =
+            Vlp16_Strongest_PcdConverter::from_config(Config::vlp_16_strongest_return());
+        let points = converter.convert(new_packet(ReturnMode::StrongestReturn, 0))?;
+        assert!(points
+            .iter()
+            .all(|point| point.return_kind == ReturnKind::Strongest));
+
+        Ok(())
+    }
+
+    #[test]
+    fn validation_level_test() -> Result<()> {
+        let mut packet = PacketBuilder::new().channels(500, 0).build();
+
+        // malformed product ID byte
+        let mut buffer =
+            unsafe { mem::transmute::<DataPacket, [u8; mem::size_of::<DataPacket>()]>(packet) };
+        *buffer.last_mut().unwrap() = 0xff;
+        let mut diagnostics = vec![];
+        assert!(DataPacket::from_slice_validated(
+            &buffer,
+            ValidationLevel::Strict,
+            &mut diagnostics
+        )
+        .is_err());
+        assert!(DataPacket::from_slice_validated(
+            &buffer,
+            ValidationLevel::Lenient,
+            &mut diagnostics
+        )?
+        .is_none());
+        assert_eq!(
+            diagnostics,
+            vec![Diagnostic::InvalidFactoryBytes {
+                return_mode: ReturnMode::StrongestReturn as u8,
+                product_id: 0xff,
+            }]
+        );
+
+        // azimuth count beyond a revolution
+        packet
+            .blocks
+            .iter_mut()
+            .for_each(|block| block.azimuth_count = 40000);
+        let mut converter =
+            Vlp16_Strongest_PcdConverter::from_config(Config::vlp_16_strongest_return());
+        converter.enable_diagnostics();
+        converter.convert(packet)?;
+        assert_eq!(
+            converter.take_diagnostics(),
+            vec![
+                Diagnostic::EncoderOverflow {
+                    encoder_count: 40000
+                };
+                BLOCKS_PER_PACKET
+            ]
+        );
+        converter.set_validation_level(ValidationLevel::Strict);
+        assert!(converter.convert(packet).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn original_azimuth_and_firing_time_offset_test() -> Result<()> {
+        let mut packet = PacketBuilder::new().channels(500, 0).build();
+        packet
+            .blocks
+            .iter_mut()
+            .enumerate()
+            .for_each(|(idx, block)| block.azimuth_count = 1000 + 40 * idx as u16);
+
+        let mut config = Config::vlp_16_strongest_return();
+        config
+            .lasers
+            .iter_mut()
+            .enumerate()
+            .for_each(|(idx, laser)| laser.azimuth_offset = Angle::new::<degree>(idx as f64 * 0.1));
+        let lasers = config.lasers.clone();
+        let mut converter = Vlp16_Strongest_PcdConverter::from_config(config);
+        let points = converter.convert(packet)?;
+
+        // the first firing starts exactly at the azimuth of the first block
+        let block_azimuth = packet.blocks[0].azimuth_angle();
+        let firing_timestamp = points[0].timestamp();
+        points[..16].iter().enumerate().for_each(|(idx, point)| {
+            assert_eq!(point.laser_id() as usize, idx);
+
+            let expect_offset = Time::new::<microsecond>(CHANNEL_PERIOD * idx as f64);
+            let diff = point.firing_time_offset() - expect_offset;
+            assert!(diff.get::<nanosecond>().abs() < 1e-6);
+            let diff = point.timestamp() - point.firing_time_offset() - firing_timestamp;
+            assert!(diff.get::<nanosecond>().abs() < 1e-6);
+
+            // the azimuth advances between channels within the firing
+            let interpolated = Angle::new::<degree>(0.4 / (2.0 * FIRING_PERIOD))
+                * point.firing_time_offset().get::<microsecond>();
+            let expect_azimuth = block_azimuth + interpolated;
+            let diff = point.original_azimuth_angle() - expect_azimuth;
+            assert!(diff.get::<degree>().abs() < 1e-9);
+
+            // the azimuth offset is added once
+            let diff = point.corrected_azimuth_angle()
+                - point.original_azimuth_angle()
+                - lasers[idx].azimuth_offset;
+            assert!(diff.get::<degree>().abs() < 1e-9);
+        });
+
+        // the azimuth offsets of VLP-32C are not in the original azimuth either
+        packet.product_id = ProductID::VLP32C;
+        let mut converter =
+            Vlp32_Strongest_PcdConverter::from_config(Config::vlp_32c_strongest_return());
+        let lasers = Config::vlp_32c_strongest_return().lasers;
+        let points = converter.convert(packet)?;
+        points.iter().for_each(|point| {
+            let diff = point.corrected_azimuth_angle()
+                - point.original_azimuth_angle()
+                - lasers[point.laser_id() as usize].azimuth_offset;
+            assert!(diff.get::<degree>().abs() < 1e-9);
+        });
+
+        Ok(())
+    }
+
+    #[test]
+    fn dual_return_blocks_test() -> Result<()> {
+        // the strongest returns are in even blocks and the last returns are in odd blocks
+        let new_packet = |product_id: ProductID, timestamp: u32| {
+            let mut packet = PacketBuilder::new()
+                .product_id(product_id)
+                .return_mode(ReturnMode::DualReturn)
+                .timestamp(timestamp)
+                .channels(1000, 10)
+                .build();
+            packet
+                .blocks
+                .iter_mut()
+                .skip(1)
+                .step_by(2)
+                .for_each(|block| {
+                    block.channels.iter_mut().for_each(|channel| {
+                        channel.distance = 3000;
+                        channel.intensity = 5;
+                    })
+                });
+            packet
+        };
+        let check = |points: &[DualReturnPoint]| {
+            assert!(!points.is_empty());
+            points.iter().for_each(|point| {
+                let strongest = point.strongest_return_data.distance.get::<meter>();
+                let last = point.last_return_data.distance.get::<meter>();
+                assert!(strongest > 0.0);
+                assert!((last - strongest * 3.0).abs() < 1e-9);
+                assert_eq!(point.strongest_return_data.intensity, 10);
+                assert_eq!(point.last_return_data.intensity, 5);
+            });
+        };
+
+        let mut converter = Vlp16_Dual_PcdConverter::from_config(Config::vlp_16_dual_return());
+        check(&converter.convert(new_packet(ProductID::VLP16, 0))?);
+        check(&converter.convert(new_packet(ProductID::VLP16, 1327))?);
+
+        let mut converter = Vlp32_Dual_PcdConverter::from_config(Config::vlp_32c_dual_return());
+        check(&converter.convert(new_packet(ProductID::VLP32C, 0))?);
+        check(&converter.convert(new_packet(ProductID::VLP32C, 553))?);
+
+        Ok(())
     }
 }
@@ -1,10 +1,15 @@
+use super::options::ConverterOptions;
 use crate::{
     common::*,
+    diagnostic::Diagnostic,
     velodyne::{
         config::LaserParameter,
-        consts::{self, CHANNEL_PERIOD, FIRING_PERIOD},
+        consts::{self, CHANNEL_PERIOD, FIRING_PERIOD, HDL_64E_FIRING_PERIOD},
         packet::{Block, Channel, DataPacket, ReturnMode},
-        point::{DualReturnPoint, LidarFrameEntry, PointData, SingleReturnPoint},
+        point::{
+            CylindricalPoint, DualReturnPoint, DynamicReturnPoints, LidarFrameEntry, LidarFrameMsg,
+            PointData, ReturnKind, RotateAzimuth, SingleReturnPoint,
+        },
     },
 };
 
@@ -18,7 +23,7 @@ struct FiringInfo<'a> {
 
 pub(crate) fn convert_single_return_16_channel(
     lasers: &[LaserParameter; 16],
-    distance_resolution: Length,
+    options: &mut ConverterOptions,
     last_block: &mut Option<(Time, Block)>,
     packet: &DataPacket,
 ) -> Vec<SingleReturnPoint> {
@@ -37,6 +42,12 @@ pub(crate) fn convert_single_return_16_channel(
         let new_block = *packet.blocks.last().unwrap();
         last_block.replace((new_timestamp, new_block))
     };
+    report_dropped_firings(
+        options.diagnostics.as_mut(),
+        prev_last_block.map(|(block_timestamp, _)| block_timestamp + block_period - firing_period),
+        packet_timestamp,
+        firing_period,
+    );
 
     let packet_blocks_iter = packet.blocks.iter().enumerate().map(|(idx, block)| {
         let block_timestamp = packet_timestamp + block_period * idx as f64;
@@ -50,7 +61,7 @@ pub(crate) fn convert_single_return_16_channel(
 
     //set lidar beam channel index
     let mut channel_vec =
-        convert_to_points_16_channel(lasers, distance_resolution, &mut blocks_iter);
+        convert_to_points_16_channel(lasers, options, packet.return_mode.into(), &mut blocks_iter);
 
     // get index  array
     let corr_deg_index = consts::VLP_16_ELEVAION_INDEX;
@@ -66,7 +77,7 @@ pub(crate) fn convert_single_return_16_channel(
 
 pub(crate) fn convert_dual_return_16_channel(
     lasers: &[LaserParameter; 16],
-    distance_resolution: Length,
+    options: &mut ConverterOptions,
     last_block: &mut Option<(Time, Block, Block)>,
     packet: &DataPacket,
 ) -> Vec<DualReturnPoint> {
@@ -94,6 +105,13 @@ pub(crate) fn convert_dual_return_16_channel(
             None => (None, None),
         }
     };
+    report_dropped_firings(
+        options.diagnostics.as_mut(),
+        prev_strongest_block
+            .map(|(block_timestamp, _)| block_timestamp + block_period - firing_period),
+        packet_timestamp,
+        firing_period,
+    );
 
     let mut strongest_blocks_iter = {
         let packet_strongest_blocks_iter =
@@ -118,6 +136,7 @@ pub(crate) fn convert_dual_return_16_channel(
             packet
                 .blocks
                 .iter()
+                .skip(1)
                 .step_by(2)
                 .enumerate()
                 .map(|(idx, block)| {
@@ -134,8 +153,12 @@ pub(crate) fn convert_dual_return_16_channel(
     // get index  array
     let corr_deg_index = consts::VLP_16_ELEVAION_INDEX;
 
-    let mut strongest_points =
-        convert_to_points_16_channel(lasers, distance_resolution, &mut strongest_blocks_iter);
+    let mut strongest_points = convert_to_points_16_channel(
+        lasers,
+        options,
+        ReturnKind::Strongest,
+        &mut strongest_blocks_iter,
+    );
 
     // set channel_index
     for i in 0..strongest_points.len() {
@@ -144,7 +167,7 @@ pub(crate) fn convert_dual_return_16_channel(
     }
 
     let mut last_points =
-        convert_to_points_16_channel(lasers, distance_resolution, &mut last_blocks_iter);
+        convert_to_points_16_channel(lasers, options, ReturnKind::Last, &mut last_blocks_iter);
 
     // set channel_index
     for i in 0..last_points.len() {
@@ -152,13 +175,15 @@ pub(crate) fn convert_dual_return_16_channel(
         last_points[i].lidar_frame_entry.row_idx = corr_deg_index[i % 16];
     }
 
+    // the first packet yields one less block since there is no previous block
+    let num_blocks = packet.blocks.len() / 2 - prev_strongest_block.is_none() as usize;
     debug_assert_eq!(
         strongest_points.len(),
-        packet.blocks.len() / 2 * packet.blocks[0].channels.len()
+        num_blocks * packet.blocks[0].channels.len()
     );
     debug_assert_eq!(
         last_points.len(),
-        packet.blocks.len() / 2 * packet.blocks[0].channels.len()
+        num_blocks * packet.blocks[0].channels.len()
     );
 
     strongest_points
@@ -172,7 +197,7 @@ pub(crate) fn convert_dual_return_16_channel(
 
 pub(crate) fn convert_single_return_32_channel(
     lasers: &[LaserParameter; 32],
-    distance_resolution: Length,
+    options: &mut ConverterOptions,
     last_block: &mut Option<(Time, Block)>,
     packet: &DataPacket,
 ) -> Vec<SingleReturnPoint> {
@@ -195,6 +220,12 @@ pub(crate) fn convert_single_return_32_channel(
         let new_block = *packet.blocks.last().unwrap();
         last_block.replace((new_timestamp, new_block))
     };
+    report_dropped_firings(
+        options.diagnostics.as_mut(),
+        prev_last_block.map(|(block_timestamp, _)| block_timestamp + block_period - firing_period),
+        packet_timestamp,
+        firing_period,
+    );
 
     let mut blocks_iter = prev_last_block
         .iter()
@@ -203,7 +234,8 @@ pub(crate) fn convert_single_return_32_channel(
 
     //set lidar beam channel index
     let mut channel_vec =
-        convert_to_points_32_channel(lasers, distance_resolution, &mut blocks_iter).clone();
+        convert_to_points_32_channel(lasers, options, packet.return_mode.into(), &mut blocks_iter)
+            .clone();
 
     // get index  array
     let corr_deg_index = consts::VLP_32C_ELEVAION_INDEX;
@@ -219,7 +251,7 @@ pub(crate) fn convert_single_return_32_channel(
 
 pub(crate) fn convert_dual_return_32_channel(
     lasers: &[LaserParameter; 32],
-    distance_resolution: Length,
+    options: &mut ConverterOptions,
     last_block: &mut Option<(Time, Block, Block)>,
     packet: &DataPacket,
 ) -> Vec<DualReturnPoint> {
@@ -247,6 +279,13 @@ pub(crate) fn convert_dual_return_32_channel(
             None => (None, None),
         }
     };
+    report_dropped_firings(
+        options.diagnostics.as_mut(),
+        prev_strongest_block
+            .map(|(block_timestamp, _)| block_timestamp + block_period - firing_period),
+        packet_timestamp,
+        firing_period,
+    );
 
     let mut strongest_blocks_iter = {
         let packet_strongest_blocks_iter =
@@ -271,6 +310,7 @@ pub(crate) fn convert_dual_return_32_channel(
             packet
                 .blocks
                 .iter()
+                .skip(1)
                 .step_by(2)
                 .enumerate()
                 .map(|(idx, block)| {
@@ -284,8 +324,12 @@ pub(crate) fn convert_dual_return_32_channel(
             .chain(packet_last_blocks_iter)
     };
 
-    let mut strongest_points =
-        convert_to_points_32_channel(lasers, distance_resolution, &mut strongest_blocks_iter);
+    let mut strongest_points = convert_to_points_32_channel(
+        lasers,
+        options,
+        ReturnKind::Strongest,
+        &mut strongest_blocks_iter,
+    );
 
     // get index  array
     let corr_deg_index = consts::VLP_32C_ELEVAION_INDEX;
@@ -297,7 +341,7 @@ pub(crate) fn convert_dual_return_32_channel(
     }
 
     let mut last_points =
-        convert_to_points_32_channel(lasers, distance_resolution, &mut last_blocks_iter);
+        convert_to_points_32_channel(lasers, options, ReturnKind::Last, &mut last_blocks_iter);
 
     // set channel_index
     for i in 0..last_points.len() {
@@ -305,13 +349,15 @@ pub(crate) fn convert_dual_return_32_channel(
         last_points[i].lidar_frame_entry.row_idx = corr_deg_index[i % 32];
     }
 
+    // the first packet yields one less block since there is no previous block
+    let num_blocks = packet.blocks.len() / 2 - prev_strongest_block.is_none() as usize;
     debug_assert_eq!(
         strongest_points.len(),
-        packet.blocks.len() / 2 * packet.blocks[0].channels.len()
+        num_blocks * packet.blocks[0].channels.len()
     );
     debug_assert_eq!(
         last_points.len(),
-        packet.blocks.len() / 2 * packet.blocks[0].channels.len()
+        num_blocks * packet.blocks[0].channels.len()
     );
 
     strongest_points
@@ -323,223 +369,807 @@ pub(crate) fn convert_dual_return_32_channel(
         .collect()
 }
 
-pub(crate) fn convert_to_points_16_channel<'a, I>(
+pub(crate) fn convert_single_return_64_channel(
+    lasers: &[LaserParameter; 64],
+    options: &mut ConverterOptions,
+    last_timestamp: &mut Option<Time>,
+    packet: &DataPacket,
+) -> Vec<SingleReturnPoint> {
+    debug_assert!(
+        [ReturnMode::StrongestReturn, ReturnMode::LastReturn].contains(&packet.return_mode)
+    );
+
+    // consts
+    let firing_period = Time::new::<microsecond>(HDL_64E_FIRING_PERIOD);
+    let packet_timestamp = packet.time();
+
+    // update the timestamp of last seen firing
+    let prev_timestamp = {
+        let num_firings = packet.blocks.len() / 2;
+        last_timestamp.replace(packet_timestamp + firing_period * (num_firings - 1) as f64)
+    };
+    report_dropped_firings(
+        options.diagnostics.as_mut(),
+        prev_timestamp,
+        packet_timestamp,
+        firing_period,
+    );
+
+    decode_64_channel(lasers, options, packet, 0..packet.blocks.len())
+}
+
+/// Decodes the blocks within the range of an HDL-64E packet.
+fn decode_64_channel(
+    lasers: &[LaserParameter; 64],
+    options: &ConverterOptions,
+    packet: &DataPacket,
+    block_range: Range<usize>,
+) -> Vec<SingleReturnPoint> {
+    // consts
+    let firing_period = Time::new::<microsecond>(HDL_64E_FIRING_PERIOD);
+    let packet_timestamp = packet.time();
+    let full_angle = Angle::new::<radian>(std::f64::consts::PI * 2.0);
+    let return_kind = ReturnKind::from(packet.return_mode);
+    let direction_cache = &options.direction_cache;
+
+    // rank lasers by elevation angle, from top to bottom
+    let row_indices = {
+        let mut laser_ids: Vec<usize> = (0..lasers.len()).collect();
+        laser_ids.sort_by(|&lhs, &rhs| {
+            lasers[rhs]
+                .elevation_angle
+                .partial_cmp(&lasers[lhs].elevation_angle)
+                .unwrap()
+        });
+
+        let mut row_indices = [0; 64];
+        laser_ids
+            .into_iter()
+            .enumerate()
+            .for_each(|(row_idx, laser_id)| row_indices[laser_id] = row_idx);
+        row_indices
+    };
+
+    // upper and lower blocks of the same firing are fired simultaneously
+    packet.blocks[block_range.clone()]
+        .iter()
+        .zip(block_range)
+        .flat_map(|(block, block_idx)| {
+            let timestamp = packet_timestamp + firing_period * (block_idx / 2) as f64;
+            let original_azimuth_angle = block.azimuth_angle();
+            let laser_id_offset = block.laser_id_offset();
+
+            block
+                .channels
+                .iter()
+                .zip(laser_id_offset..)
+                .map(move |(channel, laser_id)| {
+                    let LaserParameter {
+                        elevation_angle,
+                        azimuth_offset,
+                        vertical_offset,
+                        horizontal_offset,
+                    } = &lasers[laser_id as usize];
+
+                    let corrected_azimuth_angle = {
+                        let mut azimuth = original_azimuth_angle + *azimuth_offset;
+                        if azimuth >= full_angle {
+                            azimuth -= full_angle;
+                        } else if azimuth < Angle::new::<radian>(0.0) {
+                            azimuth += full_angle;
+                        }
+                        azimuth
+                    };
+                    let distance = options.decode_distance(channel.distance);
+                    let position = match direction_cache {
+                        Some(cache) => {
+                            cache.position(laser_id as usize, distance, corrected_azimuth_angle)
+                        }
+                        None => compute_position(
+                            distance,
+                            *elevation_angle,
+                            corrected_azimuth_angle,
+                            *vertical_offset,
+                            *horizontal_offset,
+                        ),
+                    };
+
+                    SingleReturnPoint {
+                        laser_id,
+                        timestamp,
+                        firing_time_offset: Time::new::<second>(0.0),
+                        original_azimuth_angle,
+                        corrected_azimuth_angle,
+                        data: PointData {
+                            distance,
+                            intensity: channel.intensity,
+                            intensity_value: channel.intensity as f64,
+                            position,
+                            suspect: false,
+                        },
+                        lidar_frame_entry: LidarFrameEntry {
+                            row_idx: row_indices[laser_id as usize],
+                            col_idx: 0,
+                        },
+                        return_kind,
+                    }
+                })
+        })
+        .collect()
+}
+
+/// Converts the firings within the block range of a single return packet. See
+/// [PointCloudConverter::convert_blocks](super::PointCloudConverter::convert_blocks).
+pub(crate) fn convert_blocks_single_return_16_channel(
     lasers: &[LaserParameter; 16],
+    options: &ConverterOptions,
+    packet: &DataPacket,
+    range: Range<usize>,
+) -> Result<Vec<SingleReturnPoint>> {
+    let firings = firing_range(range, 1)?;
+    let block_period = Time::new::<microsecond>(FIRING_PERIOD) * 2.0;
+    let blocks = firing_blocks(packet, firings, 1, 0, block_period);
+
+    let mut points = convert_to_points_16_channel(
+        lasers,
+        options,
+        packet.return_mode.into(),
+        &mut blocks.iter().map(|(timestamp, block)| (*timestamp, block)),
+    );
+    set_row_indices(&mut points, &consts::VLP_16_ELEVAION_INDEX);
+    Ok(points)
+}
+
+/// Converts the firings within the block range of a dual return packet. See
+/// [PointCloudConverter::convert_blocks](super::PointCloudConverter::convert_blocks).
+pub(crate) fn convert_blocks_dual_return_16_channel(
+    lasers: &[LaserParameter; 16],
+    options: &ConverterOptions,
+    packet: &DataPacket,
+    range: Range<usize>,
+) -> Result<Vec<DualReturnPoint>> {
+    let firings = firing_range(range, 2)?;
+    let block_period = Time::new::<microsecond>(FIRING_PERIOD) * 2.0;
+
+    // the strongest and last return blocks of a firing are interleaved
+    let convert_return = |return_idx: usize, return_kind: ReturnKind| {
+        let blocks = firing_blocks(packet, firings.clone(), 2, return_idx, block_period);
+        let mut points = convert_to_points_16_channel(
+            lasers,
+            options,
+            return_kind,
+            &mut blocks.iter().map(|(timestamp, block)| (*timestamp, block)),
+        );
+        set_row_indices(&mut points, &consts::VLP_16_ELEVAION_INDEX);
+        points
+    };
+    let strongest_points = convert_return(0, ReturnKind::Strongest);
+    let last_points = convert_return(1, ReturnKind::Last);
+
+    Ok(pair_dual_points(strongest_points, last_points))
+}
+
+/// Converts the firings within the block range of a single return packet. See
+/// [PointCloudConverter::convert_blocks](super::PointCloudConverter::convert_blocks).
+pub(crate) fn convert_blocks_single_return_32_channel(
+    lasers: &[LaserParameter; 32],
+    options: &ConverterOptions,
+    packet: &DataPacket,
+    range: Range<usize>,
+) -> Result<Vec<SingleReturnPoint>> {
+    let firings = firing_range(range, 1)?;
+    let block_period = Time::new::<microsecond>(FIRING_PERIOD);
+    let blocks = firing_blocks(packet, firings, 1, 0, block_period);
+
+    let mut points = convert_to_points_32_channel(
+        lasers,
+        options,
+        packet.return_mode.into(),
+        &mut blocks.iter().map(|(timestamp, block)| (*timestamp, block)),
+    );
+    set_row_indices(&mut points, &consts::VLP_32C_ELEVAION_INDEX);
+    Ok(points)
+}
+
+/// Converts the firings within the block range of a dual return packet. See
+/// [PointCloudConverter::convert_blocks](super::PointCloudConverter::convert_blocks).
+pub(crate) fn convert_blocks_dual_return_32_channel(
+    lasers: &[LaserParameter; 32],
+    options: &ConverterOptions,
+    packet: &DataPacket,
+    range: Range<usize>,
+) -> Result<Vec<DualReturnPoint>> {
+    let firings = firing_range(range, 2)?;
+    let block_period = Time::new::<microsecond>(FIRING_PERIOD);
+
+    // the strongest and last return blocks of a firing are interleaved
+    let convert_return = |return_idx: usize, return_kind: ReturnKind| {
+        let blocks = firing_blocks(packet, firings.clone(), 2, return_idx, block_period);
+        let mut points = convert_to_points_32_channel(
+            lasers,
+            options,
+            return_kind,
+            &mut blocks.iter().map(|(timestamp, block)| (*timestamp, block)),
+        );
+        set_row_indices(&mut points, &consts::VLP_32C_ELEVAION_INDEX);
+        points
+    };
+    let strongest_points = convert_return(0, ReturnKind::Strongest);
+    let last_points = convert_return(1, ReturnKind::Last);
+
+    Ok(pair_dual_points(strongest_points, last_points))
+}
+
+/// Converts the firings within the block range of an HDL-64E packet, where the upper and
+/// lower blocks of a firing are decoded together. See
+/// [PointCloudConverter::convert_blocks](super::PointCloudConverter::convert_blocks).
+pub(crate) fn convert_blocks_single_return_64_channel(
+    lasers: &[LaserParameter; 64],
+    options: &ConverterOptions,
+    packet: &DataPacket,
+    range: Range<usize>,
+) -> Result<Vec<SingleReturnPoint>> {
+    let Range { start, end } = firing_range(range, 2)?;
+    Ok(decode_64_channel(
+        lasers,
+        options,
+        packet,
+        (start * 2)..(end * 2),
+    ))
+}
+
+/// Rounds a block range out to the range of whole firings, where a firing spans
+/// `blocks_per_firing` blocks.
+fn firing_range(range: Range<usize>, blocks_per_firing: usize) -> Result<Range<usize>> {
+    let Range { start, end } = range;
+    ensure!(
+        start < end && end <= consts::BLOCKS_PER_PACKET,
+        "the block range {}..{} is empty or exceeds {} blocks",
+        start,
+        end,
+        consts::BLOCKS_PER_PACKET
+    );
+    Ok((start / blocks_per_firing)..((end - 1) / blocks_per_firing + 1))
+}
+
+/// Lists the blocks of a return in the firings with their timestamps, followed by the block
+/// of the next firing that bounds the azimuth of the last firing.
+///
+/// If the firings reach the end of packet, the next block is extrapolated at the speed of
+/// the last two firings. The `return_idx` selects the block within every firing.
+fn firing_blocks(
+    packet: &DataPacket,
+    firings: Range<usize>,
+    blocks_per_firing: usize,
+    return_idx: usize,
+    block_period: Time,
+) -> Vec<(Time, Block)> {
+    let counts_per_rev = (consts::AZIMUTH_COUNT_PER_REV - 1) as i64;
+    let packet_timestamp = Time::new::<microsecond>(packet.time().get::<microsecond>());
+    let num_firings = packet.blocks.len() / blocks_per_firing;
+    let block_at = |firing_idx: usize| packet.blocks[firing_idx * blocks_per_firing + return_idx];
+
+    let next_firing = firings.end;
+    let next_block = if next_firing < num_firings {
+        block_at(next_firing)
+    } else {
+        let last = block_at(num_firings - 1);
+        let prev = block_at(num_firings - 2);
+        let step =
+            (last.azimuth_count as i64 - prev.azimuth_count as i64).rem_euclid(counts_per_rev);
+        Block {
+            azimuth_count: (last.azimuth_count as i64 + step).rem_euclid(counts_per_rev) as u16,
+            ..last
+        }
+    };
+
+    firings
+        .map(|firing_idx| {
+            (
+                packet_timestamp + block_period * firing_idx as f64,
+                block_at(firing_idx),
+            )
+        })
+        .chain(iter::once((
+            packet_timestamp + block_period * next_firing as f64,
+            next_block,
+        )))
+        .collect()
+}
+
+/// Assigns the row indices of points converted in firing order.
+fn set_row_indices(points: &mut [SingleReturnPoint], elevation_index: &[usize]) {
+    points.iter_mut().enumerate().for_each(|(idx, point)| {
+        point.lidar_frame_entry.row_idx = elevation_index[idx % elevation_index.len()]
+    });
+}
+
+/// Returns the elevation index table that the converters assign rows by, which is indexed
+/// by laser ID.
+fn row_table(num_lasers: usize) -> Option<&'static [usize]> {
+    match num_lasers {
+        16 => Some(&consts::VLP_16_ELEVAION_INDEX),
+        32 => Some(&consts::VLP_32C_ELEVAION_INDEX),
+        _ => None,
+    }
+}
+
+/// Pairs the strongest and last return points of the same firings.
+fn pair_dual_points(
+    strongest_points: Vec<SingleReturnPoint>,
+    last_points: Vec<SingleReturnPoint>,
+) -> Vec<DualReturnPoint> {
+    debug_assert_eq!(strongest_points.len(), last_points.len());
+    strongest_points
+        .into_iter()
+        .zip(last_points)
+        .map(|(strongest_return_point, last_return_point)| {
+            DualReturnPoint::try_from_pair(strongest_return_point, last_return_point).unwrap()
+        })
+        .collect()
+}
+
+/// Checks that the azimuth counts of the blocks are within a revolution.
+pub(crate) fn validate_encoder(packet: &DataPacket, options: &mut ConverterOptions) -> Result<()> {
+    let ConverterOptions {
+        validation_level,
+        ref mut diagnostics,
+        ..
+    } = *options;
+
+    for block in packet.blocks.iter() {
+        let azimuth_count = block.azimuth_count;
+        if azimuth_count as usize >= consts::AZIMUTH_COUNT_PER_REV {
+            validation_level.check(
+                diagnostics.as_mut(),
+                Diagnostic::EncoderOverflow {
+                    encoder_count: azimuth_count as u32,
+                },
+            )?;
+        }
+    }
+    Ok(())
+}
+
+/// Converts a packet into points in cylindrical coordinates without interpolating
+/// azimuth angles nor computing positions.
+pub(crate) fn convert_cylindrical(
+    lasers: &[LaserParameter],
     distance_resolution: Length,
+    elevation_index: Option<&[usize]>,
+    packet: &DataPacket,
+) -> Vec<CylindricalPoint> {
+    let num_lasers = lasers.len();
+
+    // the rows of converted points, which rank lasers by elevation angles in descending
+    // order if the model has no elevation index table
+    let row_indices: Vec<usize> = match elevation_index.or_else(|| row_table(num_lasers)) {
+        Some(elevation_index) => elevation_index.to_vec(),
+        None => {
+            let mut laser_ids: Vec<usize> = (0..num_lasers).collect();
+            laser_ids.sort_by(|&lhs, &rhs| {
+                lasers[rhs]
+                    .elevation_angle
+                    .partial_cmp(&lasers[lhs].elevation_angle)
+                    .unwrap()
+            });
+            let mut row_indices = vec![0; num_lasers];
+            laser_ids
+                .into_iter()
+                .enumerate()
+                .for_each(|(row_idx, laser_id)| row_indices[laser_id] = row_idx);
+            row_indices
+        }
+    };
+
+    packet
+        .blocks
+        .iter()
+        .flat_map(|block| {
+            let azimuth_bin = block.azimuth_count;
+            let laser_id_offset = if num_lasers > consts::CHANNELS_PER_BLOCK {
+                block.laser_id_offset() as usize
+            } else {
+                0
+            };
+            let row_indices = &row_indices;
+            block
+                .channels
+                .iter()
+                .enumerate()
+                .map(move |(channel_idx, channel)| {
+                    let laser_id = (laser_id_offset + channel_idx) % num_lasers;
+                    CylindricalPoint {
+                        range: distance_resolution * channel.distance as f64,
+                        azimuth_bin,
+                        ring: row_indices[laser_id] as u8,
+                        intensity: channel.intensity,
+                    }
+                })
+        })
+        .collect()
+}
+
+/// Overrides row indices of converted points by an elevation index table.
+pub(crate) trait RemapRows {
+    fn remap_rows(&mut self, elevation_index: &[usize]);
+}
+
+impl RemapRows for Vec<SingleReturnPoint> {
+    fn remap_rows(&mut self, elevation_index: &[usize]) {
+        self.iter_mut()
+            .for_each(|point| point.set_row_idx(elevation_index[point.laser_id as usize]));
+    }
+}
+
+impl RemapRows for Vec<DualReturnPoint> {
+    fn remap_rows(&mut self, elevation_index: &[usize]) {
+        self.iter_mut()
+            .for_each(|point| point.set_row_idx(elevation_index[point.laser_id as usize]));
+    }
+}
+
+impl RemapRows for DynamicReturnPoints {
+    fn remap_rows(&mut self, elevation_index: &[usize]) {
+        match self {
+            Self::Single(points) => points.remap_rows(elevation_index),
+            Self::Dual(points) => points.remap_rows(elevation_index),
+        }
+    }
+}
+
+/// Adds a clockwise angle to the azimuth of converted points, rotating their positions accordingly.
+pub(crate) trait OffsetAzimuth {
+    fn offset_azimuth(&mut self, offset: Angle);
+}
+
+impl<P> OffsetAzimuth for Vec<P>
+where
+    P: RotateAzimuth,
+{
+    fn offset_azimuth(&mut self, offset: Angle) {
+        // a counter-clockwise rotation decreases the azimuth
+        self.iter_mut()
+            .for_each(|point| point.rotate_azimuth(-offset));
+    }
+}
+
+impl OffsetAzimuth for DynamicReturnPoints {
+    fn offset_azimuth(&mut self, offset: Angle) {
+        match self {
+            Self::Single(points) => points.offset_azimuth(offset),
+            Self::Dual(points) => points.offset_azimuth(offset),
+        }
+    }
+}
+
+/// Negates the z coordinates of points, and hence the elevation angles.
+pub(crate) trait FlipElevation {
+    fn flip_elevation(&mut self);
+}
+
+impl PointData {
+    fn flip_elevation(&mut self) {
+        let [x, y, z] = self.position;
+        self.position = [x, y, -z];
+    }
+}
+
+impl FlipElevation for Vec<SingleReturnPoint> {
+    fn flip_elevation(&mut self) {
+        self.iter_mut()
+            .for_each(|point| point.data.flip_elevation());
+    }
+}
+
+impl FlipElevation for Vec<DualReturnPoint> {
+    fn flip_elevation(&mut self) {
+        self.iter_mut().for_each(|point| {
+            point.strongest_return_data.flip_elevation();
+            point.last_return_data.flip_elevation();
+        });
+    }
+}
+
+impl FlipElevation for DynamicReturnPoints {
+    fn flip_elevation(&mut self) {
+        match self {
+            Self::Single(points) => points.flip_elevation(),
+            Self::Dual(points) => points.flip_elevation(),
+        }
+    }
+}
+
+/// Reports a [Diagnostic::DroppedFiring] if firings are missing between the
+/// last firing of previous packet and the first firing of current packet.
+fn report_dropped_firings(
+    diagnostics: Option<&mut Vec<Diagnostic>>,
+    prev_timestamp: Option<Time>,
+    curr_timestamp: Time,
+    firing_period: Time,
+) {
+    let (diagnostics, prev_timestamp) = match (diagnostics, prev_timestamp) {
+        (Some(diagnostics), Some(prev_timestamp)) => (diagnostics, prev_timestamp),
+        _ => return,
+    };
+
+    let num_periods = ((curr_timestamp - prev_timestamp).get::<microsecond>()
+        / firing_period.get::<microsecond>())
+    .round();
+    if num_periods > 1.0 {
+        diagnostics.push(Diagnostic::DroppedFiring {
+            prev_timestamp,
+            curr_timestamp,
+            num_dropped: num_periods as usize - 1,
+        });
+    }
+}
+
+pub(crate) fn convert_to_points_16_channel<'a, I>(
+    lasers: &[LaserParameter; 16],
+    options: &ConverterOptions,
+    return_kind: ReturnKind,
     iter: &mut I,
 ) -> Vec<SingleReturnPoint>
 where
     I: Iterator<Item = (Time, &'a Block)>,
 {
+    let direction_cache = &options.direction_cache;
     let channel_period = Time::new::<microsecond>(CHANNEL_PERIOD);
     let firing_period = Time::new::<microsecond>(FIRING_PERIOD);
 
     let first_item = iter.next().unwrap();
 
-    iter.scan(first_item, |prev_pair, (curr_timestamp, curr_block)| {
-        let (prev_timestamp, prev_block) = *prev_pair;
-        *prev_pair = (curr_timestamp, curr_block);
+    let mut points: Vec<_> = iter
+        .scan(first_item, |prev_pair, (curr_timestamp, curr_block)| {
+            let (prev_timestamp, prev_block) = *prev_pair;
+            *prev_pair = (curr_timestamp, curr_block);
+
+            let mid_timestamp = prev_timestamp + firing_period;
+
+            let prev_azimuth_angle = prev_block.azimuth_angle();
+            let curr_azimuth_angle = {
+                // fix roll-over case
+                let curr_angle = curr_block.azimuth_angle();
+                if curr_angle < prev_azimuth_angle {
+                    curr_angle + Angle::new::<radian>(std::f64::consts::PI * 2.0)
+                } else {
+                    curr_angle
+                }
+            };
+            let mid_azimuth_angle: Angle = (prev_azimuth_angle + curr_azimuth_angle) / 2.0;
 
-        let mid_timestamp = prev_timestamp + firing_period;
+            let former_firing = FiringInfo {
+                lower_timestamp: prev_timestamp,
+                lower_azimuth_angle: prev_azimuth_angle,
+                upper_azimuth_angle: mid_azimuth_angle,
+                firing: &prev_block.channels[0..16],
+            };
 
-        let prev_azimuth_angle = prev_block.azimuth_angle();
-        let curr_azimuth_angle = {
-            // fix roll-over case
-            let curr_angle = curr_block.azimuth_angle();
-            if curr_angle < prev_azimuth_angle {
-                curr_angle + Angle::new::<radian>(std::f64::consts::PI * 2.0)
-            } else {
-                curr_angle
-            }
-        };
-        let mid_azimuth_angle: Angle = (prev_azimuth_angle + curr_azimuth_angle) / 2.0;
-
-        let former_firing = FiringInfo {
-            lower_timestamp: prev_timestamp,
-            lower_azimuth_angle: prev_azimuth_angle,
-            upper_azimuth_angle: mid_azimuth_angle,
-            firing: &prev_block.channels[0..16],
-        };
-
-        let latter_firing = FiringInfo {
-            lower_timestamp: mid_timestamp,
-            lower_azimuth_angle: mid_azimuth_angle,
-            upper_azimuth_angle: curr_azimuth_angle,
-            firing: &prev_block.channels[16..32],
-        };
-
-        Some(vec![former_firing, latter_firing])
-    })
-    .flatten()
-    .flat_map(|firing_info| {
-        let FiringInfo {
-            lower_timestamp,
-            lower_azimuth_angle,
-            upper_azimuth_angle,
-            firing,
-        } = firing_info;
-
-        debug_assert_eq!(firing.len(), 16);
-        debug_assert!(lower_azimuth_angle <= upper_azimuth_angle);
-
-        izip!(firing.iter(), lasers.iter(), 0..).enumerate().map(
-            move |(channel_idx, (channel, laser_params, laser_id))| {
-                let timestamp = lower_timestamp + channel_period * channel_idx as f64;
-                let ratio = channel_period * channel_idx as f64 / firing_period;
-                let LaserParameter {
-                    elevation_angle,
-                    azimuth_offset,
-                    vertical_offset,
-                    horizontal_offset,
-                } = laser_params;
-
-                // clockwise angle with origin points to front of sensor
-                let original_azimuth_angle = {
-                    let mut azimuth = lower_azimuth_angle
-                        + Angle::from((upper_azimuth_angle - lower_azimuth_angle) * ratio)
-                        + *azimuth_offset;
-                    if azimuth >= Angle::new::<radian>(std::f64::consts::PI * 2.0) {
-                        azimuth -= Angle::new::<radian>(std::f64::consts::PI * 2.0);
-                    }
-                    azimuth
-                };
-                let corrected_azimuth_angle = {
-                    let mut azimuth = original_azimuth_angle + *azimuth_offset;
-                    if azimuth >= Angle::new::<radian>(std::f64::consts::PI * 2.0) {
-                        azimuth -= Angle::new::<radian>(std::f64::consts::PI * 2.0);
+            let latter_firing = FiringInfo {
+                lower_timestamp: mid_timestamp,
+                lower_azimuth_angle: mid_azimuth_angle,
+                upper_azimuth_angle: curr_azimuth_angle,
+                firing: &prev_block.channels[16..32],
+            };
+
+            Some(vec![former_firing, latter_firing])
+        })
+        .flatten()
+        .flat_map(|firing_info| {
+            let FiringInfo {
+                lower_timestamp,
+                lower_azimuth_angle,
+                upper_azimuth_angle,
+                firing,
+            } = firing_info;
+
+            debug_assert_eq!(firing.len(), 16);
+            debug_assert!(lower_azimuth_angle <= upper_azimuth_angle);
+
+            izip!(firing.iter(), lasers.iter(), 0..).enumerate().map(
+                move |(channel_idx, (channel, laser_params, laser_id))| {
+                    let timestamp = lower_timestamp + channel_period * channel_idx as f64;
+                    let ratio = channel_period * channel_idx as f64 / firing_period;
+                    let LaserParameter { azimuth_offset, .. } = laser_params;
+
+                    // clockwise angle with origin points to front of sensor
+                    let original_azimuth_angle = {
+                        let mut azimuth = lower_azimuth_angle
+                            + Angle::from((upper_azimuth_angle - lower_azimuth_angle) * ratio);
+                        if azimuth >= Angle::new::<radian>(std::f64::consts::PI * 2.0) {
+                            azimuth -= Angle::new::<radian>(std::f64::consts::PI * 2.0);
+                        }
+                        azimuth
+                    };
+                    let corrected_azimuth_angle = {
+                        let mut azimuth = original_azimuth_angle + *azimuth_offset;
+                        if azimuth >= Angle::new::<radian>(std::f64::consts::PI * 2.0) {
+                            azimuth -= Angle::new::<radian>(std::f64::consts::PI * 2.0);
+                        }
+                        azimuth
+                    };
+                    let distance = options.decode_distance(channel.distance);
+                    let position = match direction_cache {
+                        Some(cache) => {
+                            cache.position(laser_id as usize, distance, corrected_azimuth_angle)
+                        }
+                        // computed in batch after all points are collected
+                        None => [Length::new::<meter>(0.0); 3],
+                    };
+
+                    SingleReturnPoint {
+                        laser_id,
+                        timestamp,
+                        firing_time_offset: channel_period * channel_idx as f64,
+                        original_azimuth_angle,
+                        corrected_azimuth_angle,
+                        data: PointData {
+                            distance,
+                            intensity: channel.intensity,
+                            intensity_value: channel.intensity as f64,
+                            position,
+                            suspect: false,
+                        },
+                        lidar_frame_entry: LidarFrameEntry {
+                            row_idx: 0,
+                            col_idx: 0,
+                        },
+                        return_kind,
                     }
-                    azimuth
-                };
-                let distance = distance_resolution * channel.distance as f64;
-                let position = compute_position(
-                    distance,
-                    *elevation_angle,
-                    corrected_azimuth_angle,
-                    *vertical_offset,
-                    *horizontal_offset,
-                );
-
-                SingleReturnPoint {
-                    laser_id,
-                    timestamp,
-                    original_azimuth_angle,
-                    corrected_azimuth_angle,
-                    data: PointData {
-                        distance,
-                        intensity: channel.intensity,
-                        position,
-                    },
-                    lidar_frame_entry: LidarFrameEntry {
-                        row_idx: std::usize::MIN,
-                        col_idx: std::usize::MIN,
-                    },
-                }
-            },
-        )
-    })
-    .collect()
+                },
+            )
+        })
+        .collect();
+
+    if direction_cache.is_none() {
+        compute_positions(lasers, &mut points);
+    }
+    points
 }
 
 pub(crate) fn convert_to_points_32_channel<'a, I>(
     lasers: &[LaserParameter; 32],
-    distance_resolution: Length,
+    options: &ConverterOptions,
+    return_kind: ReturnKind,
     iter: &mut I,
 ) -> Vec<SingleReturnPoint>
 where
     I: Iterator<Item = (Time, &'a Block)>,
 {
+    let direction_cache = &options.direction_cache;
     let channel_period = Time::new::<microsecond>(CHANNEL_PERIOD);
     let firing_period = Time::new::<microsecond>(FIRING_PERIOD);
 
     let first_item = iter.next().unwrap();
-    iter.scan(first_item, |prev_pair, (curr_timestamp, curr_block)| {
-        let (prev_timestamp, prev_block) = *prev_pair;
-        *prev_pair = (curr_timestamp, curr_block);
-
-        let prev_azimuth_angle = prev_block.azimuth_angle();
-        let curr_azimuth_angle = {
-            let curr_angle = curr_block.azimuth_angle();
-            // fix roll-over case
-            if curr_angle < prev_azimuth_angle {
-                curr_angle + Angle::new::<radian>(std::f64::consts::PI * 2.0)
-            } else {
-                curr_angle
-            }
-        };
-
-        let firing_info = FiringInfo {
-            lower_timestamp: prev_timestamp,
-            lower_azimuth_angle: prev_azimuth_angle,
-            upper_azimuth_angle: curr_azimuth_angle,
-            firing: &prev_block.channels,
-        };
-        Some(firing_info)
-    })
-    .flat_map(|firing_info| {
-        let FiringInfo {
-            lower_timestamp,
-            lower_azimuth_angle,
-            upper_azimuth_angle,
-            firing,
-        } = firing_info;
-
-        debug_assert_eq!(firing.len(), 32);
-
-        izip!(firing.iter(), lasers.iter(), 0..).enumerate().map(
-            move |(channel_idx, (channel, laser_params, laser_id))| {
-                let timestamp = lower_timestamp + channel_period * (channel_idx / 2) as f64;
-                let ratio: Ratio = channel_period * (channel_idx / 2) as f64 / firing_period;
-                let LaserParameter {
-                    elevation_angle,
-                    azimuth_offset,
-                    vertical_offset,
-                    horizontal_offset,
-                } = laser_params;
-
-                // clockwise angle with origin points to front of sensor
-                let original_azimuth_angle = {
-                    let mut azimuth = lower_azimuth_angle
-                        + Angle::from((upper_azimuth_angle - lower_azimuth_angle) * ratio);
-                    if azimuth >= Angle::new::<radian>(std::f64::consts::PI * 2.0) {
-                        azimuth -= Angle::new::<radian>(std::f64::consts::PI * 2.0);
-                    }
-                    azimuth
-                };
-                let corrected_azimuth_angle = {
-                    let mut azimuth = original_azimuth_angle + *azimuth_offset;
-                    if azimuth >= Angle::new::<radian>(std::f64::consts::PI * 2.0) {
-                        azimuth -= Angle::new::<radian>(std::f64::consts::PI * 2.0);
-                    }
-                    azimuth
-                };
-                let distance = distance_resolution * channel.distance as f64;
-                let position = compute_position(
-                    distance,
-                    *elevation_angle,
-                    corrected_azimuth_angle,
-                    *vertical_offset,
-                    *horizontal_offset,
-                );
-
-                SingleReturnPoint {
-                    laser_id,
-                    timestamp,
-                    original_azimuth_angle,
-                    corrected_azimuth_angle,
-                    data: PointData {
-                        distance,
-                        intensity: channel.intensity,
-                        position,
-                    },
-                    lidar_frame_entry: LidarFrameEntry {
-                        row_idx: std::usize::MIN,
-                        col_idx: std::usize::MIN,
-                    },
+    let mut points: Vec<_> = iter
+        .scan(first_item, |prev_pair, (curr_timestamp, curr_block)| {
+            let (prev_timestamp, prev_block) = *prev_pair;
+            *prev_pair = (curr_timestamp, curr_block);
+
+            let prev_azimuth_angle = prev_block.azimuth_angle();
+            let curr_azimuth_angle = {
+                let curr_angle = curr_block.azimuth_angle();
+                // fix roll-over case
+                if curr_angle < prev_azimuth_angle {
+                    curr_angle + Angle::new::<radian>(std::f64::consts::PI * 2.0)
+                } else {
+                    curr_angle
                 }
-            },
-        )
-    })
-    .collect::<Vec<_>>()
+            };
+
+            let firing_info = FiringInfo {
+                lower_timestamp: prev_timestamp,
+                lower_azimuth_angle: prev_azimuth_angle,
+                upper_azimuth_angle: curr_azimuth_angle,
+                firing: &prev_block.channels,
+            };
+            Some(firing_info)
+        })
+        .flat_map(|firing_info| {
+            let FiringInfo {
+                lower_timestamp,
+                lower_azimuth_angle,
+                upper_azimuth_angle,
+                firing,
+            } = firing_info;
+
+            debug_assert_eq!(firing.len(), 32);
+
+            izip!(firing.iter(), lasers.iter(), 0..).enumerate().map(
+                move |(channel_idx, (channel, laser_params, laser_id))| {
+                    let timestamp = lower_timestamp + channel_period * (channel_idx / 2) as f64;
+                    let ratio: Ratio = channel_period * (channel_idx / 2) as f64 / firing_period;
+                    let LaserParameter { azimuth_offset, .. } = laser_params;
+
+                    // clockwise angle with origin points to front of sensor
+                    let original_azimuth_angle = {
+                        let mut azimuth = lower_azimuth_angle
+                            + Angle::from((upper_azimuth_angle - lower_azimuth_angle) * ratio);
+                        if azimuth >= Angle::new::<radian>(std::f64::consts::PI * 2.0) {
+                            azimuth -= Angle::new::<radian>(std::f64::consts::PI * 2.0);
+                        }
+                        azimuth
+                    };
+                    let corrected_azimuth_angle = {
+                        let mut azimuth = original_azimuth_angle + *azimuth_offset;
+                        if azimuth >= Angle::new::<radian>(std::f64::consts::PI * 2.0) {
+                            azimuth -= Angle::new::<radian>(std::f64::consts::PI * 2.0);
+                        }
+                        azimuth
+                    };
+                    let distance = options.decode_distance(channel.distance);
+                    let position = match direction_cache {
+                        Some(cache) => {
+                            cache.position(laser_id as usize, distance, corrected_azimuth_angle)
+                        }
+                        // computed in batch after all points are collected
+                        None => [Length::new::<meter>(0.0); 3],
+                    };
+
+                    SingleReturnPoint {
+                        laser_id,
+                        timestamp,
+                        firing_time_offset: channel_period * (channel_idx / 2) as f64,
+                        original_azimuth_angle,
+                        corrected_azimuth_angle,
+                        data: PointData {
+                            distance,
+                            intensity: channel.intensity,
+                            intensity_value: channel.intensity as f64,
+                            position,
+                            suspect: false,
+                        },
+                        lidar_frame_entry: LidarFrameEntry {
+                            row_idx: 0,
+                            col_idx: 0,
+                        },
+                        return_kind,
+                    }
+                },
+            )
+        })
+        .collect();
+
+    if direction_cache.is_none() {
+        compute_positions(lasers, &mut points);
+    }
+    points
+}
+
+/// Computes the positions of points from their distances and corrected azimuth angles.
+#[cfg(not(feature = "simd"))]
+fn compute_positions(lasers: &[LaserParameter], points: &mut [SingleReturnPoint]) {
+    points.iter_mut().for_each(|point| {
+        point.data.position = compute_point_position(lasers, point);
+    });
+}
+
+/// Computes the positions of points from their distances and corrected azimuth angles.
+///
+/// Four channels are processed at once with SIMD instructions.
+#[cfg(feature = "simd")]
+fn compute_positions(lasers: &[LaserParameter], points: &mut [SingleReturnPoint]) {
+    super::simd::compute_positions(lasers, points);
+}
+
+pub(crate) fn compute_point_position(
+    lasers: &[LaserParameter],
+    point: &SingleReturnPoint,
+) -> [Length; 3] {
+    let LaserParameter {
+        elevation_angle,
+        vertical_offset,
+        horizontal_offset,
+        ..
+    } = lasers[point.laser_id as usize];
+    compute_position(
+        point.data.distance,
+        elevation_angle,
+        point.corrected_azimuth_angle,
+        vertical_offset,
+        horizontal_offset,
+    )
 }
 
 fn compute_position(
@@ -1,7 +1,13 @@
 //! Velodyne point cloud converter that converts a packet into a point cloud.
 
 // pub mod context;
+mod cache;
 mod converter;
 mod impls;
+mod options;
+#[cfg(feature = "simd")]
+mod simd;
 
+pub use cache::*;
 pub use converter::*;
+pub use options::*;
@@ -0,0 +1,143 @@
+//! Options shared by all variants of point cloud converters.
+
+use super::{
+    cache::DirectionCache,
+    impls::{FlipElevation, OffsetAzimuth, RemapRows},
+};
+use crate::{
+    common::*,
+    diagnostic::{Diagnostic, ValidationLevel},
+    velodyne::{
+        config::Config,
+        intensity::{ApplyIntensityMode, CalibrateIntensity, IntensityCalibration, IntensityMode},
+        marker::{ModelMarker, ReturnTypeMarker},
+        mask::{MaskPoints, SelfReturnMask},
+        temperature::TemperatureCorrection,
+    },
+};
+
+/// The decoding options and corrections of a point cloud converter.
+///
+/// They are initialized from [Config] and updated by the setters of
+/// [PointCloudConverter](super::PointCloudConverter). The collected diagnostics are kept here
+/// as well, so that every converter holds a single copy of its settings.
+#[derive(Debug, Clone)]
+pub struct ConverterOptions {
+    pub(crate) distance_resolution: Length,
+    pub(crate) elevation_index: Option<Vec<usize>>,
+    pub(crate) intensity_mode: IntensityMode,
+    pub(crate) azimuth_zero_offset: Angle,
+    pub(crate) flip_elevation_sign: bool,
+    pub(crate) range_quantum: Option<Length>,
+    pub(crate) diagnostics: Option<Vec<Diagnostic>>,
+    pub(crate) self_return_mask: Option<SelfReturnMask>,
+    pub(crate) temperature_correction: Option<TemperatureCorrection>,
+    pub(crate) intensity_calibration: Option<IntensityCalibration>,
+    pub(crate) direction_cache: Option<DirectionCache>,
+    pub(crate) validation_level: ValidationLevel,
+}
+
+impl ConverterOptions {
+    pub(crate) fn from_config<Model, ReturnType>(config: &Config<Model, ReturnType>) -> Self
+    where
+        Model: ModelMarker,
+        ReturnType: ReturnTypeMarker,
+    {
+        let distance_resolution = config.effective_distance_resolution();
+        let Config {
+            ref elevation_index,
+            ref intensity_mode,
+            azimuth_zero_offset,
+            flip_elevation_sign,
+            range_quantum,
+            ..
+        } = *config;
+
+        Self {
+            distance_resolution,
+            elevation_index: elevation_index.clone(),
+            intensity_mode: intensity_mode.clone(),
+            azimuth_zero_offset,
+            flip_elevation_sign,
+            range_quantum,
+            diagnostics: None,
+            self_return_mask: None,
+            temperature_correction: None,
+            intensity_calibration: None,
+            direction_cache: None,
+            validation_level: ValidationLevel::default(),
+        }
+    }
+
+    /// Scales a raw distance, and then applies the temperature correction and the range
+    /// quantum in order. The positions are computed from the decoded distance.
+    pub(crate) fn decode_distance(&self, raw_distance: u16) -> Length {
+        let distance = self.distance_resolution * raw_distance as f64;
+
+        // zero distance indicates no return
+        if raw_distance == 0 {
+            return distance;
+        }
+
+        let distance = match &self.temperature_correction {
+            Some(correction) => distance + correction.range_offset(),
+            None => distance,
+        };
+        match self.range_quantum {
+            Some(quantum) => quantum * (distance / quantum).value.round(),
+            None => distance,
+        }
+    }
+}
+
+/// Collections of converted points that can be post-processed.
+pub(crate) trait PostProcess:
+    RemapRows + CalibrateIntensity + ApplyIntensityMode + OffsetAzimuth + FlipElevation + MaskPoints
+{
+}
+
+impl<T> PostProcess for T where
+    T: RemapRows
+        + CalibrateIntensity
+        + ApplyIntensityMode
+        + OffsetAzimuth
+        + FlipElevation
+        + MaskPoints
+{
+}
+
+/// Applies the corrections, the mask and the transforms to converted points in order.
+///
+/// The distance corrections are applied in decoding, see [ConverterOptions::decode_distance].
+pub(crate) fn post_process<T>(options: &ConverterOptions, points: &mut T)
+where
+    T: PostProcess,
+{
+    let ConverterOptions {
+        ref elevation_index,
+        ref intensity_mode,
+        azimuth_zero_offset,
+        flip_elevation_sign,
+        ref self_return_mask,
+        ref intensity_calibration,
+        ..
+    } = *options;
+
+    if let Some(elevation_index) = elevation_index {
+        points.remap_rows(elevation_index);
+    }
+    if let Some(calibration) = intensity_calibration {
+        points.calibrate_intensity(calibration);
+    }
+    points.apply_intensity_mode(intensity_mode);
+    // the mask windows are in the sensor frame
+    if let Some(mask) = self_return_mask {
+        points.mask(mask);
+    }
+    if azimuth_zero_offset != Angle::new::<radian>(0.0) {
+        points.offset_azimuth(azimuth_zero_offset);
+    }
+    if flip_elevation_sign {
+        points.flip_elevation();
+    }
+}
@@ -0,0 +1,127 @@
+//! SIMD computation of point positions.
+
+use super::impls::compute_point_position;
+use crate::{
+    common::*,
+    velodyne::{config::LaserParameter, point::SingleReturnPoint},
+};
+use wide::f64x4;
+
+const LANES: usize = 4;
+
+/// Computes the positions of points, four channels at a time.
+///
+/// The trailing points that do not fill up the lanes fall back to the scalar computation.
+pub(crate) fn compute_positions(lasers: &[LaserParameter], points: &mut [SingleReturnPoint]) {
+    let mut chunks = points.chunks_exact_mut(LANES);
+
+    for chunk in &mut chunks {
+        let mut distance = [0.0; LANES];
+        let mut elevation_angle = [0.0; LANES];
+        let mut azimuth_angle = [0.0; LANES];
+        let mut vertical_offset = [0.0; LANES];
+        let mut horizontal_offset = [0.0; LANES];
+
+        chunk.iter().enumerate().for_each(|(lane, point)| {
+            let laser = &lasers[point.laser_id as usize];
+            distance[lane] = point.data.distance.get::<meter>();
+            elevation_angle[lane] = laser.elevation_angle.get::<radian>();
+            azimuth_angle[lane] = point.corrected_azimuth_angle.get::<radian>();
+            vertical_offset[lane] = laser.vertical_offset.get::<meter>();
+            horizontal_offset[lane] = laser.horizontal_offset.get::<meter>();
+        });
+
+        let distance = f64x4::from(distance);
+        let vertical_offset = f64x4::from(vertical_offset);
+        let horizontal_offset = f64x4::from(horizontal_offset);
+        let (elevation_sin, elevation_cos) = f64x4::from(elevation_angle).sin_cos();
+        let (azimuth_sin, azimuth_cos) = f64x4::from(azimuth_angle).sin_cos();
+
+        // same as the scalar compute_position()
+        let distance_plane = distance * elevation_cos - vertical_offset * elevation_sin;
+        let x = (distance_plane * azimuth_sin - horizontal_offset * azimuth_cos).to_array();
+        let y = (distance_plane * azimuth_cos + horizontal_offset * azimuth_sin).to_array();
+        let z = (distance * elevation_sin + vertical_offset * elevation_cos).to_array();
+
+        chunk.iter_mut().enumerate().for_each(|(lane, point)| {
+            point.data.position = [
+                Length::new::<meter>(x[lane]),
+                Length::new::<meter>(y[lane]),
+                Length::new::<meter>(z[lane]),
+            ];
+        });
+    }
+
+    chunks.into_remainder().iter_mut().for_each(|point| {
+        point.data.position = compute_point_position(lasers, point);
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::velodyne::{
+        config::Config,
+        consts::BLOCKS_PER_PACKET,
+        packet::{DataPacket, ProductID},
+        pcd_converter::{PointCloudConverter, Vlp32_Strongest_PcdConverter},
+        test_support::PacketBuilder,
+    };
+
+    fn new_packet(packet_idx: usize) -> DataPacket {
+        let mut packet = PacketBuilder::new()
+            .product_id(ProductID::VLP32C)
+            .timestamp(packet_idx as u32 * 553)
+            .azimuth_counts(packet_idx * BLOCKS_PER_PACKET * 3271, 3271)
+            .build();
+        packet
+            .blocks
+            .iter_mut()
+            .enumerate()
+            .for_each(|(block_idx, block)| {
+                let firing_idx = packet_idx * BLOCKS_PER_PACKET + block_idx;
+                block
+                    .channels
+                    .iter_mut()
+                    .enumerate()
+                    .for_each(|(channel_idx, channel)| {
+                        channel.distance = ((firing_idx * 97 + channel_idx * 1013) % 60000) as u16;
+                    });
+            });
+        packet
+    }
+
+    #[test]
+    fn simd_position_test() -> Result<()> {
+        let config = Config::vlp_32c_strongest_return();
+        let lasers = config.lasers.clone();
+        let mut converter = Vlp32_Strongest_PcdConverter::from_config(config);
+
+        let mut points = vec![];
+        for packet_idx in 0..4 {
+            points.extend(converter.convert(new_packet(packet_idx))?);
+        }
+        assert!(!points.is_empty());
+
+        // include trailing points which are not a multiple of lanes
+        let mut partial = points[..7].to_vec();
+        partial
+            .iter_mut()
+            .for_each(|point| point.data.position = [Length::new::<meter>(0.0); 3]);
+        compute_positions(&lasers, &mut partial);
+
+        points.iter().chain(partial.iter()).for_each(|point| {
+            let expect = compute_point_position(&lasers, point);
+            point
+                .data
+                .position
+                .iter()
+                .zip(expect.iter())
+                .for_each(|(lhs, rhs)| {
+                    assert!((lhs.get::<meter>() - rhs.get::<meter>()).abs() <= 1e-9);
+                });
+        });
+
+        Ok(())
+    }
+}
@@ -0,0 +1,277 @@
+//! A conversion pipeline from packets to filtered frames.
+
+use super::{
+    config::{Config, Dynamic_Config},
+    frame_converter::{Dynamic_FrameConverter, FrameConverter, PcdFrame},
+    fusion::Extrinsic,
+    marker::DynamicModel,
+    packet::{DataPacket, ReturnMode},
+    point::{DynamicReturnFrame, PrimaryData},
+};
+use crate::common::*;
+
+/// Builds a [Pipeline]. The model and return type are required.
+#[derive(Debug, Clone, Default)]
+pub struct PipelineBuilder {
+    model: Option<DynamicModel>,
+    return_type: Option<ReturnMode>,
+    range_filter: Option<Range<Length>>,
+    extrinsic: Option<Extrinsic>,
+}
+
+impl PipelineBuilder {
+    /// Sets the sensor model. Only [DynamicModel::Vlp16] and [DynamicModel::Vlp32]
+    /// are supported, since other models require calibration parameters.
+    pub fn model(mut self, model: DynamicModel) -> Self {
+        self.model = Some(model);
+        self
+    }
+
+    /// Sets the return mode configured on the sensor.
+    pub fn return_type(mut self, return_type: ReturnMode) -> Self {
+        self.return_type = Some(return_type);
+        self
+    }
+
+    /// Keeps the points with distance within the range, and marks the others as invalid.
+    pub fn range_filter(mut self, range: Range<Length>) -> Self {
+        self.range_filter = Some(range);
+        self
+    }
+
+    /// Transforms the valid returns from the sensor frame by the extrinsic.
+    pub fn extrinsic(mut self, extrinsic: Extrinsic) -> Self {
+        self.extrinsic = Some(extrinsic);
+        self
+    }
+
+    /// Creates the pipeline. It fails if the model or the return type is not set,
+    /// or the model is not supported.
+    pub fn build(self) -> Result<Pipeline> {
+        let Self {
+            model,
+            return_type,
+            range_filter,
+            extrinsic,
+        } = self;
+        let model = model.ok_or_else(|| format_err!("the model is not set"))?;
+        let return_type = return_type.ok_or_else(|| format_err!("the return type is not set"))?;
+
+        let config: Dynamic_Config = match model {
+            DynamicModel::Vlp16 => Config::vlp_16_dynamic_return(return_type).into_dyn(),
+            DynamicModel::Vlp32 => Config::vlp_32c_dynamic_return(return_type).into_dyn(),
+            DynamicModel::Hdl64 | DynamicModel::Custom(_) => {
+                bail!("the model {:?} is not supported by pipeline", model)
+            }
+        };
+
+        Ok(Pipeline {
+            converter: Dynamic_FrameConverter::from_config(config),
+            range_filter,
+            extrinsic,
+        })
+    }
+}
+
+/// Converts packets into frames, and applies the range filter and the extrinsic in order.
+///
+/// ```no_run
+/// use lidar_utils::velodyne::{DynamicModel, Extrinsic, Pipeline, ReturnMode};
+/// use uom::si::{f64::Length, length::meter};
+///
+/// let mut pipeline = Pipeline::builder()
+///     .model(DynamicModel::Vlp16)
+///     .return_type(ReturnMode::StrongestReturn)
+///     .range_filter(Length::new::<meter>(0.5)..Length::new::<meter>(100.0))
+///     .extrinsic(Extrinsic::identity())
+///     .build()?;
+/// # Ok::<_, anyhow::Error>(())
+/// ```
+#[derive(Debug)]
+pub struct Pipeline {
+    converter: Dynamic_FrameConverter,
+    range_filter: Option<Range<Length>>,
+    extrinsic: Option<Extrinsic>,
+}
+
+impl Pipeline {
+    pub fn builder() -> PipelineBuilder {
+        PipelineBuilder::default()
+    }
+
+    /// Processes a packet, and returns a frame if a rotation is completed.
+    pub fn process<P>(&mut self, packet: P) -> Option<DynamicReturnFrame>
+    where
+        P: Borrow<DataPacket>,
+    {
+        let frame = self.converter.convert(packet)?;
+        Some(self.post_process(frame))
+    }
+
+    /// Flushes the remaining points into a partial frame, which is typically called at end of input.
+    pub fn finish(&mut self) -> Option<DynamicReturnFrame> {
+        let frame = self.converter.finish()?;
+        Some(self.post_process(frame))
+    }
+
+    fn post_process(&self, frame: DynamicReturnFrame) -> DynamicReturnFrame {
+        match frame {
+            DynamicReturnFrame::Single(frame) => DynamicReturnFrame::Single(self.filter(frame)),
+            DynamicReturnFrame::Dual(frame) => DynamicReturnFrame::Dual(self.filter(frame)),
+        }
+    }
+
+    fn filter<P>(&self, frame: PcdFrame<P>) -> PcdFrame<P>
+    where
+        P: Copy + PrimaryData,
+    {
+        let mut frame = match &self.range_filter {
+            Some(range) => frame.mask_range(range.clone()),
+            None => frame,
+        };
+        if let Some(extrinsic) = &self.extrinsic {
+            frame.data.iter_mut().for_each(|point| {
+                point.for_each_return_mut(|data| {
+                    if data.distance > Length::new::<meter>(0.0) {
+                        data.position = extrinsic.transform(data.position);
+                    }
+                })
+            });
+        }
+        frame
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::velodyne::{
+        consts::BLOCKS_PER_PACKET, point::PointData, test_support::PacketBuilder,
+    };
+
+    #[test]
+    fn pipeline_test() -> Result<()> {
+        let mut pipeline = Pipeline::builder()
+            .model(DynamicModel::Vlp16)
+            .return_type(ReturnMode::StrongestReturn)
+            .range_filter(Length::new::<meter>(0.5)..Length::new::<meter>(2.0))
+            .extrinsic(Extrinsic::from_translation([
+                Length::new::<meter>(0.0),
+                Length::new::<meter>(0.0),
+                Length::new::<meter>(1.5),
+            ]))
+            .build()?;
+
+        // the laser 0 in both firings of each block is beyond the range filter
+        let packets = (0..100u32).map(|packet_idx| {
+            let mut packet = PacketBuilder::new()
+                .timestamp(packet_idx * 1000)
+                .channels(500, 0)
+                .azimuth_counts(packet_idx as usize * BLOCKS_PER_PACKET * 40, 40)
+                .build();
+            packet.blocks.iter_mut().for_each(|block| {
+                block.channels[0].distance = 2000;
+                block.channels[16].distance = 2000;
+            });
+            packet
+        });
+
+        let frame = packets
+            .filter_map(|packet| pipeline.process(packet))
+            .next()
+            .unwrap();
+        let frame = frame.into_single().unwrap();
+        assert_eq!(frame.height, 16);
+        assert!(frame.width > 0);
+
+        // the laser 0 is masked and the others are lifted by 1.5 meters
+        frame.data.iter().for_each(|point| {
+            let [x, y, z] = point.data.position;
+            if point.laser_id == 0 {
+                assert_eq!(point.data.distance, Length::new::<meter>(0.0));
+                assert_eq!(point.data.position, [Length::new::<meter>(0.0); 3]);
+            } else {
+                assert_eq!(point.data.distance, Length::new::<meter>(1.0));
+                let [x, y, z] = [x.get::<meter>(), y.get::<meter>(), z.get::<meter>() - 1.5];
+                assert!(((x * x + y * y + z * z).sqrt() - 1.0).abs() < 0.05);
+            }
+        });
+        assert!(pipeline.finish().is_some());
+
+        // both returns are filtered and transformed in dual return mode
+        let mut pipeline = Pipeline::builder()
+            .model(DynamicModel::Vlp16)
+            .return_type(ReturnMode::DualReturn)
+            .range_filter(Length::new::<meter>(0.5)..Length::new::<meter>(2.0))
+            .extrinsic(Extrinsic::from_translation([
+                Length::new::<meter>(0.0),
+                Length::new::<meter>(0.0),
+                Length::new::<meter>(1.5),
+            ]))
+            .build()?;
+
+        // the last return of laser 0 is beyond the range filter
+        let packets = (0..200u32).map(|packet_idx| {
+            let mut packet = PacketBuilder::new()
+                .return_mode(ReturnMode::DualReturn)
+                .timestamp(packet_idx * 1000)
+                .channels(500, 0)
+                .build();
+            packet
+                .blocks
+                .iter_mut()
+                .enumerate()
+                .for_each(|(block_idx, block)| {
+                    let firing_idx = packet_idx as usize * BLOCKS_PER_PACKET / 2 + block_idx / 2;
+                    block.azimuth_count = (firing_idx * 40 % 36000) as u16;
+                    if block_idx % 2 == 1 {
+                        block
+                            .channels
+                            .iter_mut()
+                            .for_each(|channel| channel.distance = 750);
+                        block.channels[0].distance = 2000;
+                        block.channels[16].distance = 2000;
+                    }
+                });
+            packet
+        });
+
+        let frame = packets
+            .filter_map(|packet| pipeline.process(packet))
+            .next()
+            .unwrap();
+        let frame = frame.into_dual().unwrap();
+        assert!(frame.width > 0);
+
+        let assert_lifted = |data: &PointData, distance: f64| {
+            assert_eq!(data.distance, Length::new::<meter>(distance));
+            let [x, y, z] = data.position;
+            let [x, y, z] = [x.get::<meter>(), y.get::<meter>(), z.get::<meter>() - 1.5];
+            assert!(((x * x + y * y + z * z).sqrt() - distance).abs() < 0.05);
+        };
+        frame.data.iter().for_each(|point| {
+            assert_lifted(&point.strongest_return_data, 1.0);
+            if point.laser_id == 0 {
+                assert_eq!(point.last_return_data.distance, Length::new::<meter>(0.0));
+                assert_eq!(
+                    point.last_return_data.position,
+                    [Length::new::<meter>(0.0); 3]
+                );
+            } else {
+                assert_lifted(&point.last_return_data, 1.5);
+            }
+        });
+
+        assert!(Pipeline::builder()
+            .model(DynamicModel::Vlp16)
+            .build()
+            .is_err());
+        assert!(Pipeline::builder()
+            .model(DynamicModel::Hdl64)
+            .return_type(ReturnMode::StrongestReturn)
+            .build()
+            .is_err());
+
+        Ok(())
+    }
+}
@@ -1,5 +1,10 @@
 //! Point data types.
 
+use super::{
+    consts::{VLP_16_ELEVAION_INDEX, VLP_32C_ELEVAION_INDEX},
+    marker::{DynamicModel, ModelMarker},
+    packet::ReturnMode,
+};
 use crate::common::*;
 
 pub use dual_return_point::*;
@@ -9,18 +14,167 @@ pub use single_return_point::*;
 /// Generic point from Velodyne LiDAR device.
 
 pub trait VelodynePoint {
+    /// Returns the laser ID.
     fn laser_id(&self) -> u32;
+    /// Returns the time when the laser fired.
     fn timestamp(&self) -> Time;
+    /// Returns the azimuth angle interpolated at the firing time, before the azimuth offset
+    /// of the laser is added.
+    ///
+    /// It can be used together with [firing_time_offset](VelodynePoint::firing_time_offset)
+    /// to deskew the point for sensor motion.
     fn original_azimuth_angle(&self) -> Angle;
+    /// Returns the azimuth angle where the point is projected.
     fn corrected_azimuth_angle(&self) -> Angle;
+    /// Returns the time elapsed from the start of the firing sequence to the laser firing.
+    fn firing_time_offset(&self) -> Time;
 }
 
 /// Point in strongest or last return mode.
 #[derive(Debug, Clone, Copy)]
 pub struct PointData {
     pub distance: Length,
+    /// The reflectivity byte, which is corrected by the intensity calibration if set.
     pub intensity: u8,
+    /// The intensity reported in the [IntensityMode](super::intensity::IntensityMode) of the config.
+    pub intensity_value: f64,
     pub position: [Length; 3],
+    /// Whether the return is likely an artifact, such as the near-field crosstalk flagged
+    /// by [NearFieldClassifier](super::near_field::NearFieldClassifier).
+    pub suspect: bool,
+}
+
+/// Indicates which return a point comes from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ReturnKind {
+    Strongest,
+    Last,
+    /// The point carries both the strongest and the last returns.
+    Both,
+}
+
+impl From<ReturnMode> for ReturnKind {
+    fn from(mode: ReturnMode) -> Self {
+        match mode {
+            ReturnMode::StrongestReturn => Self::Strongest,
+            ReturnMode::LastReturn => Self::Last,
+            ReturnMode::DualReturn => Self::Both,
+        }
+    }
+}
+
+/// Points that can be rotated about the Z axis.
+pub trait RotateAzimuth {
+    /// Rotates the position counter-clockwise by `delta` viewed from above, and
+    /// decreases the clockwise azimuth angles accordingly.
+    fn rotate_azimuth(&mut self, delta: Angle);
+}
+
+/// Points that carry a primary [PointData].
+pub trait PrimaryData {
+    /// Returns the point data, which is the strongest return for dual return points.
+    fn primary_data(&self) -> &PointData;
+
+    /// Returns the mutable point data, which is the strongest return for dual return points.
+    fn primary_data_mut(&mut self) -> &mut PointData;
+
+    /// Visits the mutable data of every return, which are the strongest and the last
+    /// returns in order for dual return points.
+    fn for_each_return_mut<F>(&mut self, f: F)
+    where
+        F: FnMut(&mut PointData);
+}
+
+impl PointData {
+    fn rotate_azimuth(&mut self, delta: Angle) {
+        let [x, y, z] = self.position;
+        let (sin, cos) = delta.get::<radian>().sin_cos();
+        self.position = [x * cos - y * sin, x * sin + y * cos, z];
+    }
+}
+
+/// Wraps the angle into [0, 2π).
+fn wrap_azimuth(angle: Angle) -> Angle {
+    let radians = angle.get::<radian>().rem_euclid(std::f64::consts::PI * 2.0);
+    Angle::new::<radian>(radians)
+}
+
+/// Point in sensor-native cylindrical coordinates, which skips the Cartesian transform.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CylindricalPoint {
+    pub range: Length,
+    /// Raw encoder count of the block in 0.01 degrees. Firings within a block share the same bin.
+    pub azimuth_bin: u16,
+    /// Row index of the laser, which agrees with the row index of converted points.
+    pub ring: u8,
+    pub intensity: u8,
+}
+
+/// The row of a laser in frames, which is given by the elevation index table of the model.
+/// The table is indexed by laser ID, and it is the same table the converters assign row
+/// indices by, so that [LidarFrameMsg::ring] of converted points agrees with
+/// [Ring::from_firing_order].
+///
+/// It is distinguished from the laser ID, which is the firing order of the laser.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Ring(pub u16);
+
+impl Ring {
+    /// Converts the firing order, namely the laser ID, to the ring.
+    pub fn from_firing_order<Model>(model: Model, laser_id: usize) -> Result<Self>
+    where
+        Model: ModelMarker,
+    {
+        let table = Self::elevation_index(model.into_dynamic())?;
+        let ring = table
+            .get(laser_id)
+            .copied()
+            .ok_or_else(|| format_err!("the laser ID {} is out of range", laser_id))?;
+        Ok(Self(ring as u16))
+    }
+
+    /// Creates the ring from the elevation order.
+    pub fn from_elevation_order<Model>(model: Model, row_idx: usize) -> Result<Self>
+    where
+        Model: ModelMarker,
+    {
+        let table = Self::elevation_index(model.into_dynamic())?;
+        ensure!(
+            row_idx < table.len(),
+            "the elevation order {} is out of range",
+            row_idx
+        );
+        Ok(Self(row_idx as u16))
+    }
+
+    /// Converts the ring to the firing order, namely the laser ID.
+    pub fn firing_order<Model>(&self, model: Model) -> Result<usize>
+    where
+        Model: ModelMarker,
+    {
+        let table = Self::elevation_index(model.into_dynamic())?;
+        table
+            .iter()
+            .position(|&ring| ring == self.index())
+            .ok_or_else(|| format_err!("the ring {} is out of range", self.0))
+    }
+
+    /// Returns the elevation order as index.
+    pub fn index(&self) -> usize {
+        self.0 as usize
+    }
+
+    /// Returns the rings indexed by laser ID.
+    fn elevation_index(model: DynamicModel) -> Result<&'static [usize]> {
+        let table: &[usize] = match model {
+            DynamicModel::Vlp16 => &VLP_16_ELEVAION_INDEX,
+            DynamicModel::Vlp32 => &VLP_32C_ELEVAION_INDEX,
+            DynamicModel::Hdl64 | DynamicModel::Custom(_) => {
+                bail!("the elevation order of {:?} depends on calibration", model)
+            }
+        };
+        Ok(table)
+    }
 }
 
 pub trait LidarFrameMsg {
@@ -28,6 +182,11 @@ pub trait LidarFrameMsg {
     fn row_idx(&self) -> usize;
     fn set_col_idx(&mut self, id: usize);
     fn col_idx(&self) -> usize;
+
+    /// Returns the row index as [Ring].
+    fn ring(&self) -> Ring {
+        Ring(self.row_idx() as u16)
+    }
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -61,10 +220,14 @@ mod single_return_point {
     pub struct SingleReturnPoint {
         pub laser_id: u32,
         pub timestamp: Time,
+        /// The time elapsed from the start of the firing sequence to the laser firing.
+        pub firing_time_offset: Time,
+        /// The azimuth angle interpolated at the firing time, excluding the laser azimuth offset.
         pub original_azimuth_angle: Angle,
         pub corrected_azimuth_angle: Angle,
         pub data: PointData,
         pub lidar_frame_entry: LidarFrameEntry,
+        pub return_kind: ReturnKind,
     }
 
     impl VelodynePoint for SingleReturnPoint {
@@ -83,6 +246,35 @@ mod single_return_point {
         fn corrected_azimuth_angle(&self) -> Angle {
             self.corrected_azimuth_angle
         }
+
+        fn firing_time_offset(&self) -> Time {
+            self.firing_time_offset
+        }
+    }
+
+    impl RotateAzimuth for SingleReturnPoint {
+        fn rotate_azimuth(&mut self, delta: Angle) {
+            self.data.rotate_azimuth(delta);
+            self.original_azimuth_angle = wrap_azimuth(self.original_azimuth_angle - delta);
+            self.corrected_azimuth_angle = wrap_azimuth(self.corrected_azimuth_angle - delta);
+        }
+    }
+
+    impl PrimaryData for SingleReturnPoint {
+        fn primary_data(&self) -> &PointData {
+            &self.data
+        }
+
+        fn primary_data_mut(&mut self) -> &mut PointData {
+            &mut self.data
+        }
+
+        fn for_each_return_mut<F>(&mut self, mut f: F)
+        where
+            F: FnMut(&mut PointData),
+        {
+            f(&mut self.data);
+        }
     }
 
     impl LidarFrameMsg for SingleReturnPoint {
@@ -109,11 +301,15 @@ mod dual_return_point {
     pub struct DualReturnPoint {
         pub laser_id: u32,
         pub timestamp: Time,
+        /// The time elapsed from the start of the firing sequence to the laser firing.
+        pub firing_time_offset: Time,
+        /// The azimuth angle interpolated at the firing time, excluding the laser azimuth offset.
         pub original_azimuth_angle: Angle,
         pub corrected_azimuth_angle: Angle,
         pub strongest_return_data: PointData,
         pub last_return_data: PointData,
         pub lidar_frame_entry: LidarFrameEntry,
+        pub return_kind: ReturnKind,
     }
 
     impl DualReturnPoint {
@@ -124,10 +320,12 @@ mod dual_return_point {
             let SingleReturnPoint {
                 laser_id: laser_id_strongest,
                 timestamp: timestamp_strongest,
+                firing_time_offset,
                 original_azimuth_angle: original_azimuth_angle_strongest,
                 corrected_azimuth_angle: corrected_azimuth_angle_strongest,
                 data: strongest_return_data,
                 lidar_frame_entry,
+                ..
             } = strongest_return_point;
 
             let SingleReturnPoint {
@@ -137,6 +335,7 @@ mod dual_return_point {
                 corrected_azimuth_angle: corrected_azimuth_angle_last,
                 data: last_return_data,
                 lidar_frame_entry,
+                ..
             } = last_return_point;
 
             ensure!(
@@ -159,11 +358,13 @@ mod dual_return_point {
             let dual_return_point = DualReturnPoint {
                 laser_id: laser_id_strongest,
                 timestamp: timestamp_strongest,
+                firing_time_offset,
                 original_azimuth_angle: original_azimuth_angle_strongest,
                 corrected_azimuth_angle: corrected_azimuth_angle_strongest,
                 strongest_return_data,
                 last_return_data,
                 lidar_frame_entry,
+                return_kind: ReturnKind::Both,
             };
 
             Ok(dual_return_point)
@@ -186,6 +387,37 @@ mod dual_return_point {
         fn corrected_azimuth_angle(&self) -> Angle {
             self.corrected_azimuth_angle
         }
+
+        fn firing_time_offset(&self) -> Time {
+            self.firing_time_offset
+        }
+    }
+
+    impl RotateAzimuth for DualReturnPoint {
+        fn rotate_azimuth(&mut self, delta: Angle) {
+            self.strongest_return_data.rotate_azimuth(delta);
+            self.last_return_data.rotate_azimuth(delta);
+            self.original_azimuth_angle = wrap_azimuth(self.original_azimuth_angle - delta);
+            self.corrected_azimuth_angle = wrap_azimuth(self.corrected_azimuth_angle - delta);
+        }
+    }
+
+    impl PrimaryData for DualReturnPoint {
+        fn primary_data(&self) -> &PointData {
+            &self.strongest_return_data
+        }
+
+        fn primary_data_mut(&mut self) -> &mut PointData {
+            &mut self.strongest_return_data
+        }
+
+        fn for_each_return_mut<F>(&mut self, mut f: F)
+        where
+            F: FnMut(&mut PointData),
+        {
+            f(&mut self.strongest_return_data);
+            f(&mut self.last_return_data);
+        }
     }
 
     impl LidarFrameMsg for DualReturnPoint {
@@ -230,6 +462,46 @@ mod dynamic_return_points {
                 Self::Dual(_) => Self::Dual(PcdFrame::new()),
             }
         }
+
+        /// See [PcdFrame::spans_full_rotation].
+        pub fn spans_full_rotation(&self) -> bool {
+            match self {
+                Self::Single(frame) => frame.spans_full_rotation(),
+                Self::Dual(frame) => frame.spans_full_rotation(),
+            }
+        }
+
+        /// Returns the frame if it is in single return mode.
+        pub fn as_single(&self) -> Option<&PcdFrame<SingleReturnPoint>> {
+            match self {
+                Self::Single(frame) => Some(frame),
+                Self::Dual(_) => None,
+            }
+        }
+
+        /// Returns the frame if it is in dual return mode.
+        pub fn as_dual(&self) -> Option<&PcdFrame<DualReturnPoint>> {
+            match self {
+                Self::Single(_) => None,
+                Self::Dual(frame) => Some(frame),
+            }
+        }
+
+        /// Takes the frame if it is in single return mode.
+        pub fn into_single(self) -> Option<PcdFrame<SingleReturnPoint>> {
+            match self {
+                Self::Single(frame) => Some(frame),
+                Self::Dual(_) => None,
+            }
+        }
+
+        /// Takes the frame if it is in dual return mode.
+        pub fn into_dual(self) -> Option<PcdFrame<DualReturnPoint>> {
+            match self {
+                Self::Single(_) => None,
+                Self::Dual(frame) => Some(frame),
+            }
+        }
     }
 
     impl IntoIterator for DynamicReturnFrame {
@@ -352,3 +624,91 @@ mod dynamic_return_points {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::velodyne::{
+        config::Config,
+        marker::{Hdl64, Vlp16, Vlp32},
+        packet::ProductID,
+        pcd_converter::{
+            PointCloudConverter, Vlp16_Strongest_PcdConverter, Vlp32_Strongest_PcdConverter,
+        },
+        test_support::PacketBuilder,
+        PcdFrame,
+    };
+
+    #[test]
+    fn ring_test() -> Result<()> {
+        // the rings are looked up by laser ID in the elevation index table
+        let ring = Ring::from_firing_order(Vlp16, 0)?;
+        assert_eq!(ring, Ring(15));
+        assert_eq!(ring.firing_order(Vlp16)?, 0);
+        assert_eq!(Ring::from_firing_order(Vlp16, 1)?, Ring(13));
+        assert_eq!(Ring(13).firing_order(Vlp16)?, 1);
+        assert_eq!(
+            Ring::from_elevation_order(Vlp16, 0)?.firing_order(Vlp16)?,
+            15
+        );
+        for laser_id in 0..16 {
+            let ring = Ring::from_firing_order(Vlp16, laser_id)?;
+            assert_eq!(ring.firing_order(Vlp16)?, laser_id);
+        }
+        assert!(Ring::from_firing_order(Vlp16, 16).is_err());
+        assert!(Ring::from_elevation_order(Vlp16, 16).is_err());
+
+        assert_eq!(Ring::from_firing_order(Vlp32, 0)?, Ring(29));
+        assert_eq!(Ring(0).firing_order(Vlp32)?, 31);
+        assert!(Ring::from_firing_order(Hdl64, 0).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn converted_point_ring_test() -> Result<()> {
+        // every laser is in the ring given by its laser ID
+        let packet = PacketBuilder::new().channels(500, 7).build();
+        let points = Vlp16_Strongest_PcdConverter::from_config(Config::vlp_16_strongest_return())
+            .convert(packet)?;
+        assert!((0..16).all(|laser_id| points.iter().any(|point| point.laser_id == laser_id)));
+        for point in points.iter() {
+            assert_eq!(
+                point.ring(),
+                Ring::from_firing_order(Vlp16, point.laser_id as usize)?
+            );
+        }
+
+        let packet = PacketBuilder::new()
+            .product_id(ProductID::VLP32C)
+            .channels(500, 7)
+            .build();
+        let points = Vlp32_Strongest_PcdConverter::from_config(Config::vlp_32c_strongest_return())
+            .convert(packet)?;
+        assert!((0..32).all(|laser_id| points.iter().any(|point| point.laser_id == laser_id)));
+        for point in points.iter() {
+            assert_eq!(
+                point.ring(),
+                Ring::from_firing_order(Vlp32, point.laser_id as usize)?
+            );
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn dynamic_return_frame_downcast_test() {
+        let single = DynamicReturnFrame::Single(PcdFrame::new());
+        let dual = DynamicReturnFrame::Dual(PcdFrame::new());
+
+        assert!(single.as_single().is_some());
+        assert!(single.as_dual().is_none());
+        assert!(dual.as_single().is_none());
+        assert!(dual.as_dual().is_some());
+
+        assert!(single.clone().into_single().is_some());
+        assert!(single.into_dual().is_none());
+        assert!(dual.clone().into_single().is_none());
+        assert!(dual.into_dual().is_some());
+    }
+}
@@ -0,0 +1,234 @@
+//! Streaming quantile estimation of point ranges.
+
+use super::{
+    frame_converter::PcdFrame,
+    point::{PointData, PrimaryData},
+};
+use crate::common::*;
+
+#[derive(Debug, Clone, Copy)]
+struct Centroid {
+    mean: f64,
+    weight: f64,
+}
+
+/// Estimates the quantiles of ranges over a stream of frames without storing all points,
+/// for example, to auto-scale range images.
+///
+/// It implements the merging t-digest. Ranges are buffered and merged into a bounded number
+/// of centroids, which are kept small near the tails, so that extreme quantiles such as
+/// the 5th and 95th percentiles are accurate.
+#[derive(Debug, Clone)]
+pub struct StreamingQuantile {
+    compression: f64,
+    centroids: Vec<Centroid>,
+    buffer: Vec<f64>,
+    buffer_size: usize,
+    count: usize,
+    min: f64,
+    max: f64,
+}
+
+impl StreamingQuantile {
+    /// Creates an estimator with the compression factor, which roughly bounds the number of
+    /// centroids. Larger compression trades memory for accuracy.
+    ///
+    /// It fails if `compression` is less than 10.
+    pub fn new(compression: usize) -> Result<Self> {
+        ensure!(
+            compression >= 10,
+            "the compression must be at least 10, but get {}",
+            compression
+        );
+        Ok(Self {
+            compression: compression as f64,
+            centroids: vec![],
+            buffer: Vec::with_capacity(compression * 5),
+            buffer_size: compression * 5,
+            count: 0,
+            min: f64::INFINITY,
+            max: f64::NEG_INFINITY,
+        })
+    }
+
+    /// Adds a range. Non-finite ranges are ignored.
+    pub fn push(&mut self, range: Length) {
+        let range = range.get::<meter>();
+        if !range.is_finite() {
+            return;
+        }
+
+        self.buffer.push(range);
+        self.count += 1;
+        self.min = self.min.min(range);
+        self.max = self.max.max(range);
+
+        if self.buffer.len() >= self.buffer_size {
+            self.centroids = self.merged();
+            self.buffer.clear();
+        }
+    }
+
+    /// Adds the ranges of valid points in the frame. Points with zero distance are skipped.
+    pub fn push_frame<P>(&mut self, frame: &PcdFrame<P>)
+    where
+        P: Copy + PrimaryData,
+    {
+        frame
+            .data
+            .iter()
+            .map(|point| point.primary_data())
+            .filter(|data| data.distance > Length::new::<meter>(0.0))
+            .for_each(|&PointData { distance, .. }| self.push(distance));
+    }
+
+    /// Returns the number of added ranges.
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    /// Estimates the `q`-quantile of added ranges, or zero if no range is added.
+    ///
+    /// It panics if `q` is not within [0, 1].
+    pub fn quantile(&self, q: f64) -> Length {
+        assert!((0.0..=1.0).contains(&q), "q must be within [0, 1]");
+        if self.count == 0 {
+            return Length::new::<meter>(0.0);
+        }
+
+        let centroids = if self.buffer.is_empty() {
+            self.centroids.clone()
+        } else {
+            self.merged()
+        };
+        let total = self.count as f64;
+        let target = q * total;
+
+        // the weight of a centroid spreads evenly around its mean
+        let mut cumulative = 0.0;
+        let mut prev: Option<(f64, f64)> = None;
+        for centroid in centroids.iter() {
+            let center = cumulative + centroid.weight / 2.0;
+            if target < center {
+                let (prev_center, prev_mean) = prev.unwrap_or((0.0, self.min));
+                let ratio = (target - prev_center) / (center - prev_center);
+                let value = prev_mean + (centroid.mean - prev_mean) * ratio;
+                return Length::new::<meter>(value);
+            }
+            prev = Some((center, centroid.mean));
+            cumulative += centroid.weight;
+        }
+
+        let (prev_center, prev_mean) = prev.unwrap();
+        let value = if total > prev_center {
+            prev_mean + (self.max - prev_mean) * (target - prev_center) / (total - prev_center)
+        } else {
+            self.max
+        };
+        Length::new::<meter>(value)
+    }
+
+    /// Merges the buffered ranges into the centroids.
+    fn merged(&self) -> Vec<Centroid> {
+        let mut sorted: Vec<Centroid> = self
+            .centroids
+            .iter()
+            .cloned()
+            .chain(
+                self.buffer
+                    .iter()
+                    .map(|&mean| Centroid { mean, weight: 1.0 }),
+            )
+            .collect();
+        sorted.sort_by(|lhs, rhs| lhs.mean.partial_cmp(&rhs.mean).unwrap());
+
+        let total = self.count as f64;
+        let mut iter = sorted.into_iter();
+        let mut current = match iter.next() {
+            Some(centroid) => centroid,
+            None => return vec![],
+        };
+        let mut merged = vec![];
+        let mut weight_so_far = 0.0;
+        let mut weight_limit = total * self.k_inverse(self.k(0.0) + 1.0);
+
+        for centroid in iter {
+            if weight_so_far + current.weight + centroid.weight <= weight_limit {
+                let weight = current.weight + centroid.weight;
+                current.mean += (centroid.mean - current.mean) * centroid.weight / weight;
+                current.weight = weight;
+            } else {
+                weight_so_far += current.weight;
+                weight_limit = total * self.k_inverse(self.k(weight_so_far / total) + 1.0);
+                merged.push(current);
+                current = centroid;
+            }
+        }
+        merged.push(current);
+        merged
+    }
+
+    /// The scale function that maps a quantile to the centroid index.
+    fn k(&self, q: f64) -> f64 {
+        self.compression / (2.0 * std::f64::consts::PI) * (2.0 * q - 1.0).asin()
+    }
+
+    fn k_inverse(&self, k: f64) -> f64 {
+        let k = k.min(self.compression / 4.0);
+        ((2.0 * std::f64::consts::PI * k / self.compression).sin() + 1.0) / 2.0
+    }
+}
+
+impl Default for StreamingQuantile {
+    fn default() -> Self {
+        Self::new(100).unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn streaming_quantile_test() -> Result<()> {
+        let mut estimator = StreamingQuantile::default();
+        assert_eq!(estimator.quantile(0.5), Length::new::<meter>(0.0));
+
+        // a skewed distribution from a linear congruential generator
+        let mut state: u64 = 12345;
+        let mut ranges: Vec<f64> = (0..100_000)
+            .map(|_| {
+                state = state
+                    .wrapping_mul(6364136223846793005)
+                    .wrapping_add(1442695040888963407);
+                let uniform = (state >> 11) as f64 / (1u64 << 53) as f64;
+                0.5 + 80.0 * uniform * uniform
+            })
+            .collect();
+        ranges
+            .iter()
+            .for_each(|&range| estimator.push(Length::new::<meter>(range)));
+        assert_eq!(estimator.count(), ranges.len());
+
+        ranges.sort_by(|lhs, rhs| lhs.partial_cmp(rhs).unwrap());
+        [0.0, 0.01, 0.05, 0.25, 0.5, 0.75, 0.95, 0.99, 1.0]
+            .iter()
+            .for_each(|&q| {
+                let index = ((ranges.len() - 1) as f64 * q).round() as usize;
+                let expect = ranges[index];
+                let estimate = estimator.quantile(q).get::<meter>();
+                assert!(
+                    (estimate - expect).abs() < 0.1,
+                    "q={} expect {} but get {}",
+                    q,
+                    expect,
+                    estimate
+                );
+            });
+
+        // the number of centroids is bounded
+        assert!(estimator.centroids.len() < 200);
+
+        Ok(())
+    }
+}
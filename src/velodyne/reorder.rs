@@ -0,0 +1,111 @@
+//! Reordering of packets received out of order.
+
+use super::{consts::TIMESTAMP_PERIOD, packet::DataPacket};
+use crate::common::*;
+
+/// Restores the timestamp order of packets within a window before they are fed to
+/// frame converters, since Velodyne packets have no sequence numbers.
+///
+/// It holds up to `window` packets, and emits the earliest one when another packet
+/// arrives. The wrap-around of timestamps at the top of hour is respected. A packet delayed
+/// by more than the window cannot be reordered, and it is emitted as soon as possible.
+#[derive(Debug, Clone)]
+pub struct PacketReorderer {
+    window: usize,
+    buffer: Vec<DataPacket>,
+}
+
+impl PacketReorderer {
+    /// Creates a reorderer holding up to `window` packets. The packets pass through
+    /// if `window` is zero.
+    pub fn new(window: usize) -> Self {
+        Self {
+            window,
+            buffer: Vec::with_capacity(window + 1),
+        }
+    }
+
+    /// Pushes a packet, and returns the earliest packet if the window is full.
+    pub fn push<P>(&mut self, packet: P) -> Option<DataPacket>
+    where
+        P: Borrow<DataPacket>,
+    {
+        self.buffer.push(*packet.borrow());
+        if self.buffer.len() > self.window {
+            self.pop_earliest()
+        } else {
+            None
+        }
+    }
+
+    /// Takes the remaining packets in timestamp order, which is typically called at end of input.
+    pub fn finish(&mut self) -> Vec<DataPacket> {
+        let mut packets = Vec::with_capacity(self.buffer.len());
+        while let Some(packet) = self.pop_earliest() {
+            packets.push(packet);
+        }
+        packets
+    }
+
+    /// Returns the number of held packets.
+    pub fn len(&self) -> usize {
+        self.buffer.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.buffer.is_empty()
+    }
+
+    fn pop_earliest(&mut self) -> Option<DataPacket> {
+        let (index, _) = self
+            .buffer
+            .iter()
+            .enumerate()
+            .min_by(|(_, lhs), (_, rhs)| Self::time_diff(lhs.timestamp, rhs.timestamp).cmp(&0))?;
+        Some(self.buffer.remove(index))
+    }
+
+    /// Computes `lhs - rhs` in microseconds, wrapped into [-half hour, half hour).
+    fn time_diff(lhs: u32, rhs: u32) -> i64 {
+        (lhs as i64 - rhs as i64 + TIMESTAMP_PERIOD / 2).rem_euclid(TIMESTAMP_PERIOD)
+            - TIMESTAMP_PERIOD / 2
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::velodyne::test_support::PacketBuilder;
+
+    fn new_packet(timestamp: u32) -> DataPacket {
+        PacketBuilder::new()
+            .timestamp(timestamp)
+            .channels(500, 0)
+            .build()
+    }
+
+    #[test]
+    fn packet_reorderer_test() {
+        let mut reorderer = PacketReorderer::new(2);
+
+        // the second and third packets are swapped, and the timestamp wraps around
+        let timestamps = [3_599_999_000, 500, 100, 900, 1300];
+        let mut output: Vec<u32> = timestamps
+            .iter()
+            .filter_map(|&timestamp| reorderer.push(new_packet(timestamp)))
+            .map(|packet| packet.timestamp)
+            .collect();
+        assert_eq!(reorderer.len(), 2);
+        output.extend(reorderer.finish().iter().map(|packet| packet.timestamp));
+        assert_eq!(output, vec![3_599_999_000, 100, 500, 900, 1300]);
+        assert!(reorderer.is_empty());
+
+        // packets pass through with zero window
+        let mut reorderer = PacketReorderer::new(0);
+        let timestamp = reorderer.push(new_packet(500)).unwrap().timestamp;
+        assert_eq!(timestamp, 500);
+        let timestamp = reorderer.push(new_packet(100)).unwrap().timestamp;
+        assert_eq!(timestamp, 100);
+        assert!(reorderer.finish().is_empty());
+    }
+}
@@ -0,0 +1,35 @@
+//! Temperature-based range correction.
+
+use crate::common::*;
+
+/// A linear range correction depending on sensor temperature.
+///
+/// The range offset is `range_per_degree * (temperature - reference_temperature)`,
+/// which is added to the distance of every return along the line of sight.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TemperatureCorrection {
+    /// The temperature at which the ranges need no correction.
+    pub reference_temperature: ThermodynamicTemperature,
+    /// The range offset per degree Celsius.
+    pub range_per_degree: Length,
+    /// The current sensor temperature.
+    pub temperature: ThermodynamicTemperature,
+}
+
+impl TemperatureCorrection {
+    /// Creates a correction with the current temperature set to the reference temperature.
+    pub fn new(reference_temperature: ThermodynamicTemperature, range_per_degree: Length) -> Self {
+        Self {
+            reference_temperature,
+            range_per_degree,
+            temperature: reference_temperature,
+        }
+    }
+
+    /// Returns the range offset at current temperature.
+    pub fn range_offset(&self) -> Length {
+        let delta = self.temperature.get::<degree_celsius>()
+            - self.reference_temperature.get::<degree_celsius>();
+        self.range_per_degree * delta
+    }
+}
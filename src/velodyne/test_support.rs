@@ -0,0 +1,175 @@
+//! Builders of packets and points shared by unit tests.
+
+use super::{
+    consts::{BLOCKS_PER_PACKET, CHANNELS_PER_BLOCK},
+    packet::{Block, BlockIdentifier, Channel, DataPacket, ProductID, ReturnMode},
+    point::{LidarFrameEntry, PointData, ReturnKind, SingleReturnPoint},
+};
+use crate::common::*;
+
+/// Builds a [DataPacket] with uniform blocks.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct PacketBuilder {
+    packet: DataPacket,
+}
+
+impl PacketBuilder {
+    /// Starts from a VLP-16 strongest return packet at time zero, where all blocks are
+    /// upper blocks at zero azimuth and all channels have no return.
+    pub fn new() -> Self {
+        Self {
+            packet: DataPacket {
+                blocks: [Block {
+                    block_identifier: BlockIdentifier::Block0To31,
+                    azimuth_count: 0,
+                    channels: [Channel {
+                        distance: 0,
+                        intensity: 0,
+                    }; CHANNELS_PER_BLOCK],
+                }; BLOCKS_PER_PACKET],
+                timestamp: 0,
+                return_mode: ReturnMode::StrongestReturn,
+                product_id: ProductID::VLP16,
+            },
+        }
+    }
+
+    pub fn product_id(mut self, product_id: ProductID) -> Self {
+        self.packet.product_id = product_id;
+        self
+    }
+
+    pub fn return_mode(mut self, return_mode: ReturnMode) -> Self {
+        self.packet.return_mode = return_mode;
+        self
+    }
+
+    pub fn timestamp(mut self, timestamp: u32) -> Self {
+        self.packet.timestamp = timestamp;
+        self
+    }
+
+    /// Sets the same raw distance and intensity to all channels.
+    pub fn channels(mut self, distance: u16, intensity: u8) -> Self {
+        self.packet
+            .blocks
+            .iter_mut()
+            .flat_map(|block| block.channels.iter_mut())
+            .for_each(|channel| {
+                *channel = Channel {
+                    distance,
+                    intensity,
+                }
+            });
+        self
+    }
+
+    /// Advances the azimuth count of blocks by `step` from `start`, wrapping around at
+    /// 36000 counts.
+    pub fn azimuth_counts(mut self, start: usize, step: usize) -> Self {
+        self.packet
+            .blocks
+            .iter_mut()
+            .enumerate()
+            .for_each(|(idx, block)| block.azimuth_count = ((start + idx * step) % 36000) as u16);
+        self
+    }
+
+    /// Alternates the upper and lower blocks, which is the layout of 64-laser sensors.
+    pub fn alternate_blocks(mut self) -> Self {
+        self.packet
+            .blocks
+            .iter_mut()
+            .skip(1)
+            .step_by(2)
+            .for_each(|block| block.block_identifier = BlockIdentifier::Block32To63);
+        self
+    }
+
+    pub fn build(self) -> DataPacket {
+        self.packet
+    }
+}
+
+/// Builds a [SingleReturnPoint].
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct PointBuilder {
+    point: SingleReturnPoint,
+}
+
+impl PointBuilder {
+    /// Starts from a strongest return of laser 0 at the origin and at time zero.
+    pub fn new() -> Self {
+        Self {
+            point: SingleReturnPoint {
+                laser_id: 0,
+                timestamp: Time::new::<second>(0.0),
+                firing_time_offset: Time::new::<second>(0.0),
+                original_azimuth_angle: Angle::new::<radian>(0.0),
+                corrected_azimuth_angle: Angle::new::<radian>(0.0),
+                data: PointData {
+                    distance: Length::new::<meter>(0.0),
+                    intensity: 0,
+                    intensity_value: 0.0,
+                    position: [Length::new::<meter>(0.0); 3],
+                    suspect: false,
+                },
+                lidar_frame_entry: LidarFrameEntry {
+                    row_idx: 0,
+                    col_idx: 0,
+                },
+                return_kind: ReturnKind::Strongest,
+            },
+        }
+    }
+
+    pub fn laser_id(mut self, laser_id: u32) -> Self {
+        self.point.laser_id = laser_id;
+        self
+    }
+
+    pub fn timestamp(mut self, timestamp: Time) -> Self {
+        self.point.timestamp = timestamp;
+        self
+    }
+
+    /// Sets both the original and corrected azimuth angles.
+    pub fn azimuth(mut self, azimuth: Angle) -> Self {
+        self.point.original_azimuth_angle = azimuth;
+        self.point.corrected_azimuth_angle = azimuth;
+        self
+    }
+
+    /// Sets the position in meters, and the distance to its norm.
+    pub fn position(mut self, [x, y, z]: [f64; 3]) -> Self {
+        self.point.data.position = [
+            Length::new::<meter>(x),
+            Length::new::<meter>(y),
+            Length::new::<meter>(z),
+        ];
+        self.point.data.distance = Length::new::<meter>((x * x + y * y + z * z).sqrt());
+        self
+    }
+
+    /// Overrides the distance without moving the point.
+    pub fn distance(mut self, distance: Length) -> Self {
+        self.point.data.distance = distance;
+        self
+    }
+
+    /// Sets both the intensity byte and value.
+    pub fn intensity(mut self, intensity: u8) -> Self {
+        self.point.data.intensity = intensity;
+        self.point.data.intensity_value = intensity as f64;
+        self
+    }
+
+    pub fn entry(mut self, row_idx: usize, col_idx: usize) -> Self {
+        self.point.lidar_frame_entry = LidarFrameEntry { row_idx, col_idx };
+        self
+    }
+
+    pub fn build(self) -> SingleReturnPoint {
+        self.point
+    }
+}
@@ -103,3 +103,26 @@ fn ouster_frame_converter() -> Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn ouster_metadata_schemas() -> Result<()> {
+    let flat = Config::from_metadata_json_str(include_str!("../test_files/ouster_example.json"))?;
+    let nested =
+        Config::from_metadata_json_str(include_str!("../test_files/ouster_example_nested.json"))?;
+    assert_eq!(flat, nested);
+    assert_eq!(flat, Config::from_path("test_files/ouster_example.json")?);
+    assert!(!nested.reflectivity_calibrated);
+
+    // the calibration status is read from the nested schema
+    let calibrated = include_str!("../test_files/ouster_example_nested.json").replacen(
+        '{',
+        r#"{"calibration_status": {"reflectivity": {"valid": true}},"#,
+        1,
+    );
+    assert!(Config::from_metadata_json_str(&calibrated)?.reflectivity_calibrated);
+
+    let err = Config::from_metadata_json_str(r#"{"beam_intrinsics": {}}"#).unwrap_err();
+    assert!(err.to_string().contains("neither the flat schema"));
+
+    Ok(())
+}
@@ -184,3 +184,490 @@ fn velodyne_vlp_32_pcap_file() -> Result<()> {
 
     Ok(())
 }
+
+#[test]
+#[cfg(feature = "pcap-file")]
+fn velodyne_vlp_16_pcap_file_reader() -> Result<()> {
+    use lidar_utils::pcap_file_reader::PcapFileReader;
+
+    let mut data_packets = vec![];
+    for frame in PcapFileReader::open("test_files/velodyne_vlp16.pcap")? {
+        let frame = frame?;
+        if frame.len() == mem::size_of::<DataPacket>() + UDP_HEADER_SIZE {
+            data_packets.push(DataPacket::from_pcap_file_packet(&frame, UDP_HEADER_SIZE)?);
+        }
+    }
+
+    // compare with packets read by libpcap
+    let mut cap = Capture::from_file("test_files/velodyne_vlp16.pcap")?;
+    cap.filter("udp", true)?;
+    let mut expect_packets = vec![];
+    while let Ok(packet) = cap.next() {
+        if packet.data.len() == mem::size_of::<DataPacket>() + UDP_HEADER_SIZE {
+            expect_packets.push(DataPacket::from_pcap(&packet)?);
+        }
+    }
+    ensure!(!data_packets.is_empty(), "no data packets found");
+    ensure!(data_packets == expect_packets, "packets mismatch");
+
+    // convert to point cloud
+    let config = Config::vlp_16_strongest_return();
+    let mut converter = Vlp16_Strongest_PcdConverter::from_config(config);
+    data_packets.iter().try_for_each(|packet| -> Result<_> {
+        converter.convert(packet)?;
+        Ok(())
+    })?;
+
+    Ok(())
+}
+
+#[test]
+#[cfg(feature = "pcap")]
+fn velodyne_vlp_16_pcap_to_frames() -> Result<()> {
+    use lidar_utils::velodyne::pcap_to_frames;
+
+    let frames = pcap_to_frames::<Vlp16_Strongest_FrameConverter, _, _, _>(
+        "test_files/velodyne_vlp16.pcap",
+        Config::vlp_16_strongest_return(),
+    )?;
+    // the fixture covers a little more than one revolution
+    let max_width = Config::vlp_16_strongest_return().columns_per_frame(600.0);
+    ensure!(
+        frames.len() == 2,
+        "expect 2 frames, but get {}",
+        frames.len()
+    );
+    ensure!(
+        frames
+            .iter()
+            .all(|frame| frame.height == 16 && frame.width > 0 && frame.width <= max_width),
+        "invalid frame size"
+    );
+    ensure!(
+        frames
+            .iter()
+            .all(|frame| frame.data.len() == frame.height * frame.width),
+        "frame size mismatch"
+    );
+
+    Ok(())
+}
+
+#[test]
+#[cfg(feature = "pcap")]
+fn velodyne_vlp_16_pcap_dir_to_frames() -> Result<()> {
+    use lidar_utils::velodyne::{pcap_dir_to_frames, pcap_to_frames};
+
+    // the fixture is split into segment_9.pcap and segment_10.pcap in the middle of a rotation
+    let frames = pcap_dir_to_frames::<Vlp16_Strongest_FrameConverter, _, _, _>(
+        "test_files/velodyne_vlp16_segments",
+        Config::vlp_16_strongest_return(),
+    )?;
+    let expect = pcap_to_frames::<Vlp16_Strongest_FrameConverter, _, _, _>(
+        "test_files/velodyne_vlp16.pcap",
+        Config::vlp_16_strongest_return(),
+    )?;
+    ensure!(
+        frames.len() == expect.len(),
+        "expect {} frames, but get {}",
+        expect.len(),
+        frames.len()
+    );
+    ensure!(
+        izip!(frames.iter(), expect.iter()).all(|(lhs, rhs)| {
+            lhs.width == rhs.width
+                && lhs.data.first().map(|point| point.timestamp)
+                    == rhs.data.first().map(|point| point.timestamp)
+        }),
+        "frames are not stitched across segments"
+    );
+
+    Ok(())
+}
+
+#[test]
+#[cfg(feature = "pcap")]
+fn velodyne_vlp_16_run_to_channel() -> Result<()> {
+    use lidar_utils::velodyne::pcap_to_frames;
+    use std::{sync::mpsc, thread};
+
+    let path = "test_files/velodyne_vlp16.pcap";
+    let mut cap = Capture::from_file(path)?;
+    cap.filter("udp", true)?;
+    let mut data_packets = vec![];
+    while let Ok(packet) = cap.next() {
+        if packet.data.len() == mem::size_of::<DataPacket>() + UDP_HEADER_SIZE {
+            data_packets.push(DataPacket::from_pcap(&packet)?);
+        }
+    }
+
+    // the producer blocks until the consumer takes the frame
+    let (sender, receiver) = mpsc::sync_channel(1);
+    let producer = thread::spawn(move || -> Result<()> {
+        let config = Config::vlp_16_strongest_return();
+        let mut converter = Vlp16_Strongest_FrameConverter::from_config(config);
+        converter.run_to_channel(data_packets, sender)
+    });
+    let received: Vec<_> = receiver.into_iter().collect();
+    producer.join().unwrap()?;
+
+    let expect = pcap_to_frames::<Vlp16_Strongest_FrameConverter, _, _, _>(
+        path,
+        Config::vlp_16_strongest_return(),
+    )?;
+    ensure!(received.len() == expect.len(), "frame count mismatch");
+    ensure!(
+        izip!(received.iter(), expect.iter()).all(|(lhs, rhs)| {
+            lhs.width == rhs.width && lhs.data[0].timestamp == rhs.data[0].timestamp
+        }),
+        "frames are not delivered in order"
+    );
+
+    Ok(())
+}
+
+#[test]
+#[cfg(feature = "pcap")]
+fn velodyne_vlp_16_raw_frame_reconvert() -> Result<()> {
+    use lidar_utils::velodyne::RawFrameConverter;
+
+    let mut cap = Capture::from_file("test_files/velodyne_vlp16.pcap")?;
+    cap.filter("udp", true)?;
+    let mut data_packets = vec![];
+    while let Ok(packet) = cap.next() {
+        if packet.data.len() == mem::size_of::<DataPacket>() + UDP_HEADER_SIZE {
+            data_packets.push(DataPacket::from_pcap(&packet)?);
+        }
+    }
+
+    let config = Config::vlp_16_strongest_return();
+    let mut frame_converter = Vlp16_Strongest_FrameConverter::from_config(config.clone());
+    let mut raw_frame_converter = RawFrameConverter::new();
+    let mut expect_frames = vec![];
+    let mut raw_frames = vec![];
+    for packet in data_packets.iter() {
+        expect_frames.extend(frame_converter.convert(packet));
+        raw_frames.extend(raw_frame_converter.convert(packet));
+    }
+    expect_frames.extend(frame_converter.finish());
+    raw_frames.extend(raw_frame_converter.finish());
+    ensure!(
+        raw_frames.len() == expect_frames.len() && raw_frames.len() >= 2,
+        "expect {} frames, but get {}",
+        expect_frames.len(),
+        raw_frames.len()
+    );
+
+    // the reconverted frames are identical to those of the frame converter
+    for (raw_frame, expect) in izip!(raw_frames.iter(), expect_frames.iter()) {
+        let frame = raw_frame.reconvert::<Vlp16_Strongest_PcdConverter, _, _, _>(config.clone())?;
+        ensure!(
+            frame.height == expect.height && frame.width == expect.width,
+            "expect {}x{} frame, but get {}x{}",
+            expect.height,
+            expect.width,
+            frame.height,
+            frame.width
+        );
+        ensure!(
+            izip!(frame.data.iter(), expect.data.iter()).all(|(lhs, rhs)| {
+                lhs.timestamp == rhs.timestamp
+                    && lhs.laser_id == rhs.laser_id
+                    && lhs.data.position == rhs.data.position
+                    && lhs.lidar_frame_entry.row_idx == rhs.lidar_frame_entry.row_idx
+                    && lhs.lidar_frame_entry.col_idx == rhs.lidar_frame_entry.col_idx
+            }),
+            "reconverted points differ from the frame converter"
+        );
+    }
+
+    Ok(())
+}
+
+#[test]
+#[cfg(feature = "pcap")]
+fn velodyne_vlp_16_frame_time_span() -> Result<()> {
+    use lidar_utils::velodyne::SingleReturnPoint;
+    use lidar_utils::velodyne::{pcap_to_frames, PcdFrame};
+    use uom::si::time::second;
+
+    let config = Config::vlp_16_strongest_return();
+    let columns_per_rev = config.columns_per_frame(600.0);
+    let frames = pcap_to_frames::<Vlp16_Strongest_FrameConverter, _, _, _>(
+        "test_files/velodyne_vlp16.pcap",
+        config,
+    )?;
+    ensure!(!frames.is_empty(), "no frames are produced");
+
+    for frame in frames.iter() {
+        let (start, end) = frame.time_span().unwrap();
+        ensure!(start < end, "frame start time is not before end time");
+
+        // a full revolution takes 0.1s at 600 RPM
+        let duration = (end - start).get::<second>();
+        let expect = 0.1 * frame.width as f64 / columns_per_rev as f64;
+        ensure!(
+            (duration - expect).abs() < expect * 0.05,
+            "expect duration {}s, but get {}s",
+            expect,
+            duration
+        );
+    }
+
+    let empty_frame = PcdFrame::<SingleReturnPoint>::new();
+    ensure!(
+        empty_frame.time_span().is_none(),
+        "empty frame has no time span"
+    );
+
+    Ok(())
+}
+
+#[test]
+#[cfg(feature = "pcap")]
+fn velodyne_vlp_16_frame_iter_flush() -> Result<()> {
+    use lidar_utils::velodyne::FrameIter;
+
+    let mut cap = Capture::from_file("test_files/velodyne_vlp16.pcap")?;
+    cap.filter("udp", true)?;
+    let mut data_packets = vec![];
+    while let Ok(packet) = cap.next() {
+        if packet.data.len() == mem::size_of::<DataPacket>() + UDP_HEADER_SIZE {
+            data_packets.push(DataPacket::from_pcap(&packet)?);
+        }
+    }
+
+    // cut the capture at a few packets after the first wrap, which ends mid-rotation
+    let wrap_idx = data_packets
+        .windows(2)
+        .position(|pair| pair[1].blocks[0].azimuth_count < pair[0].blocks[0].azimuth_count)
+        .unwrap()
+        + 1;
+    let packets = &data_packets[..(wrap_idx + 10)];
+
+    let converter = Vlp16_Strongest_FrameConverter::from_config(Config::vlp_16_strongest_return());
+    let frames: Vec<_> = FrameIter::new(converter, packets).collect();
+    ensure!(
+        frames.len() == 2,
+        "expect 2 frames, but get {}",
+        frames.len()
+    );
+
+    // the trailing frame covers the packets after the wrap
+    let trailing = frames.last().unwrap();
+    ensure!(
+        trailing.width > 0 && trailing.height == 16,
+        "invalid trailing frame"
+    );
+    let last_timestamp = trailing.data.last().unwrap().timestamp;
+    let first_timestamp = trailing.data[0].timestamp;
+    ensure!(
+        first_timestamp.value * 1e6 >= packets[wrap_idx - 1].timestamp as f64,
+        "the trailing frame starts before the wrap"
+    );
+    ensure!(
+        last_timestamp.value * 1e6 >= packets.last().unwrap().timestamp as f64,
+        "the trailing frame does not reach the end of capture"
+    );
+
+    Ok(())
+}
+
+#[test]
+#[cfg(feature = "pcap")]
+fn velodyne_vlp_16_frames_over() -> Result<()> {
+    use lidar_utils::velodyne::frames_over;
+
+    let mut cap = Capture::from_file("test_files/velodyne_vlp16.pcap")?;
+    cap.filter("udp", true)?;
+    let mut data_packets = vec![];
+    while let Ok(packet) = cap.next() {
+        if packet.data.len() == mem::size_of::<DataPacket>() + UDP_HEADER_SIZE {
+            data_packets.push(DataPacket::from_pcap(&packet)?);
+        }
+    }
+
+    // the frames of pushing packets one by one, and flushing at the end
+    let config = Config::vlp_16_strongest_return();
+    let mut converter = Vlp16_Strongest_FrameConverter::from_config(config.clone());
+    let mut expect_frames: Vec<_> = data_packets
+        .iter()
+        .filter_map(|packet| converter.convert(packet))
+        .collect();
+    expect_frames.extend(converter.finish());
+
+    // the frames own their points after the packets are dropped
+    let frames: Vec<_> =
+        frames_over::<Vlp16_Strongest_FrameConverter, _, _>(&data_packets, &config).collect();
+    let num_packets = data_packets.len();
+    drop(data_packets);
+
+    ensure!(
+        frames.len() == expect_frames.len() && frames.len() >= 2,
+        "expect {} frames, but get {}",
+        expect_frames.len(),
+        frames.len()
+    );
+    ensure!(
+        izip!(frames.iter(), expect_frames.iter()).all(|(lhs, rhs)| {
+            lhs.width == rhs.width
+                && lhs.height == rhs.height
+                && izip!(lhs.data.iter(), rhs.data.iter()).all(|(lhs, rhs)| {
+                    lhs.timestamp == rhs.timestamp && lhs.data.position == rhs.data.position
+                })
+        }),
+        "frames differ from the frame converter"
+    );
+
+    // the trailing partial frame is flushed, and only the last firings are left undecoded
+    let num_points: usize = frames.iter().map(|frame| frame.data.len()).sum();
+    let max_points = num_packets * consts::BLOCKS_PER_PACKET * consts::CHANNELS_PER_BLOCK;
+    ensure!(
+        num_points <= max_points && num_points >= max_points - 2 * consts::CHANNELS_PER_BLOCK,
+        "expect about {} points, but get {}",
+        max_points,
+        num_points
+    );
+
+    Ok(())
+}
+
+#[test]
+#[cfg(feature = "pcap")]
+fn velodyne_detect_model_from_pcap() -> Result<()> {
+    use lidar_utils::velodyne::{detect_model_from_pcap, DynamicModel};
+
+    let model = detect_model_from_pcap("test_files/velodyne_vlp16.pcap")?;
+    ensure!(
+        matches!(model, DynamicModel::Vlp16),
+        "expect VLP-16, but get {:?}",
+        model
+    );
+
+    // the capture is from HDL-32E, which reports the same product ID as the VLP-16 capture
+    ensure!(
+        detect_model_from_pcap("test_files/velodyne_vlp32.pcap").is_err(),
+        "HDL-32E capture is not rejected"
+    );
+
+    // the capture has no Velodyne data packets
+    ensure!(
+        detect_model_from_pcap("test_files/ouster_example.pcap").is_err(),
+        "Ouster capture is not rejected"
+    );
+
+    Ok(())
+}
+
+#[test]
+#[cfg(feature = "pcap")]
+fn velodyne_config_from_packet() -> Result<()> {
+    use lidar_utils::velodyne::{DynamicModel, ProductID, Vlp16_Dynamic_Config};
+
+    let load_packets = |path: &str| -> Result<Vec<DataPacket>> {
+        let mut cap = Capture::from_file(path)?;
+        cap.filter("udp", true)?;
+        let mut packets = vec![];
+        while let Ok(packet) = cap.next() {
+            if packet.data.len() == mem::size_of::<DataPacket>() + UDP_HEADER_SIZE {
+                packets.push(DataPacket::from_pcap(&packet)?);
+            }
+        }
+        Ok(packets)
+    };
+
+    // both captures report the byte 0x21, which is resolved by the firing layout
+    let packets = load_packets("test_files/velodyne_vlp16.pcap")?;
+    ensure!(!packets.is_empty(), "no data packets");
+    for packet in packets.iter() {
+        ensure!(
+            packet.product_id == ProductID::HDL32E,
+            "unexpected product ID"
+        );
+        ensure!(
+            matches!(packet.model(), Some(DynamicModel::Vlp16)),
+            "expect VLP-16, but get {:?}",
+            packet.model()
+        );
+        Vlp16_Dynamic_Config::from_packet(packet)?;
+    }
+
+    let packets = load_packets("test_files/velodyne_vlp32.pcap")?;
+    ensure!(!packets.is_empty(), "no data packets");
+    for packet in packets.iter() {
+        ensure!(
+            packet.product_id == ProductID::HDL32E,
+            "unexpected product ID"
+        );
+        ensure!(packet.model().is_none(), "HDL-32E packet is not rejected");
+        ensure!(
+            Vlp16_Dynamic_Config::from_packet(packet).is_err(),
+            "HDL-32E packet is not rejected"
+        );
+    }
+
+    Ok(())
+}
+
+#[test]
+#[cfg(feature = "pcap")]
+fn velodyne_vlp_16_split_pcap_by_frame() -> Result<()> {
+    use lidar_utils::velodyne::{pcap_to_frames, split_pcap_by_frame};
+
+    let output_dir = std::env::temp_dir().join("lidar_utils_split_pcap_by_frame");
+    let _ = std::fs::remove_dir_all(&output_dir);
+
+    // the fixture passes the zero azimuth once
+    let paths = split_pcap_by_frame::<Vlp16_Strongest_FrameConverter, _, _, _, _>(
+        "test_files/velodyne_vlp16.pcap",
+        &output_dir,
+        Config::vlp_16_strongest_return(),
+    )?;
+    ensure!(paths.len() == 2, "expect 2 files, but get {}", paths.len());
+
+    // the files follow the frames of the converter
+    let frames = pcap_to_frames::<Vlp16_Strongest_FrameConverter, _, _, _>(
+        "test_files/velodyne_vlp16.pcap",
+        Config::vlp_16_strongest_return(),
+    )?;
+    ensure!(
+        frames.len() == paths.len(),
+        "expect {} files, but get {}",
+        frames.len(),
+        paths.len()
+    );
+
+    // all packets are kept, including the position packets
+    let count_packets = |path: &std::path::Path| -> Result<(usize, usize)> {
+        let mut cap = Capture::from_file(path)?;
+        let (mut num_packets, mut num_data_packets) = (0, 0);
+        while let Ok(packet) = cap.next() {
+            num_packets += 1;
+            if packet.data.len() == mem::size_of::<DataPacket>() + UDP_HEADER_SIZE {
+                num_data_packets += 1;
+            }
+        }
+        Ok((num_packets, num_data_packets))
+    };
+    let (mut num_packets, mut num_data_packets) = (0, 0);
+    for path in paths.iter() {
+        ensure!(path.is_file(), "{} is not written", path.display());
+        let (packets, data_packets) = count_packets(path)?;
+        num_packets += packets;
+        num_data_packets += data_packets;
+    }
+    let expect = count_packets(std::path::Path::new("test_files/velodyne_vlp16.pcap"))?;
+    ensure!(
+        (num_packets, num_data_packets) == expect,
+        "expect {:?} packets and data packets, but get {:?}",
+        expect,
+        (num_packets, num_data_packets)
+    );
+    ensure!(
+        num_packets > num_data_packets,
+        "the fixture has no packets other than data packets"
+    );
+
+    std::fs::remove_dir_all(&output_dir)?;
+    Ok(())
+}